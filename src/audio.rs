@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+pub struct AudioSettings {
+    pub sfx_volume: f32,
+    /// Caps how many shot sounds can start in a single frame so volleys don't clip.
+    pub max_shots_per_frame: u32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            sfx_volume: 0.5,
+            max_shots_per_frame: 3,
+        }
+    }
+}
+
+pub struct MusicSettings {
+    pub volume: f32,
+    pub enabled: bool,
+}
+
+impl Default for MusicSettings {
+    fn default() -> Self {
+        Self {
+            volume: 0.4,
+            enabled: true,
+        }
+    }
+}
+
+/// The currently playing music track, if any. Only one instance is ever started.
+#[derive(Default)]
+pub struct MusicPlayback {
+    pub sink: Option<Handle<AudioSink>>,
+}
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioSettings>()
+            .init_resource::<MusicSettings>()
+            .init_resource::<MusicPlayback>()
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(start_music))
+            .add_system_set(SystemSet::on_enter(GameState::Next).with_system(start_music))
+            .add_system_set(SystemSet::on_enter(GameState::Paused).with_system(pause_music))
+            .add_system_set(SystemSet::on_exit(GameState::Paused).with_system(resume_music))
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(stop_music))
+            .add_system_set(SystemSet::on_enter(GameState::Victory).with_system(stop_music))
+            .add_system_set(SystemSet::on_update(GameState::Next).with_system(play_shot_sounds))
+            .add_system(toggle_music_mute)
+            .add_system(apply_music_settings);
+    }
+}
+
+fn play_shot_sounds(
+    mut fired_events: EventReader<TowerFired>,
+    audio: Res<Audio>,
+    assets: Res<GameAssets>,
+    settings: Res<AudioSettings>,
+) {
+    for _ in fired_events
+        .iter()
+        .take(settings.max_shots_per_frame as usize)
+    {
+        audio.play_with_settings(
+            assets.shoot_sfx.clone(),
+            PlaybackSettings::ONCE.with_volume(settings.sfx_volume),
+        );
+    }
+}
+
+fn start_music(
+    mut playback: ResMut<MusicPlayback>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    assets: Res<GameAssets>,
+    settings: Res<MusicSettings>,
+) {
+    if playback.sink.is_some() {
+        return;
+    }
+
+    let sink = audio.play_with_settings(
+        assets.music.clone(),
+        PlaybackSettings::LOOP.with_volume(settings.volume),
+    );
+    playback.sink = Some(audio_sinks.get_handle(sink));
+}
+
+fn pause_music(playback: Res<MusicPlayback>, audio_sinks: Res<Assets<AudioSink>>) {
+    if let Some(sink) = playback
+        .sink
+        .as_ref()
+        .and_then(|sink| audio_sinks.get(sink))
+    {
+        sink.pause();
+    }
+}
+
+fn resume_music(playback: Res<MusicPlayback>, audio_sinks: Res<Assets<AudioSink>>) {
+    if let Some(sink) = playback
+        .sink
+        .as_ref()
+        .and_then(|sink| audio_sinks.get(sink))
+    {
+        sink.play();
+    }
+}
+
+fn stop_music(mut playback: ResMut<MusicPlayback>, audio_sinks: Res<Assets<AudioSink>>) {
+    if let Some(sink) = playback.sink.take().and_then(|sink| audio_sinks.get(&sink)) {
+        sink.stop();
+    }
+}
+
+fn toggle_music_mute(
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut settings: ResMut<MusicSettings>,
+) {
+    if bindings.is_action_just_pressed(&keyboard, Action::MuteMusic) {
+        settings.enabled = !settings.enabled;
+    }
+}
+
+fn apply_music_settings(
+    playback: Res<MusicPlayback>,
+    settings: Res<MusicSettings>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    if let Some(sink) = playback
+        .sink
+        .as_ref()
+        .and_then(|sink| audio_sinks.get(sink))
+    {
+        sink.set_volume(if settings.enabled {
+            settings.volume
+        } else {
+            0.0
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::AssetPlugin;
+    use bevy_asset_loader::prelude::AssetCollection;
+
+    /// `GameAssets` as the loading state would build it, along with the asset server that
+    /// loaded it.
+    fn load_game_assets() -> (App, GameAssets) {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugin(AssetPlugin);
+
+        GameAssets::load(&mut app.world);
+        let assets = GameAssets::create(&mut app.world);
+
+        (app, assets)
+    }
+
+    fn asset_path(app: &App, handle: &Handle<AudioSource>) -> Option<String> {
+        app.world
+            .resource::<AssetServer>()
+            .get_handle_path(handle)
+            .map(|path| path.path().to_string_lossy().into_owned())
+    }
+
+    #[test]
+    fn shot_sound_handle_is_populated() {
+        let (app, assets) = load_game_assets();
+
+        assert_ne!(assets.shoot_sfx, Handle::default());
+        assert_eq!(
+            asset_path(&app, &assets.shoot_sfx).as_deref(),
+            Some("audio/shoot.wav")
+        );
+    }
+
+    #[test]
+    fn music_handle_loads_and_its_settings_exist() {
+        let (app, assets) = load_game_assets();
+
+        assert_ne!(assets.music, Handle::default());
+        assert_eq!(
+            asset_path(&app, &assets.music).as_deref(),
+            Some("audio/music.wav")
+        );
+
+        let mut app = App::new();
+        app.add_plugin(GameAudioPlugin);
+        let settings = app.world.resource::<MusicSettings>();
+        assert!(settings.enabled);
+        assert!(settings.volume > 0.0 && settings.volume <= 1.0);
+    }
+}
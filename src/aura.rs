@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+use std::time::Duration;
+
+use crate::*;
+
+/// Makes a tower boost every other tower within its range instead of shooting. Bonuses are
+/// fractions, so 0.2 is +20%.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct Aura {
+    pub fire_rate: f32,
+    pub damage: f32,
+}
+
+/// The combined bonus a tower gets from the auras it stands in, kept up to date every step by
+/// `buff_apply`. The tower's own stats are never changed; shooting systems scale them as they
+/// fire.
+#[derive(Reflect, Component, Default, Clone, Copy, PartialEq, Debug)]
+#[reflect(Component)]
+pub struct Buffed {
+    pub fire_rate: f32,
+    pub damage: f32,
+}
+
+impl Buffed {
+    pub fn damage(&self, base: i32) -> i32 {
+        (base as f32 * (1.0 + self.damage)).round() as i32
+    }
+
+    /// How far a cooldown timer should tick over `delta`.
+    pub fn cooldown_delta(&self, delta: Duration) -> Duration {
+        delta.mul_f32(1.0 + self.fire_rate)
+    }
+}
+
+/// The most overlapping auras can add up to, so stacking buff towers has diminishing returns.
+pub struct AuraSettings {
+    pub max_fire_rate: f32,
+    pub max_damage: f32,
+}
+
+impl Default for AuraSettings {
+    fn default() -> Self {
+        Self {
+            max_fire_rate: 0.4,
+            max_damage: 0.4,
+        }
+    }
+}
+
+pub struct AuraPlugin;
+
+impl Plugin for AuraPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Aura>()
+            .register_type::<Buffed>()
+            .init_resource::<AuraSettings>()
+            .add_system_to_stage(GameStep, buff_apply);
+    }
+}
+
+/// Sums the auras covering each tower, capped by `AuraSettings`. Aura towers don't buff each
+/// other or themselves, and a tower that has left every aura loses its `Buffed`.
+fn buff_apply(
+    mut commands: Commands,
+    auras: Query<(&Tower, &Aura, &GlobalTransform)>,
+    mut towers: Query<
+        (Entity, &GlobalTransform, Option<&mut Buffed>),
+        (With<Tower>, Without<Aura>),
+    >,
+    settings: Res<AuraSettings>,
+) {
+    for (entity, transform, buffed) in &mut towers {
+        let position = transform.translation();
+        let mut total = Buffed::default();
+
+        for (aura_tower, aura, aura_transform) in &auras {
+            if Vec3::distance(aura_transform.translation(), position) <= aura_tower.range {
+                total.fire_rate += aura.fire_rate;
+                total.damage += aura.damage;
+            }
+        }
+
+        total.fire_rate = total.fire_rate.min(settings.max_fire_rate);
+        total.damage = total.damage.min(settings.max_damage);
+
+        match buffed {
+            Some(mut buffed) if total != Buffed::default() => {
+                if *buffed != total {
+                    *buffed = total;
+                }
+            }
+            Some(_) => {
+                commands.entity(entity).remove::<Buffed>();
+            }
+            None if total != Buffed::default() => {
+                commands.entity(entity).insert(total);
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn towers_in_an_aura_are_buffed_up_to_the_cap() {
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+        let assets = GameAssets::headless();
+        let mut app = App::new();
+        app.init_resource::<AuraSettings>().add_system(buff_apply);
+        let spawn = |app: &mut App, tower_type: TowerType, x: f32| {
+            app.world
+                .spawn()
+                .insert(Tower::new(tower_type, &config, &assets))
+                .insert(GlobalTransform::from_translation(Vec3::X * x))
+                .id()
+        };
+        let inside = spawn(&mut app, TowerType::Cannon, 1.0);
+        let outside = spawn(&mut app, TowerType::Cannon, 50.0);
+        for _ in 0..3 {
+            let aura = spawn(&mut app, TowerType::Buff, 0.0);
+            app.world.entity_mut(aura).insert(Aura {
+                fire_rate: 0.2,
+                damage: 0.1,
+            });
+        }
+
+        app.update();
+
+        // Three auras would add up to +60% fire rate, past the 40% cap.
+        let buffed = *app.world.get::<Buffed>(inside).unwrap();
+        assert_eq!(buffed.fire_rate, 0.4);
+        assert!((buffed.damage - 0.3).abs() < 1e-6);
+        assert!(app.world.get::<Buffed>(outside).is_none());
+    }
+}
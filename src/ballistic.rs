@@ -0,0 +1,343 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// A shell lobbed on an arc instead of flying straight. It explodes when it comes back down
+/// to the ground plane, no matter what it passes over on the way.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct Ballistic {
+    pub velocity: Vec3,
+    pub gravity: f32,
+    pub damage: i32,
+    pub splash_radius: f32,
+    #[reflect(ignore)]
+    pub damage_type: DamageType,
+    /// Splashes flying enemies too. Only shells from towers with `CanTargetAir` do.
+    pub anti_air: bool,
+}
+
+/// Turns a tower into a mortar that fires `Ballistic` shells at where its target will be.
+#[derive(Reflect, Component)]
+#[reflect(Component)]
+pub struct Mortar {
+    pub flight_time: f32,
+    pub gravity: f32,
+    pub splash_radius: f32,
+}
+
+impl Default for Mortar {
+    fn default() -> Self {
+        Self {
+            flight_time: 1.2,
+            gravity: 9.8,
+            splash_radius: 1.5,
+        }
+    }
+}
+
+/// Makes a shell burst into `submunitions` smaller shells when it lands, flung out in a ring
+/// about `spread` across that each splash for `child_damage`.
+#[derive(Reflect, Component, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct ClusterBomb {
+    pub submunitions: u32,
+    pub child_damage: i32,
+    pub spread: f32,
+}
+
+impl ClusterBomb {
+    /// Seconds each bomblet spends in the air.
+    pub const CHILD_FLIGHT_TIME: f32 = 0.5;
+
+    /// Where each bomblet comes down around `impact`, evenly spaced round the ring from
+    /// `angle` radians.
+    pub fn landings(&self, impact: Vec3, angle: f32) -> Vec<Vec3> {
+        let step = std::f32::consts::TAU / self.submunitions.max(1) as f32;
+
+        (0..self.submunitions)
+            .map(|index| {
+                let rotation = Quat::from_rotation_y(angle + step * index as f32);
+                impact + rotation * Vec3::X * self.spread
+            })
+            .collect()
+    }
+}
+
+/// The launch velocity that carries a shell from `from` to `to` in exactly `flight_time`
+/// seconds under `gravity`.
+pub fn launch_velocity(from: Vec3, to: Vec3, flight_time: f32, gravity: f32) -> Vec3 {
+    (to - from) / flight_time + Vec3::Y * 0.5 * gravity * flight_time
+}
+
+/// Sent when a shell lands, whether or not it hit anything.
+pub struct Explosion {
+    pub position: Vec3,
+    pub radius: f32,
+}
+
+pub struct BallisticPlugin;
+
+impl Plugin for BallisticPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Ballistic>()
+            .register_type::<Mortar>()
+            .register_type::<ClusterBomb>()
+            .add_event::<Explosion>()
+            .add_system_set_to_stage(
+                GameStep,
+                SystemSet::new()
+                    .with_system(move_ballistic)
+                    .with_system(ballistic_impact.after(move_ballistic)),
+            );
+    }
+}
+
+pub fn spawn_shell(
+    commands: &mut Commands,
+    scene: &Handle<Scene>,
+    position: Vec3,
+    shell: Ballistic,
+) -> Entity {
+    commands
+        .spawn_bundle(SceneBundle {
+            scene: scene.clone(),
+            transform: Transform::from_translation(position),
+            ..default()
+        })
+        .insert(shell)
+        .insert(Interpolated::new(position))
+        .insert(Gameplay)
+        .insert(Name::new("Shell"))
+        .id()
+}
+
+fn move_ballistic(mut shells: Query<(&mut Ballistic, &mut Transform)>, time: Res<GameTime>) {
+    let dt = time.delta_seconds();
+
+    for (mut shell, mut transform) in &mut shells {
+        shell.velocity.y -= shell.gravity * dt;
+        transform.translation += shell.velocity * dt;
+    }
+}
+
+/// Cluster bombs are swapped for their bomblets as they land. The bomblets are ordinary shells
+/// at half the size and half the splash, so they can't burst again.
+fn ballistic_impact(
+    mut commands: Commands,
+    shells: Query<(Entity, &Ballistic, &Transform, Option<&ClusterBomb>)>,
+    mut targets: Query<
+        (
+            Entity,
+            &mut Health,
+            &GlobalTransform,
+            (Option<&Armor>, Option<&Resistances>),
+            Option<&mut Shield>,
+        ),
+        (With<Target>, Without<Invulnerable>),
+    >,
+    flying: Query<(), With<Flying>>,
+    mut hit_events: EventWriter<BulletHit>,
+    mut damage_events: EventWriter<DamageDealt>,
+    mut explosion_events: EventWriter<Explosion>,
+    mut rng: ResMut<GameRng>,
+    assets: Res<GameAssets>,
+) {
+    for (entity, shell, transform, cluster) in &shells {
+        if transform.translation.y > 0.0 {
+            continue;
+        }
+
+        let impact = transform.translation * Vec3::new(1.0, 0.0, 1.0);
+
+        for (target, mut health, target_transform, defenses, mut shield) in &mut targets {
+            if !shell.anti_air && flying.contains(target) {
+                continue;
+            }
+
+            let offset = target_transform.translation() - impact;
+
+            if Vec2::new(offset.x, offset.z).length() <= shell.splash_radius {
+                let amount = mitigate(shell.damage, shell.damage_type, false, defenses);
+                apply_damage(&mut health, shield.as_deref_mut(), amount);
+                damage_events.send(DamageDealt {
+                    target,
+                    amount,
+                    position: target_transform.translation(),
+                    crit: false,
+                });
+            }
+        }
+
+        hit_events.send(BulletHit {
+            bullet: entity,
+            position: impact,
+        });
+        explosion_events.send(Explosion {
+            position: impact,
+            radius: shell.splash_radius,
+        });
+        commands.entity(entity).despawn_recursive();
+
+        if let Some(cluster) = cluster {
+            // Launched from just above the ground so they don't land again straight away.
+            let launch = impact + Vec3::Y * 0.01;
+
+            for landing in cluster.landings(impact, rng.range(0.0, std::f32::consts::TAU)) {
+                let bomblet = spawn_shell(
+                    &mut commands,
+                    &assets.mortar_shell_scene,
+                    launch,
+                    Ballistic {
+                        velocity: launch_velocity(
+                            launch,
+                            landing,
+                            ClusterBomb::CHILD_FLIGHT_TIME,
+                            shell.gravity,
+                        ),
+                        gravity: shell.gravity,
+                        damage: cluster.child_damage,
+                        splash_radius: shell.splash_radius * 0.5,
+                        damage_type: shell.damage_type,
+                        anti_air: shell.anti_air,
+                    },
+                );
+                commands
+                    .entity(bomblet)
+                    .insert(Transform::from_translation(launch).with_scale(Vec3::splat(0.5)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn app() -> App {
+        let mut app = App::new();
+
+        app.insert_resource(GameAssets::headless())
+            .insert_resource(GameRng::new(0))
+            .add_event::<BulletHit>()
+            .add_event::<DamageDealt>()
+            .add_event::<Explosion>()
+            .add_system(ballistic_impact);
+
+        app
+    }
+
+    fn spawn_target(app: &mut App, position: Vec3) -> Entity {
+        app.world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .insert(Health { value: 10, max: 10 })
+            .insert(GlobalTransform::from_translation(position))
+            .id()
+    }
+
+    fn land(app: &mut App, anti_air: bool) {
+        app.world
+            .spawn()
+            .insert(Ballistic {
+                damage: 2,
+                splash_radius: 1.5,
+                anti_air,
+                ..default()
+            })
+            .insert(Transform::from_xyz(0.0, -0.01, 0.0));
+        app.update();
+    }
+
+    #[test]
+    fn shells_only_splash_flyers_when_fired_at_air() {
+        let mut app = app();
+        let walker = spawn_target(&mut app, Vec3::new(1.0, 0.0, 0.0));
+        let flyer = spawn_target(&mut app, Vec3::new(0.0, 1.5, 0.5));
+        app.world.entity_mut(flyer).insert(Flying { altitude: 1.5 });
+
+        land(&mut app, false);
+
+        assert_eq!(app.world.get::<Health>(walker).unwrap().value, 8);
+        assert_eq!(app.world.get::<Health>(flyer).unwrap().value, 10);
+
+        land(&mut app, true);
+
+        assert_eq!(app.world.get::<Health>(walker).unwrap().value, 6);
+        assert_eq!(app.world.get::<Health>(flyer).unwrap().value, 8);
+    }
+
+    #[test]
+    fn shells_launched_at_45_degrees_land_at_the_expected_range() {
+        let (speed, gravity) = (10.0, 9.8);
+        let mut app = app();
+        app.insert_resource(GameTime::stepped(Duration::from_secs_f32(1.0 / 240.0)))
+            .add_system(move_ballistic.before(ballistic_impact));
+        let direction = Vec3::new(1.0, 1.0, 0.0).normalize();
+        let shell = app
+            .world
+            .spawn()
+            .insert(Ballistic {
+                velocity: direction * speed,
+                gravity,
+                ..default()
+            })
+            .insert(Transform::default())
+            .id();
+
+        while app.world.get_entity(shell).is_some() {
+            app.update();
+        }
+
+        let explosions = app.world.resource::<Events<Explosion>>();
+        let landing = explosions
+            .get_reader()
+            .iter(explosions)
+            .last()
+            .expect("shell never landed")
+            .position;
+        let expected = speed * speed / gravity;
+        assert!(
+            (landing.x - expected).abs() < 0.1,
+            "landed at {landing} rather than {expected}"
+        );
+    }
+
+    #[test]
+    fn cluster_bombs_burst_into_their_bomblets_on_landing() {
+        let mut app = app();
+        app.world
+            .spawn()
+            .insert(Ballistic {
+                damage: 2,
+                splash_radius: 1.5,
+                gravity: 9.8,
+                ..default()
+            })
+            .insert(ClusterBomb {
+                submunitions: 5,
+                child_damage: 1,
+                spread: 2.0,
+            })
+            .insert(Transform::from_xyz(0.0, -0.01, 0.0));
+
+        app.update();
+
+        let mut shells = app
+            .world
+            .query_filtered::<&Ballistic, Without<ClusterBomb>>();
+        let bomblets: Vec<&Ballistic> = shells.iter(&app.world).collect();
+        assert_eq!(bomblets.len(), 5);
+        assert!(bomblets
+            .iter()
+            .all(|bomblet| bomblet.damage == 1 && bomblet.splash_radius == 0.75));
+        assert_eq!(
+            app.world.query::<&ClusterBomb>().iter(&app.world).count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// A boss enemy. Once its health drops below `enrage_threshold` of its maximum it speeds up by
+/// `enrage_speed` for the rest of its run.
+#[derive(Reflect, Component)]
+#[reflect(Component)]
+pub struct Boss {
+    pub enrage_threshold: f32,
+    pub enrage_speed: f32,
+    pub enraged: bool,
+}
+
+impl Default for Boss {
+    fn default() -> Self {
+        Self {
+            enrage_threshold: 0.5,
+            enrage_speed: 1.5,
+            enraged: false,
+        }
+    }
+}
+
+impl Boss {
+    /// The speed multiplier `move_targets` applies to this boss right now.
+    pub fn speed_factor(&self) -> f32 {
+        if self.enraged {
+            self.enrage_speed
+        } else {
+            1.0
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct BossAlert;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Boss>()
+            .add_system_set(SystemSet::on_update(GameState::Next).with_system(boss_alert))
+            .add_system_to_stage(GameStep, enrage_bosses.after(BulletSystem::Collision));
+    }
+}
+
+fn boss_alert(mut commands: Commands, bosses: Query<(), Added<Boss>>, assets: Res<GameAssets>) {
+    if bosses.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "A boss approaches!",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 48.0,
+                    color: Color::ORANGE_RED,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(80.0),
+                    left: Val::Percent(40.0),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(BossAlert)
+        .insert(Lifetime {
+            timer: Timer::from_seconds(3.0, false),
+        })
+        .insert(Gameplay)
+        .insert(Name::new("BossAlert"));
+}
+
+fn enrage_bosses(mut bosses: Query<(&mut Boss, &Health)>) {
+    for (mut boss, health) in &mut bosses {
+        if !boss.enraged && (health.value as f32) < health.max as f32 * boss.enrage_threshold {
+            boss.enraged = true;
+            info!("Boss enraged");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bosses_enrage_below_half_health() {
+        let mut app = App::new();
+        app.add_system(enrage_bosses);
+        let boss = app
+            .world
+            .spawn()
+            .insert(Health { value: 6, max: 10 })
+            .insert(Boss::default())
+            .id();
+
+        app.update();
+        assert_eq!(app.world.get::<Boss>(boss).unwrap().speed_factor(), 1.0);
+
+        app.world.get_mut::<Health>(boss).unwrap().value = 4;
+        app.update();
+        let boss = app.world.get::<Boss>(boss).unwrap();
+        assert!(boss.enraged);
+        assert_eq!(boss.speed_factor(), boss.enrage_speed);
+    }
+}
@@ -0,0 +1,959 @@
+use bevy::{
+    prelude::*,
+    utils::{FloatOrd, HashSet},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct Bullet {
+    pub direction: Vec3,
+    pub speed: f32,
+    pub damage: i32,
+    pub collision_radius: f32,
+    /// Damages every target within this distance of the impact. Zero hits only the struck target.
+    pub splash_radius: f32,
+    /// Speed factor applied to every target this bullet damages.
+    pub slow: Option<f32>,
+    /// Poison applied to every target this bullet damages. Zero dps means no poison.
+    pub poison_dps: f32,
+    pub poison_duration: f32,
+    /// Seconds every target this bullet damages is stunned for.
+    pub stun: Option<f32>,
+    /// Always crits, regardless of `crit_chance`.
+    pub crit: bool,
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+    /// Ignores the target's `Armor`.
+    pub true_damage: bool,
+    #[reflect(ignore)]
+    pub damage_type: DamageType,
+    /// Splash catches flying enemies too. Only bullets from towers with `CanTargetAir` do.
+    pub anti_air: bool,
+}
+
+/// What a homing bullet does once its target is gone.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum RetargetPolicy {
+    /// Stop homing and carry on in the last direction.
+    Straight,
+    /// Home in on the nearest enemy within `radius`, or fly straight if there is none.
+    Nearest { radius: f32 },
+}
+
+impl Default for RetargetPolicy {
+    fn default() -> Self {
+        Self::Straight
+    }
+}
+
+/// Steers a bullet toward `target`, turning at most `turn_rate` radians per second.
+#[derive(Reflect, Component)]
+#[reflect(Component)]
+pub struct Homing {
+    pub target: Entity,
+    pub turn_rate: f32,
+    #[reflect(ignore)]
+    pub retarget: RetargetPolicy,
+    /// Whether `RetargetPolicy::Nearest` may pick a flying enemy.
+    pub can_target_air: bool,
+}
+
+impl Bullet {
+    /// Rolls for a crit against `rng`, returning the damage before mitigation and whether it
+    /// crit. Bullets that can't crit leave the sequence untouched.
+    pub fn roll_damage(&self, rng: &mut GameRng) -> (i32, bool) {
+        let crit = self.crit || (self.crit_chance > 0.0 && rng.chance(self.crit_chance));
+
+        if crit {
+            let damage = (self.damage as f32 * self.crit_multiplier).round() as i32;
+            (damage, true)
+        } else {
+            (self.damage, false)
+        }
+    }
+}
+
+impl Default for Homing {
+    fn default() -> Self {
+        Self {
+            target: Entity::from_raw(u32::MAX),
+            turn_rate: 0.0,
+            retarget: RetargetPolicy::default(),
+            can_target_air: false,
+        }
+    }
+}
+
+/// Lets a bullet carry on through `remaining` more enemies after hitting one. Each enemy is only
+/// hit once per bullet.
+#[derive(Component, Default)]
+pub struct Pierce {
+    pub remaining: u32,
+    pub hit: HashSet<Entity>,
+}
+
+impl Pierce {
+    pub fn new(remaining: u32) -> Self {
+        Self {
+            remaining,
+            hit: HashSet::default(),
+        }
+    }
+}
+
+/// Makes a tower fire `Homing` bullets at whatever it is aiming at.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct HomingShots {
+    pub turn_rate: f32,
+    #[reflect(ignore)]
+    pub retarget: RetargetPolicy,
+}
+
+#[derive(Reflect, Component, Default)]
+pub struct Lifetime {
+    pub timer: Timer,
+}
+
+/// Shrinks a `Lifetime` entity from `base_scale` down to nothing over the last `duration` seconds
+/// before it despawns. Materials are shared between entities, so only the scale is faded.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct FadeOut {
+    pub duration: f32,
+    pub base_scale: Vec3,
+}
+
+impl FadeOut {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration,
+            base_scale: Vec3::ONE,
+        }
+    }
+
+    /// Scale factor for a lifetime timer: 1.0 until the fade window, then down to 0.0.
+    pub fn factor(&self, timer: &Timer) -> f32 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+
+        let remaining = (timer.duration() - timer.elapsed()).as_secs_f32();
+        (remaining / self.duration).clamp(0.0, 1.0)
+    }
+}
+
+/// Marks a bullet that is parked in the `BulletPool`, hidden and waiting to be fired again.
+#[derive(Component)]
+pub struct Pooled;
+
+/// Inactive bullet entities kept around so firing doesn't spawn and despawn every shot.
+#[derive(Default)]
+pub struct BulletPool {
+    pub inactive: Vec<Entity>,
+}
+
+impl BulletPool {
+    pub fn release(&mut self, commands: &mut Commands, bullet: Entity) {
+        if self.inactive.contains(&bullet) {
+            return;
+        }
+
+        commands
+            .entity(bullet)
+            .remove::<Homing>()
+            .remove::<Pierce>()
+            .remove::<AimedAt>()
+            .insert(Pooled)
+            .insert(Visibility { is_visible: false });
+        self.inactive.push(bullet);
+    }
+}
+
+pub struct BulletHit {
+    pub bullet: Entity,
+    pub position: Vec3,
+}
+
+/// Sent for every target a projectile damages, after any reductions have been applied.
+pub struct DamageDealt {
+    pub target: Entity,
+    pub amount: i32,
+    pub position: Vec3,
+    pub crit: bool,
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BulletSystem {
+    Collision,
+    Despawn,
+}
+
+pub struct BulletPlugin;
+
+impl Plugin for BulletPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Bullet>()
+            .register_type::<Homing>()
+            .register_type::<HomingShots>()
+            .register_type::<Lifetime>()
+            .register_type::<FadeOut>()
+            .init_resource::<BulletPool>()
+            .add_event::<BulletHit>()
+            .add_event::<DamageDealt>()
+            .add_system_set_to_stage(
+                GameStep,
+                SystemSet::new()
+                    .with_system(home_bullets.after(RebuildSpatialGrid).before(move_bullets))
+                    .with_system(move_bullets)
+                    .with_system(bullet_collision.label(BulletSystem::Collision))
+                    .with_system(fade_out.before(BulletSystem::Despawn))
+                    .with_system(bullet_despawn.label(BulletSystem::Despawn)),
+            );
+    }
+}
+
+/// Fires a bullet from `position`, reusing a pooled entity when one is free. The `Lifetime` is
+/// always replaced so a recycled bullet gets its full flight time back, and so is the scene,
+/// since the pool is shared by every tower type.
+pub fn spawn_bullet(
+    commands: &mut Commands,
+    pool: &mut BulletPool,
+    scene: &Handle<Scene>,
+    position: Vec3,
+    bullet: Bullet,
+    lifetime: f32,
+) -> Entity {
+    let transform = Transform::from_translation(position);
+    let lifetime = Lifetime {
+        timer: Timer::from_seconds(lifetime, false),
+    };
+
+    if let Some(entity) = pool.inactive.pop() {
+        commands
+            .entity(entity)
+            .remove::<Pooled>()
+            .insert(transform)
+            .insert(Interpolated::new(position))
+            .insert(Visibility { is_visible: true })
+            .insert(scene.clone())
+            .insert(bullet)
+            .insert(lifetime);
+        return entity;
+    }
+
+    commands
+        .spawn_bundle(SceneBundle {
+            scene: scene.clone(),
+            transform,
+            ..default()
+        })
+        .insert(lifetime)
+        .insert(bullet)
+        .insert(Interpolated::new(position))
+        .insert(Gameplay)
+        .insert(Name::new("Bullet"))
+        .id()
+}
+
+fn move_bullets(
+    mut bullets: Query<(&Bullet, &mut Transform), Without<Pooled>>,
+    time: Res<GameTime>,
+) {
+    for (bullet, mut transform) in &mut bullets {
+        transform.translation += bullet.direction.normalize() * bullet.speed * time.delta_seconds();
+    }
+}
+
+/// The closest live enemy to `position` within `radius`. The grid is rebuilt once a step, so
+/// every candidate is checked against `targets` in case it died since.
+fn nearest_target(
+    grid: &SpatialGrid,
+    targets: &Query<&GlobalTransform, With<Target>>,
+    position: Vec3,
+    radius: f32,
+    can_target_air: bool,
+) -> Option<Entity> {
+    grid.query_radius_positions(position, radius)
+        .filter(|(entity, _)| grid.is_targetable(*entity, can_target_air))
+        .filter(|(entity, _)| targets.contains(*entity))
+        .min_by_key(|(_, translation)| FloatOrd(Vec3::distance(*translation, position)))
+        .map(|(entity, _)| entity)
+}
+
+/// Turns homing bullets toward their target. Once the target is gone the bullet follows its
+/// `RetargetPolicy`, and stops homing to carry on in a straight line if there's nothing to chase.
+fn home_bullets(
+    mut commands: Commands,
+    mut bullets: Query<(Entity, &mut Bullet, &mut Homing, &GlobalTransform), Without<Pooled>>,
+    targets: Query<&GlobalTransform, With<Target>>,
+    grid: Res<SpatialGrid>,
+    time: Res<GameTime>,
+) {
+    for (entity, mut bullet, mut homing, transform) in &mut bullets {
+        if !targets.contains(homing.target) {
+            let replacement = match homing.retarget {
+                RetargetPolicy::Straight => None,
+                RetargetPolicy::Nearest { radius } => nearest_target(
+                    &grid,
+                    &targets,
+                    transform.translation(),
+                    radius,
+                    homing.can_target_air,
+                ),
+            };
+
+            match replacement {
+                Some(replacement) => homing.target = replacement,
+                None => {
+                    commands.entity(entity).remove::<Homing>();
+                    continue;
+                }
+            }
+        }
+
+        let target = match targets.get(homing.target) {
+            Ok(target) => target.translation(),
+            Err(_) => continue,
+        };
+
+        let current = bullet.direction.normalize_or_zero();
+        let desired = (target - transform.translation()).normalize_or_zero();
+        let angle = current.angle_between(desired);
+        let max_turn = homing.turn_rate * time.delta_seconds();
+
+        bullet.direction = if angle <= max_turn {
+            desired
+        } else {
+            let axis = current.cross(desired).try_normalize().unwrap_or(Vec3::Y);
+            Quat::from_axis_angle(axis, max_turn) * current
+        };
+    }
+}
+
+pub fn bullet_collision(
+    mut commands: Commands,
+    mut bullets: Query<(Entity, &Bullet, &GlobalTransform, Option<&mut Pierce>), Without<Pooled>>,
+    mut targets: Query<
+        (
+            Entity,
+            &mut Health,
+            &GlobalTransform,
+            (Option<&Armor>, Option<&Resistances>),
+            Option<&mut Shield>,
+            Option<&Invulnerable>,
+        ),
+        With<Target>,
+    >,
+    mut poisoned: Query<&mut Poison>,
+    flying: Query<(), With<Flying>>,
+    stunnable: Query<(), (Without<Stun>, Without<StunImmune>)>,
+    mut pool: ResMut<BulletPool>,
+    mut hit_events: EventWriter<BulletHit>,
+    mut damage_events: EventWriter<DamageDealt>,
+    mut rng: ResMut<GameRng>,
+    poison_settings: Res<PoisonSettings>,
+    phase_settings: Res<PhaseShiftSettings>,
+) {
+    for (bullet_ent, bullet, bullet_transform, mut pierce) in &mut bullets {
+        let impact = bullet_transform.translation();
+
+        let hit = targets
+            .iter()
+            .filter(|(entity, _, _, _, _, invulnerable)| {
+                let passes_through = invulnerable.is_some() && phase_settings.bullets_pass_through;
+                !passes_through
+                    && pierce
+                        .as_ref()
+                        .map_or(true, |pierce| !pierce.hit.contains(entity))
+            })
+            .find(|(_, _, target_transform, _, _, _)| {
+                Vec3::distance(impact, target_transform.translation()) < bullet.collision_radius
+            })
+            .map(|(entity, _, _, _, _, _)| entity);
+
+        let hit = match hit {
+            Some(hit) => hit,
+            None => continue,
+        };
+
+        match pierce.as_mut() {
+            Some(pierce) if pierce.remaining > 0 => {
+                pierce.remaining -= 1;
+                pierce.hit.insert(hit);
+                commands.entity(bullet_ent).remove::<AimedAt>();
+            }
+            _ => pool.release(&mut commands, bullet_ent),
+        }
+        hit_events.send(BulletHit {
+            bullet: bullet_ent,
+            position: impact,
+        });
+
+        // The struck enemy is always damaged, flying or not, but splash only reaches other flyers
+        // if the bullet can hit air.
+        let victims: Vec<Entity> = if bullet.splash_radius > 0.0 {
+            targets
+                .iter()
+                .filter(|(entity, _, target_transform, _, _, _)| {
+                    *entity == hit
+                        || ((bullet.anti_air || !flying.contains(*entity))
+                            && Vec3::distance(impact, target_transform.translation())
+                                <= bullet.splash_radius)
+                })
+                .map(|(entity, _, _, _, _, _)| entity)
+                .collect()
+        } else {
+            vec![hit]
+        };
+
+        // One roll per bullet, so a critical splash crits everything it hits.
+        let (damage, crit) = bullet.roll_damage(&mut rng);
+
+        for victim in victims {
+            // Invulnerable enemies still use the bullet up, unless set to let it pass, but shrug
+            // off its damage and effects.
+            if let Ok((_, mut health, target_transform, defenses, mut shield, invulnerable)) =
+                targets.get_mut(victim)
+            {
+                if invulnerable.is_some() {
+                    continue;
+                }
+
+                let amount = mitigate(damage, bullet.damage_type, bullet.true_damage, defenses);
+                apply_damage(&mut health, shield.as_deref_mut(), amount);
+                damage_events.send(DamageDealt {
+                    target: victim,
+                    amount,
+                    position: target_transform.translation(),
+                    crit,
+                });
+            }
+
+            // Inserting replaces any existing slow, which refreshes the timer instead of stacking.
+            if let Some(factor) = bullet.slow {
+                commands.entity(victim).insert(Slow::new(factor));
+            }
+
+            if bullet.poison_dps > 0.0 {
+                if let Ok(mut poison) = poisoned.get_mut(victim) {
+                    poison.refresh(
+                        bullet.poison_dps,
+                        bullet.poison_duration,
+                        poison_settings.refresh,
+                    );
+                } else {
+                    commands
+                        .entity(victim)
+                        .insert(Poison::new(bullet.poison_dps, bullet.poison_duration));
+                }
+            }
+
+            // A stun isn't refreshed while it lasts, nor reapplied while the enemy is immune.
+            if let Some(duration) = bullet.stun {
+                if stunnable.contains(victim) {
+                    commands.entity(victim).insert(Stun::new(duration));
+                }
+            }
+        }
+    }
+}
+
+fn fade_out(mut faders: Query<(&Lifetime, &FadeOut, &mut Transform), Without<Pooled>>) {
+    for (lifetime, fade, mut transform) in &mut faders {
+        transform.scale = fade.base_scale * fade.factor(&lifetime.timer);
+    }
+}
+
+fn bullet_despawn(
+    mut commands: Commands,
+    mut lifetimes: Query<(Entity, &mut Lifetime, Option<&Bullet>), Without<Pooled>>,
+    mut pool: ResMut<BulletPool>,
+    time: Res<GameTime>,
+) {
+    for (entity, mut lifetime, bullet) in &mut lifetimes {
+        lifetime.timer.tick(time.delta());
+
+        if lifetime.timer.just_finished() {
+            if bullet.is_some() {
+                pool.release(&mut commands, entity);
+            } else {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+
+        app.init_resource::<BulletPool>()
+            .init_resource::<PoisonSettings>()
+            .init_resource::<PhaseShiftSettings>()
+            .insert_resource(GameRng::new(0))
+            .add_event::<BulletHit>()
+            .add_event::<DamageDealt>()
+            .add_system(bullet_collision.label(BulletSystem::Collision));
+
+        app
+    }
+
+    fn spawn_target(app: &mut App, position: Vec3) -> Entity {
+        app.world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .insert(Health { value: 10, max: 10 })
+            .insert(GlobalTransform::from_translation(position))
+            .id()
+    }
+
+    fn fire(app: &mut App, position: Vec3, bullet: Bullet) -> Entity {
+        app.world
+            .spawn()
+            .insert(Bullet {
+                damage: 3,
+                collision_radius: 0.3,
+                ..bullet
+            })
+            .insert(GlobalTransform::from_translation(position))
+            .id()
+    }
+
+    fn health(app: &App, target: Entity) -> i32 {
+        app.world.get::<Health>(target).unwrap().value
+    }
+
+    #[test]
+    fn bullet_damages_the_target_it_lands_on_and_is_used_up() {
+        let mut app = app();
+        let target = spawn_target(&mut app, Vec3::ZERO);
+        let bullet = fire(&mut app, Vec3::new(0.1, 0.0, 0.0), Bullet::default());
+
+        app.update();
+
+        assert_eq!(health(&app, target), 7);
+        assert!(app.world.get::<Pooled>(bullet).is_some());
+        assert_eq!(app.world.resource::<BulletPool>().inactive, vec![bullet]);
+    }
+
+    #[test]
+    fn bullet_misses_targets_out_of_reach() {
+        let mut app = app();
+        let target = spawn_target(&mut app, Vec3::ZERO);
+        let bullet = fire(&mut app, Vec3::new(1.0, 0.0, 0.0), Bullet::default());
+
+        app.update();
+
+        assert_eq!(health(&app, target), 10);
+        assert!(app.world.get::<Pooled>(bullet).is_none());
+    }
+
+    #[test]
+    fn one_splashing_bullet_damages_every_clustered_enemy() {
+        let mut app = app();
+        let struck = spawn_target(&mut app, Vec3::ZERO);
+        let nearby = spawn_target(&mut app, Vec3::new(0.8, 0.0, 0.0));
+        let distant = spawn_target(&mut app, Vec3::new(3.0, 0.0, 0.0));
+        fire(
+            &mut app,
+            Vec3::ZERO,
+            Bullet {
+                splash_radius: 1.0,
+                ..default()
+            },
+        );
+
+        app.update();
+
+        assert_eq!(health(&app, struck), 7);
+        assert_eq!(health(&app, nearby), 7);
+        assert_eq!(health(&app, distant), 10);
+    }
+
+    #[test]
+    fn splash_passes_flyers_by_unless_the_bullet_hits_air() {
+        let mut app = app();
+        let struck = spawn_target(&mut app, Vec3::ZERO);
+        let flyer = spawn_target(&mut app, Vec3::new(0.5, 0.0, 0.0));
+        app.world.entity_mut(flyer).insert(Flying { altitude: 0.0 });
+        let splash = Bullet {
+            splash_radius: 1.0,
+            ..default()
+        };
+
+        fire(&mut app, Vec3::ZERO, splash);
+        app.update();
+
+        assert_eq!(health(&app, struck), 7);
+        assert_eq!(health(&app, flyer), 10);
+
+        fire(
+            &mut app,
+            Vec3::ZERO,
+            Bullet {
+                splash_radius: 1.0,
+                anti_air: true,
+                ..default()
+            },
+        );
+        app.update();
+
+        assert_eq!(health(&app, struck), 4);
+        assert_eq!(health(&app, flyer), 7);
+    }
+
+    #[test]
+    fn cannon_bullets_splash() {
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+        let cannon = Tower::new(TowerType::Cannon, &config, &GameAssets::headless());
+
+        assert!(cannon.splash_radius > 0.0);
+    }
+
+    #[test]
+    fn frost_bullets_slow_what_they_hit() {
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+        let frost = Tower::new(TowerType::Frost, &config, &GameAssets::headless());
+        let mut app = app();
+        let target = spawn_target(&mut app, Vec3::ZERO);
+        fire(
+            &mut app,
+            Vec3::ZERO,
+            Bullet {
+                slow: frost.slow,
+                ..default()
+            },
+        );
+
+        app.update();
+
+        let slow = app.world.get::<Slow>(target).expect("target wasn't slowed");
+        assert_eq!(Some(slow.factor), frost.slow);
+    }
+
+    #[test]
+    fn poison_bullets_refresh_poison_rather_than_stacking_it() {
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+        let (dps, duration) = config.tower(TowerType::Poison).poison.unwrap();
+        let mut app = app();
+        let target = spawn_target(&mut app, Vec3::ZERO);
+        let bullet = || Bullet {
+            poison_dps: dps,
+            poison_duration: duration,
+            ..default()
+        };
+
+        fire(&mut app, Vec3::ZERO, bullet());
+        app.update();
+        fire(&mut app, Vec3::ZERO, bullet());
+        app.update();
+
+        let poison = app
+            .world
+            .get::<Poison>(target)
+            .expect("target wasn't poisoned");
+        assert_eq!(poison.dps, dps);
+        assert_eq!(poison.timer.duration().as_secs_f32(), duration * 2.0);
+    }
+
+    fn fire_every_step(mut commands: Commands, mut pool: ResMut<BulletPool>) {
+        spawn_bullet(
+            &mut commands,
+            &mut pool,
+            &Handle::default(),
+            Vec3::ZERO,
+            Bullet::default(),
+            0.25,
+        );
+    }
+
+    #[test]
+    fn expired_bullets_are_fired_again_instead_of_piling_up() {
+        let mut app = app();
+        app.insert_resource(GameTime::stepped(std::time::Duration::from_secs_f32(0.1)))
+            .add_system(bullet_despawn.label(BulletSystem::Despawn))
+            .add_system(fire_every_step.after(BulletSystem::Despawn));
+
+        for _ in 0..30 {
+            app.update();
+        }
+
+        // Each bullet lasts three steps, so three cover every shot in flight.
+        let bullets = app
+            .world
+            .query_filtered::<Entity, With<Bullet>>()
+            .iter(&app.world)
+            .count();
+        assert_eq!(bullets, 3);
+    }
+
+    /// Which tower's bullet the next `fire_tower_bullet` fires.
+    struct NextShot(Option<Handle<Scene>>);
+
+    fn fire_tower_bullet(
+        mut commands: Commands,
+        mut pool: ResMut<BulletPool>,
+        mut next: ResMut<NextShot>,
+    ) {
+        if let Some(scene) = next.0.take() {
+            spawn_bullet(
+                &mut commands,
+                &mut pool,
+                &scene,
+                Vec3::ZERO,
+                Bullet::default(),
+                1.0,
+            );
+        }
+    }
+
+    #[test]
+    fn recycled_bullets_take_on_the_model_of_the_tower_firing_them() {
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+        let assets = GameAssets::headless();
+        let mut cannon = Tower::new(TowerType::Cannon, &config, &assets);
+        let mut frost = Tower::new(TowerType::Frost, &config, &assets);
+        cannon.bullet_scene = Handle::weak(bevy::asset::HandleId::random::<Scene>());
+        frost.bullet_scene = Handle::weak(bevy::asset::HandleId::random::<Scene>());
+
+        let mut app = app();
+        app.insert_resource(NextShot(Some(cannon.bullet_scene.clone())))
+            .add_system(fire_tower_bullet);
+        app.update();
+
+        let bullet = app
+            .world
+            .query_filtered::<Entity, With<Bullet>>()
+            .single(&app.world);
+        // Back in the pool, as if it had hit something.
+        app.world.entity_mut(bullet).insert(Pooled);
+        app.world.resource_mut::<BulletPool>().inactive.push(bullet);
+
+        app.world.resource_mut::<NextShot>().0 = Some(frost.bullet_scene.clone());
+        app.update();
+
+        assert!(app.world.get::<Pooled>(bullet).is_none());
+        assert_eq!(
+            app.world.get::<Handle<Scene>>(bullet),
+            Some(&frost.bullet_scene)
+        );
+    }
+
+    /// Bullets that fly and steer as well as hit, with transforms kept in step.
+    fn flying_app() -> App {
+        let mut app = app();
+        app.add_plugin(TransformPlugin)
+            .insert_resource(GameTime::stepped(std::time::Duration::from_secs_f32(
+                1.0 / 60.0,
+            )))
+            .init_resource::<SpatialGrid>()
+            .add_system(home_bullets.before(move_bullets))
+            .add_system(move_bullets.before(BulletSystem::Collision));
+
+        app
+    }
+
+    fn launch(app: &mut App, direction: Vec3, homing: Homing) -> Entity {
+        let bullet = fire(
+            app,
+            Vec3::ZERO,
+            Bullet {
+                direction,
+                speed: 4.0,
+                ..default()
+            },
+        );
+        app.world
+            .entity_mut(bullet)
+            .insert(Transform::default())
+            .insert(homing);
+
+        bullet
+    }
+
+    #[test]
+    fn homing_bullets_curve_onto_a_target_that_moved() {
+        let mut app = flying_app();
+        let target = spawn_target(&mut app, Vec3::new(2.0, 0.0, 2.0));
+        // Fired at where the target used to be, straight down +X.
+        launch(
+            &mut app,
+            Vec3::X,
+            Homing {
+                target,
+                turn_rate: 4.0,
+                ..default()
+            },
+        );
+
+        for _ in 0..120 {
+            app.update();
+
+            if health(&app, target) < 10 {
+                return;
+            }
+        }
+
+        panic!("homing bullet never reached its target");
+    }
+
+    #[test]
+    fn bullets_shrink_away_over_the_fade_window() {
+        let mut app = App::new();
+        app.add_system(fade_out);
+        let bullet = app
+            .world
+            .spawn()
+            .insert(Lifetime {
+                timer: Timer::from_seconds(1.0, false),
+            })
+            .insert(FadeOut {
+                duration: 0.25,
+                base_scale: Vec3::splat(2.0),
+            })
+            .insert(Transform::default())
+            .id();
+        let age = |app: &mut App, seconds: f32| {
+            app.world
+                .get_mut::<Lifetime>(bullet)
+                .unwrap()
+                .timer
+                .set_elapsed(std::time::Duration::from_secs_f32(seconds));
+            app.update();
+            app.world.get::<Transform>(bullet).unwrap().scale
+        };
+
+        assert_eq!(age(&mut app, 0.5), Vec3::splat(2.0));
+        assert!(age(&mut app, 0.875).abs_diff_eq(Vec3::splat(1.0), 1e-4));
+        assert_eq!(age(&mut app, 1.0), Vec3::ZERO);
+    }
+
+    #[test]
+    fn crits_multiply_the_damage_dealt() {
+        let mut app = app();
+        let crit_target = spawn_target(&mut app, Vec3::ZERO);
+        let plain_target = spawn_target(&mut app, Vec3::new(5.0, 0.0, 0.0));
+        let crit_multiplier = 2.0;
+        fire(
+            &mut app,
+            Vec3::ZERO,
+            Bullet {
+                crit: true,
+                crit_multiplier,
+                ..default()
+            },
+        );
+        fire(
+            &mut app,
+            Vec3::new(5.0, 0.0, 0.0),
+            Bullet {
+                crit_chance: 0.0,
+                crit_multiplier,
+                ..default()
+            },
+        );
+
+        app.update();
+
+        assert_eq!(health(&app, crit_target), 10 - 6);
+        assert_eq!(health(&app, plain_target), 10 - 3);
+        let events = app.world.resource::<Events<DamageDealt>>();
+        let mut crits: Vec<_> = events
+            .get_reader()
+            .iter(events)
+            .map(|damage| (damage.amount, damage.crit))
+            .collect();
+        crits.sort_unstable();
+        assert_eq!(crits, vec![(3, false), (6, true)]);
+    }
+
+    #[test]
+    fn piercing_bullets_pass_through_that_many_extra_enemies() {
+        let mut app = app();
+        // Stacked up, so the bullet overlaps every one of them at once.
+        let stacked: Vec<_> = (0..4).map(|_| spawn_target(&mut app, Vec3::ZERO)).collect();
+        let bullet = fire(&mut app, Vec3::ZERO, Bullet::default());
+        app.world.entity_mut(bullet).insert(Pierce::new(2));
+
+        for _ in 0..4 {
+            app.update();
+        }
+
+        let damaged = stacked
+            .iter()
+            .filter(|target| health(&app, **target) == 7)
+            .count();
+        assert_eq!(damaged, 3);
+        assert!(app.world.get::<Pooled>(bullet).is_some());
+    }
+
+    #[test]
+    fn homing_bullets_fly_straight_once_their_target_is_gone() {
+        let mut app = flying_app();
+        let target = spawn_target(&mut app, Vec3::new(0.0, 0.0, 3.0));
+        let bullet = launch(
+            &mut app,
+            Vec3::X,
+            Homing {
+                target,
+                turn_rate: 4.0,
+                ..default()
+            },
+        );
+
+        for _ in 0..5 {
+            app.update();
+        }
+        app.world.despawn(target);
+        app.update();
+
+        let direction = app.world.get::<Bullet>(bullet).unwrap().direction;
+        let before = app.world.get::<Transform>(bullet).unwrap().translation;
+        for _ in 0..30 {
+            app.update();
+        }
+        let after = app.world.get::<Transform>(bullet).unwrap().translation;
+
+        assert!(app.world.get::<Homing>(bullet).is_none());
+        assert_eq!(
+            app.world.get::<Bullet>(bullet).unwrap().direction,
+            direction
+        );
+        assert!((after - before)
+            .normalize()
+            .abs_diff_eq(direction.normalize(), 1e-4));
+    }
+
+    #[test]
+    fn stuns_dont_land_on_enemies_still_immune() {
+        let mut app = app();
+        let fresh = spawn_target(&mut app, Vec3::ZERO);
+        let immune = spawn_target(&mut app, Vec3::new(5.0, 0.0, 0.0));
+        app.world.entity_mut(immune).insert(StunImmune {
+            timer: Timer::from_seconds(STUN_IMMUNITY, false),
+        });
+        let stunning = || Bullet {
+            stun: Some(1.0),
+            ..default()
+        };
+
+        fire(&mut app, Vec3::ZERO, stunning());
+        fire(&mut app, Vec3::new(5.0, 0.0, 0.0), stunning());
+        app.update();
+
+        assert!(app.world.get::<Stun>(fresh).is_some());
+        assert!(app.world.get::<Stun>(immune).is_none());
+        // The hit still lands.
+        assert_eq!(health(&app, immune), 7);
+    }
+}
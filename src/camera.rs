@@ -0,0 +1,430 @@
+use bevy::{
+    input::mouse::{MouseMotion, MouseWheel},
+    prelude::*,
+};
+
+use crate::*;
+
+const MIN_PITCH: f32 = 0.1;
+const MAX_PITCH: f32 = 1.45;
+
+#[derive(Component)]
+pub struct CameraController {
+    pub focus: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub orbit_sensitivity: f32,
+    pub pan_sensitivity: f32,
+    pub zoom_sensitivity: f32,
+    /// How quickly the camera catches up with the requested view; higher is snappier.
+    pub smoothing: f32,
+    smoothed_focus: Vec3,
+    smoothed_yaw: f32,
+    smoothed_pitch: f32,
+    smoothed_distance: f32,
+}
+
+impl CameraController {
+    /// Builds a controller that starts at `position` orbiting around `focus`.
+    pub fn looking_at(position: Vec3, focus: Vec3) -> Self {
+        let offset = position - focus;
+        let distance = offset.length();
+        let yaw = f32::atan2(offset.x, offset.z);
+        let pitch = (offset.y / distance).asin().clamp(MIN_PITCH, MAX_PITCH);
+
+        Self {
+            focus,
+            yaw,
+            pitch,
+            distance,
+            min_distance: 2.0,
+            max_distance: 30.0,
+            orbit_sensitivity: 0.005,
+            pan_sensitivity: 0.002,
+            zoom_sensitivity: 1.0,
+            smoothing: 12.0,
+            smoothed_focus: focus,
+            smoothed_yaw: yaw,
+            smoothed_pitch: pitch,
+            smoothed_distance: distance,
+        }
+    }
+
+    pub fn zoom(&mut self, amount: f32) {
+        self.distance = (self.distance - amount * self.zoom_sensitivity)
+            .clamp(self.min_distance, self.max_distance);
+    }
+
+    /// The transform the camera should have right now, following the smoothed view.
+    pub fn transform(&self) -> Transform {
+        let rotation =
+            Quat::from_rotation_y(self.smoothed_yaw) * Quat::from_rotation_x(-self.smoothed_pitch);
+        let position = self.smoothed_focus + rotation * Vec3::new(0.0, 0.0, self.smoothed_distance);
+
+        Transform::from_translation(position).looking_at(self.smoothed_focus, Vec3::Y)
+    }
+
+    fn smooth(&mut self, delta_seconds: f32) {
+        let t = 1.0 - (-self.smoothing * delta_seconds).exp();
+
+        self.smoothed_focus = self.smoothed_focus.lerp(self.focus, t);
+        self.smoothed_yaw += (self.yaw - self.smoothed_yaw) * t;
+        self.smoothed_pitch += (self.pitch - self.smoothed_pitch) * t;
+        self.smoothed_distance += (self.distance - self.smoothed_distance) * t;
+    }
+}
+
+/// Points the camera at `target` until `timer` runs out, ignoring player input, then puts the
+/// view back on `previous_focus`.
+#[derive(Component)]
+pub struct CameraFocus {
+    pub target: Entity,
+    pub timer: Timer,
+    pub previous_focus: Vec3,
+}
+
+pub struct CameraFocusSettings {
+    /// Whether a spawning boss takes over the camera. Follows `DisplaySettings::boss_focus`.
+    pub enabled: bool,
+    pub duration: f32,
+}
+
+impl Default for CameraFocusSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            duration: 2.5,
+        }
+    }
+}
+
+/// Camera shake driven by `trauma`, which big impacts add to and which wears off over time. The
+/// shake strength goes with trauma squared, so small knocks barely register.
+pub struct ScreenShake {
+    /// From 0 to 1.
+    pub trauma: f32,
+    /// Trauma lost per second.
+    pub decay: f32,
+    pub max_offset: f32,
+    /// Maximum roll in radians.
+    pub max_roll: f32,
+    /// How fast the camera wobbles.
+    pub frequency: f32,
+    /// Scales the whole effect, for players who want it gentler.
+    pub intensity: f32,
+    pub enabled: bool,
+    pub boss_death_trauma: f32,
+    pub explosion_trauma: f32,
+    pub leak_trauma: f32,
+}
+
+impl Default for ScreenShake {
+    fn default() -> Self {
+        Self {
+            trauma: 0.0,
+            decay: 1.5,
+            max_offset: 0.3,
+            max_roll: 0.05,
+            frequency: 25.0,
+            intensity: 1.0,
+            enabled: true,
+            boss_death_trauma: 0.8,
+            explosion_trauma: 0.25,
+            leak_trauma: 0.4,
+        }
+    }
+}
+
+impl ScreenShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+
+    /// The camera-space offset and roll at `seconds`. The wobble is a few out of step sine waves
+    /// rather than random, so it doesn't draw from `GameRng`.
+    pub fn offset(&self, seconds: f32) -> (Vec3, f32) {
+        if !self.enabled {
+            return (Vec3::ZERO, 0.0);
+        }
+
+        let strength = self.trauma * self.trauma * self.intensity;
+        let t = seconds * self.frequency;
+        let offset = Vec3::new(t.sin(), (t * 1.3 + 1.7).sin(), 0.0) * self.max_offset * strength;
+        let roll = (t * 0.9 + 3.1).sin() * self.max_roll * strength;
+
+        (offset, roll)
+    }
+}
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraFocusSettings>()
+            .init_resource::<ScreenShake>()
+            .add_system(focus_camera_on_boss.before(camera_controller))
+            .add_system(camera_controller)
+            .add_system(camera_shake.after(camera_controller))
+            .add_system_set(SystemSet::on_update(GameState::Next).with_system(add_trauma));
+    }
+}
+
+fn add_trauma(
+    mut shake: ResMut<ScreenShake>,
+    mut death_events: EventReader<TargetDeath>,
+    mut explosion_events: EventReader<Explosion>,
+    mut leak_events: EventReader<TargetLeak>,
+) {
+    let bosses = death_events
+        .iter()
+        .filter(|death| death.enemy_type == EnemyType::Boss)
+        .count();
+    let explosions = explosion_events.iter().count();
+    let leaks = leak_events.iter().count();
+
+    let trauma = bosses as f32 * shake.boss_death_trauma
+        + explosions as f32 * shake.explosion_trauma
+        + leaks as f32 * shake.leak_trauma;
+
+    if trauma > 0.0 {
+        shake.add_trauma(trauma);
+    }
+}
+
+/// Runs after `camera_controller` has put the camera where the player wants it, and shakes it
+/// from there. The controller rebuilds the transform every frame, so the shake never builds up.
+fn camera_shake(
+    mut cameras: Query<&mut Transform, With<GameCamera>>,
+    mut shake: ResMut<ScreenShake>,
+    time: Res<Time>,
+) {
+    if shake.trauma <= 0.0 {
+        return;
+    }
+
+    let (offset, roll) = shake.offset(time.seconds_since_startup() as f32);
+
+    for mut transform in &mut cameras {
+        let rotation = transform.rotation;
+        transform.translation += rotation * offset;
+        transform.rotation = rotation * Quat::from_rotation_z(roll);
+    }
+
+    let decay = shake.decay * time.delta_seconds();
+    shake.trauma = (shake.trauma - decay).max(0.0);
+}
+
+fn focus_camera_on_boss(
+    mut commands: Commands,
+    cameras: Query<(Entity, &CameraController), (With<GameCamera>, Without<CameraFocus>)>,
+    bosses: Query<Entity, Added<Boss>>,
+    settings: Res<CameraFocusSettings>,
+) {
+    if !settings.enabled {
+        return;
+    }
+
+    let boss = match bosses.iter().next() {
+        Some(boss) => boss,
+        None => return,
+    };
+
+    for (camera, controller) in &cameras {
+        commands.entity(camera).insert(CameraFocus {
+            target: boss,
+            timer: Timer::from_seconds(settings.duration, false),
+            previous_focus: controller.focus,
+        });
+    }
+}
+
+fn camera_controller(
+    mut commands: Commands,
+    mut cameras: Query<
+        (
+            Entity,
+            &mut CameraController,
+            &mut Transform,
+            Option<&mut CameraFocus>,
+        ),
+        With<GameCamera>,
+    >,
+    targets: Query<&GlobalTransform>,
+    mut motion_events: EventReader<MouseMotion>,
+    mut wheel_events: EventReader<MouseWheel>,
+    mouse: Res<Input<MouseButton>>,
+    time: Res<Time>,
+) {
+    let motion: Vec2 = motion_events.iter().map(|event| event.delta).sum();
+    let scroll: f32 = wheel_events.iter().map(|event| event.y).sum();
+
+    for (camera, mut controller, mut transform, focus) in &mut cameras {
+        if let Some(mut focus) = focus {
+            focus.timer.tick(time.delta());
+
+            // The focus also ends early if the target dies before the timer is up.
+            match targets.get(focus.target) {
+                Ok(target) if !focus.timer.finished() => {
+                    controller.focus = target.translation();
+                }
+                _ => {
+                    controller.focus = focus.previous_focus;
+                    commands.entity(camera).remove::<CameraFocus>();
+                }
+            }
+
+            controller.smooth(time.delta_seconds());
+            *transform = controller.transform();
+            continue;
+        }
+
+        if mouse.pressed(MouseButton::Middle) {
+            controller.yaw -= motion.x * controller.orbit_sensitivity;
+            controller.pitch = (controller.pitch + motion.y * controller.orbit_sensitivity)
+                .clamp(MIN_PITCH, MAX_PITCH);
+        }
+
+        if mouse.pressed(MouseButton::Right) {
+            let right = Quat::from_rotation_y(controller.yaw) * Vec3::X;
+            let forward = Quat::from_rotation_y(controller.yaw) * Vec3::Z;
+            let scale = controller.pan_sensitivity * controller.distance;
+
+            controller.focus += (-right * motion.x + -forward * motion.y) * scale;
+        }
+
+        if scroll != 0.0 {
+            controller.zoom(scroll);
+        }
+
+        controller.smooth(time.delta_seconds());
+        *transform = controller.transform();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{input::mouse::MouseScrollUnit, utils::Instant};
+    use std::time::Duration;
+
+    fn app() -> App {
+        let mut app = App::new();
+
+        app.init_resource::<Time>()
+            .init_resource::<Input<MouseButton>>()
+            .init_resource::<CameraFocusSettings>()
+            .init_resource::<ScreenShake>()
+            .add_event::<MouseMotion>()
+            .add_event::<MouseWheel>()
+            .add_system(focus_camera_on_boss.before(camera_controller))
+            .add_system(camera_controller)
+            .add_system(camera_shake.after(camera_controller));
+
+        app
+    }
+
+    fn spawn_camera(app: &mut App) -> Entity {
+        let position = Vec3::new(0.0, 10.0, 10.0);
+
+        app.world
+            .spawn()
+            .insert(GameCamera)
+            .insert(CameraController::looking_at(position, Vec3::ZERO))
+            .insert(Transform::from_translation(position))
+            .id()
+    }
+
+    fn scroll(app: &mut App, y: f32) {
+        app.world
+            .resource_mut::<Events<MouseWheel>>()
+            .send(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: 0.0,
+                y,
+            });
+        app.update();
+    }
+
+    fn distance(app: &App, camera: Entity) -> f32 {
+        app.world.get::<CameraController>(camera).unwrap().distance
+    }
+
+    #[test]
+    fn scrolling_zooms_in_as_far_as_the_clamp() {
+        let mut app = app();
+        let camera = spawn_camera(&mut app);
+        let start = distance(&app, camera);
+
+        scroll(&mut app, 3.0);
+        assert!((distance(&app, camera) - (start - 3.0)).abs() < 1e-4);
+
+        scroll(&mut app, 100.0);
+        let controller = app.world.get::<CameraController>(camera).unwrap();
+        assert_eq!(controller.distance, controller.min_distance);
+    }
+
+    /// Moves the clock on by `seconds` and runs a frame.
+    fn advance(app: &mut App, now: &mut Instant, seconds: f32) {
+        *now += Duration::from_secs_f32(seconds);
+        app.world.resource_mut::<Time>().update_with_instant(*now);
+        app.update();
+    }
+
+    fn translation(app: &App, camera: Entity) -> Vec3 {
+        app.world.get::<Transform>(camera).unwrap().translation
+    }
+
+    #[test]
+    fn trauma_shakes_the_camera_until_it_wears_off() {
+        let mut app = app();
+        let camera = spawn_camera(&mut app);
+        let mut now = Instant::now();
+        app.world.resource_mut::<Time>().update_with_instant(now);
+
+        advance(&mut app, &mut now, 0.1);
+        let rest = translation(&app, camera);
+
+        app.world.resource_mut::<ScreenShake>().add_trauma(1.0);
+        advance(&mut app, &mut now, 0.1);
+        assert!(translation(&app, camera).distance(rest) > 0.01);
+        assert!(app.world.resource::<ScreenShake>().trauma < 1.0);
+
+        // Well past the time 1.5 trauma a second takes to wear off.
+        advance(&mut app, &mut now, 1.0);
+        advance(&mut app, &mut now, 0.1);
+        assert_eq!(app.world.resource::<ScreenShake>().trauma, 0.0);
+        assert!(translation(&app, camera).distance(rest) < 1e-4);
+    }
+
+    fn focus(app: &App, camera: Entity) -> Vec3 {
+        app.world.get::<CameraController>(camera).unwrap().focus
+    }
+
+    #[test]
+    fn a_spawning_boss_holds_the_camera_until_the_focus_runs_out() {
+        let mut app = app();
+        let camera = spawn_camera(&mut app);
+        let mut now = Instant::now();
+        app.world.resource_mut::<Time>().update_with_instant(now);
+        let before = focus(&app, camera);
+
+        let boss_position = Vec3::new(5.0, 0.0, -3.0);
+        app.world
+            .spawn()
+            .insert(Boss::default())
+            .insert(GlobalTransform::from_translation(boss_position));
+
+        advance(&mut app, &mut now, 0.1);
+        advance(&mut app, &mut now, 0.1);
+        assert!(app.world.get::<CameraFocus>(camera).is_some());
+        assert_eq!(focus(&app, camera), boss_position);
+
+        // Past the 2.5 second default, the view goes back to where it was.
+        advance(&mut app, &mut now, 3.0);
+        assert!(app.world.get::<CameraFocus>(camera).is_none());
+        assert_eq!(focus(&app, camera), before);
+    }
+}
@@ -0,0 +1,278 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::*;
+
+const DEFAULT_CONFIG: &str = include_str!("../assets/config.ron");
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TowerConfig {
+    pub cost: u32,
+    pub cooldown: f32,
+    pub range: f32,
+    pub damage: i32,
+    pub turn_speed: f32,
+    pub bullet_offset: (f32, f32, f32),
+    /// Offsets of every barrel, in the barrel's own frame, taken in turn on successive shots.
+    /// Empty fires everything from `bullet_offset`.
+    #[serde(default)]
+    pub muzzles: Vec<(f32, f32, f32)>,
+    /// Ignored by mortars, whose shells follow an arc instead.
+    pub bullet_speed: f32,
+    #[serde(default)]
+    pub homing_turn_rate: Option<f32>,
+    /// What homing bullets do when their target dies mid-flight.
+    #[serde(default)]
+    pub homing_retarget: RetargetPolicy,
+    /// Bullet count and total spread in degrees.
+    #[serde(default)]
+    pub multishot: Option<(u32, f32)>,
+    #[serde(default)]
+    pub mortar: bool,
+    #[serde(default)]
+    pub hitscan: bool,
+    #[serde(default)]
+    pub true_damage: bool,
+    #[serde(default)]
+    pub predictive_aim: bool,
+    #[serde(default)]
+    pub damage_type: DamageType,
+    /// Jump count, jump range and damage falloff per jump.
+    #[serde(default)]
+    pub chain_lightning: Option<(u32, f32, f32)>,
+    #[serde(default)]
+    pub can_target_air: bool,
+    /// Extra enemies each bullet passes through after the first.
+    #[serde(default)]
+    pub pierce: Option<u32>,
+    /// Bullets also damage every enemy within this distance of where they hit. Ignored by
+    /// mortars, whose shells always splash.
+    #[serde(default)]
+    pub splash_radius: f32,
+    /// Speed factor every enemy hit by a bullet is slowed to, for a few seconds.
+    #[serde(default)]
+    pub slow: Option<f32>,
+    /// Damage per second and duration of the poison each bullet leaves on what it hits.
+    #[serde(default)]
+    pub poison: Option<(f32, f32)>,
+    /// Chance for each bullet to crit, from 0 to 1.
+    #[serde(default)]
+    pub crit_chance: f32,
+    #[serde(default = "default_crit_multiplier")]
+    pub crit_multiplier: f32,
+    /// Fire rate and damage bonus given to every tower in range. Towers with an aura don't shoot.
+    #[serde(default)]
+    pub aura: Option<(f32, f32)>,
+    /// Gold paid per level and seconds between payouts. Gold mines don't shoot.
+    #[serde(default)]
+    pub gold_mine: Option<(u32, f32)>,
+    /// Heat capacity, heat per shot and heat lost per second. Towers at capacity stop firing
+    /// until they cool off.
+    #[serde(default)]
+    pub heat: Option<(f32, f32, f32)>,
+    /// Bomblet count, bomblet damage and how far out they land. Only used by mortars.
+    #[serde(default)]
+    pub cluster_bomb: Option<(u32, i32, f32)>,
+    /// Seconds between decoys, how long each lasts and how far it lures enemies from. Decoy
+    /// towers don't shoot.
+    #[serde(default)]
+    pub decoy: Option<(f32, f32, f32)>,
+    /// Seconds each bullet stuns the enemies it hits for.
+    #[serde(default)]
+    pub stun: Option<f32>,
+    /// Passes over enemies that bullets already in the air should kill.
+    #[serde(default)]
+    pub avoid_overkill: bool,
+    /// Stats for each level past the first, in order. Empty means the tower can't be upgraded.
+    #[serde(default)]
+    pub levels: Vec<TowerStats>,
+}
+
+impl TowerConfig {
+    /// Stats at `level`, counting from 1, where level 1 is the tower as bought. `None` past the
+    /// last level.
+    pub fn level(&self, level: u8) -> Option<TowerStats> {
+        match level {
+            0 => None,
+            1 => Some(TowerStats {
+                damage: self.damage,
+                range: self.range,
+                cooldown: self.cooldown,
+                cost: self.cost,
+            }),
+            _ => self.levels.get(usize::from(level) - 2).copied(),
+        }
+    }
+
+    pub fn max_level(&self) -> u8 {
+        self.levels.len() as u8 + 1
+    }
+}
+
+fn default_crit_multiplier() -> f32 {
+    2.0
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BulletConfig {
+    /// Seconds a bullet lives on past the time it takes to reach the edge of its tower's range.
+    pub lifetime_margin: f32,
+    pub collision_radius: f32,
+    /// Seconds at the end of a bullet's lifetime spent shrinking away. Zero despawns instantly.
+    #[serde(default)]
+    pub fade_duration: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EnemyConfig {
+    pub speed: f32,
+    pub health: i32,
+    pub reward: u32,
+    pub scale: f32,
+    #[serde(default)]
+    pub armor: i32,
+    #[serde(default)]
+    pub resistances: Resistances,
+    /// Enemy type and count this enemy breaks into when it dies.
+    #[serde(default)]
+    pub split: Option<(EnemyType, u32)>,
+    /// Height the enemy flies at. Flyers head straight for the goal instead of following the
+    /// path.
+    #[serde(default)]
+    pub flying: Option<f32>,
+    /// Seconds between heals, heal radius and health restored to each enemy in it.
+    #[serde(default)]
+    pub heal: Option<(f32, f32, i32)>,
+    /// Shield points and how many it regains per second once the enemy stops taking hits.
+    #[serde(default)]
+    pub shield: Option<(i32, f32)>,
+    /// How quickly the enemy turns to face along the path. `None` leaves it facing one way.
+    #[serde(default = "default_turn_speed")]
+    pub turn_speed: Option<f32>,
+    /// Speed gained per second and the most it can build up to.
+    #[serde(default)]
+    pub acceleration: Option<(f32, f32)>,
+    /// Seconds spent vulnerable, then invulnerable, over and over.
+    #[serde(default)]
+    pub phase_shift: Option<(f32, f32)>,
+    /// Fraction of max health that sends the enemy walking back, and how far it goes.
+    #[serde(default)]
+    pub retreat: Option<(f32, f32)>,
+}
+
+fn default_turn_speed() -> Option<f32> {
+    Some(FaceMovement::default().turn_speed)
+}
+
+/// Balance values designers can tune in `assets/config.ron` without touching code.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GameConfig {
+    pub towers: HashMap<TowerType, TowerConfig>,
+    pub bullet: BulletConfig,
+    pub enemies: HashMap<EnemyType, EnemyConfig>,
+    /// Seed for `GameRng`. Leave it out to get a different run every time.
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl GameConfig {
+    /// Parses a config, rejecting it if any tower or enemy type is missing.
+    pub fn parse(contents: &str) -> Result<Self, ron::Error> {
+        let config: Self = ron::from_str(contents)?;
+
+        for tower_type in TowerType::ALL {
+            if !config.towers.contains_key(&tower_type) {
+                return Err(ron::Error::Message(format!(
+                    "missing tower config for {:?}",
+                    tower_type
+                )));
+            }
+        }
+
+        for enemy_type in EnemyType::ALL {
+            if !config.enemies.contains_key(&enemy_type) {
+                return Err(ron::Error::Message(format!(
+                    "missing enemy config for {:?}",
+                    enemy_type
+                )));
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn tower(&self, tower_type: TowerType) -> &TowerConfig {
+        &self.towers[&tower_type]
+    }
+}
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_config);
+    }
+}
+
+fn load_config(mut commands: Commands) {
+    let config = GameConfig::parse(DEFAULT_CONFIG).expect("embedded config.ron is invalid");
+
+    commands.insert_resource(config);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_config_covers_every_type() {
+        let config = GameConfig::parse(DEFAULT_CONFIG).unwrap();
+
+        assert_eq!(config.towers.len(), TowerType::ALL.len());
+        assert_eq!(config.enemies.len(), EnemyType::ALL.len());
+    }
+
+    #[test]
+    fn config_missing_an_enemy_type_is_rejected() {
+        let mut config = GameConfig::parse(DEFAULT_CONFIG).unwrap();
+        config.enemies.remove(&EnemyType::Phantom);
+        let contents = ron::to_string(&config).unwrap();
+
+        let error = GameConfig::parse(&contents).unwrap_err();
+
+        assert!(
+            matches!(&error, ron::Error::Message(message) if message.contains("Phantom")),
+            "{:?}",
+            error
+        );
+    }
+
+    #[test]
+    fn tower_fire_rates_are_read_from_the_config() {
+        let contents = DEFAULT_CONFIG.replacen("cooldown: 2.0,", "cooldown: 1.75,", 1);
+        let config = GameConfig::parse(&contents).unwrap();
+
+        assert_eq!(config.tower(TowerType::Sniper).cooldown, 1.75);
+        assert_eq!(config.tower(TowerType::Cannon).cooldown, 1.0);
+
+        let sniper = Tower::new(TowerType::Sniper, &config, &GameAssets::headless());
+        assert_eq!(sniper.shooting_timer.duration().as_secs_f32(), 1.75);
+    }
+
+    #[test]
+    fn every_enemy_type_has_its_own_scale() {
+        let config = GameConfig::parse(DEFAULT_CONFIG).unwrap();
+
+        for (enemy_type, enemy) in &config.enemies {
+            for (other_type, other) in &config.enemies {
+                assert!(
+                    enemy_type == other_type || enemy.scale != other.scale,
+                    "{:?} and {:?} would look identical",
+                    enemy_type,
+                    other_type
+                );
+            }
+        }
+    }
+}
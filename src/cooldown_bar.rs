@@ -0,0 +1,238 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+const BAR_WIDTH: f32 = 0.5;
+const BAR_HEIGHT: f32 = 0.05;
+
+pub struct CooldownBarSettings {
+    pub offset: Vec3,
+    /// Bars are hidden once the camera is further than this from its focus.
+    pub hide_distance: f32,
+}
+
+impl Default for CooldownBarSettings {
+    fn default() -> Self {
+        Self {
+            offset: Vec3::new(0.0, 1.1, 0.0),
+            hide_distance: 18.0,
+        }
+    }
+}
+
+/// Fills up as `tower`'s shooting timer counts down to its next shot.
+#[derive(Component)]
+pub struct CooldownBar {
+    pub tower: Entity,
+    pub fill: Entity,
+}
+
+#[derive(Component)]
+pub struct CooldownBarFill;
+
+struct CooldownBarAssets {
+    mesh: Handle<Mesh>,
+    background: Handle<StandardMaterial>,
+    fill: Handle<StandardMaterial>,
+}
+
+pub struct CooldownBarPlugin;
+
+impl Plugin for CooldownBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CooldownBarSettings>()
+            .add_startup_system(setup_cooldown_bar_assets)
+            .add_system_set(
+                SystemSet::on_update(GameState::Next)
+                    .with_system(spawn_cooldown_bars)
+                    .with_system(update_cooldown_bars),
+            );
+    }
+}
+
+/// How far `timer` is toward the next shot, from 0.0 just after firing to 1.0 when loaded.
+pub fn cooldown_fraction(timer: &Timer) -> f32 {
+    timer.percent()
+}
+
+fn setup_cooldown_bar_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(CooldownBarAssets {
+        mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::new(
+            BAR_WIDTH, BAR_HEIGHT,
+        )))),
+        background: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.1, 0.1, 0.1),
+            unlit: true,
+            ..default()
+        }),
+        fill: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.3, 0.7, 1.0),
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+/// Hitscan towers fire the instant they're loaded, and aura towers, gold mines and decoy towers
+/// never fire, so none of them get a bar.
+fn spawn_cooldown_bars(
+    mut commands: Commands,
+    towers: Query<
+        Entity,
+        (
+            Added<Tower>,
+            Without<Hitscan>,
+            Without<Aura>,
+            Without<GoldMine>,
+            Without<DecoyEmitter>,
+        ),
+    >,
+    bar_assets: Res<CooldownBarAssets>,
+) {
+    for tower in &towers {
+        let background = commands
+            .spawn_bundle(PbrBundle {
+                mesh: bar_assets.mesh.clone(),
+                material: bar_assets.background.clone(),
+                ..default()
+            })
+            .id();
+
+        let fill = commands
+            .spawn_bundle(PbrBundle {
+                mesh: bar_assets.mesh.clone(),
+                material: bar_assets.fill.clone(),
+                transform: Transform::from_xyz(0.0, 0.0, 0.001),
+                ..default()
+            })
+            .insert(CooldownBarFill)
+            .id();
+
+        commands
+            .spawn_bundle(SpatialBundle::default())
+            .insert(CooldownBar { tower, fill })
+            .insert(Gameplay)
+            .insert(Name::new("CooldownBar"))
+            .push_children(&[background, fill]);
+    }
+}
+
+fn update_cooldown_bars(
+    mut commands: Commands,
+    mut bars: Query<
+        (Entity, &CooldownBar, &mut Transform, &mut Visibility),
+        Without<CooldownBarFill>,
+    >,
+    mut fills: Query<&mut Transform, With<CooldownBarFill>>,
+    towers: Query<(&GlobalTransform, &Tower)>,
+    cameras: Query<(&GlobalTransform, &CameraController), With<GameCamera>>,
+    settings: Res<CooldownBarSettings>,
+) {
+    let (camera_rotation, zoomed_out) = match cameras.get_single() {
+        Ok((camera, controller)) => (
+            camera.compute_transform().rotation,
+            controller.distance > settings.hide_distance,
+        ),
+        Err(_) => return,
+    };
+
+    for (bar_ent, bar, mut transform, mut visibility) in &mut bars {
+        let (tower_transform, tower) = match towers.get(bar.tower) {
+            Ok(tower) => tower,
+            Err(_) => {
+                commands.entity(bar_ent).despawn_recursive();
+                continue;
+            }
+        };
+
+        visibility.is_visible = !zoomed_out;
+        if zoomed_out {
+            continue;
+        }
+
+        transform.translation = tower_transform.translation() + settings.offset;
+        transform.rotation = camera_rotation;
+
+        if let Ok(mut fill_transform) = fills.get_mut(bar.fill) {
+            let ratio = cooldown_fraction(&tower.shooting_timer);
+
+            fill_transform.scale.x = ratio;
+            fill_transform.translation.x = -(1.0 - ratio) * BAR_WIDTH / 2.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// A cannon with a bar over it and a camera `distance` from the focus.
+    fn app(distance: f32) -> (App, Entity, Entity) {
+        let mut app = App::new();
+        app.init_resource::<CooldownBarSettings>()
+            .add_system(update_cooldown_bars);
+
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+        let tower = app
+            .world
+            .spawn()
+            .insert(Tower::new(
+                TowerType::Cannon,
+                &config,
+                &GameAssets::headless(),
+            ))
+            .insert(GlobalTransform::default())
+            .id();
+        let fill = app
+            .world
+            .spawn()
+            .insert(CooldownBarFill)
+            .insert(Transform::default())
+            .id();
+        let bar = app
+            .world
+            .spawn()
+            .insert(CooldownBar { tower, fill })
+            .insert(Transform::default())
+            .insert(Visibility::default())
+            .id();
+        app.world
+            .spawn()
+            .insert(GameCamera)
+            .insert(CameraController::looking_at(
+                Vec3::new(0.0, distance, 0.1),
+                Vec3::ZERO,
+            ))
+            .insert(GlobalTransform::default());
+
+        (app, tower, bar)
+    }
+
+    #[test]
+    fn the_bar_fills_with_the_tower_timer() {
+        let (mut app, tower, bar) = app(10.0);
+
+        let mut loading = app.world.get_mut::<Tower>(tower).unwrap();
+        loading.shooting_timer.tick(Duration::from_millis(250));
+        let elapsed = loading.shooting_timer.percent();
+        app.update();
+
+        let fill = app.world.get::<CooldownBar>(bar).unwrap().fill;
+        let scale = app.world.get::<Transform>(fill).unwrap().scale.x;
+        assert!((scale - elapsed).abs() < 1e-4);
+        assert!((scale - 0.25).abs() < 1e-4);
+    }
+
+    #[test]
+    fn bars_hide_when_zoomed_far_out() {
+        let (mut app, _, bar) = app(25.0);
+        app.update();
+
+        assert!(!app.world.get::<Visibility>(bar).unwrap().is_visible);
+    }
+}
@@ -0,0 +1,227 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DamageType {
+    Physical,
+    Magic,
+    Fire,
+}
+
+impl Default for DamageType {
+    fn default() -> Self {
+        Self::Physical
+    }
+}
+
+/// Flat reduction to every hit an enemy takes.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct Armor {
+    pub value: i32,
+}
+
+/// Damage multipliers per `DamageType`. Below 1.0 resists, above 1.0 is a weakness.
+#[derive(Reflect, Component, Serialize, Deserialize, Clone, Copy, Debug)]
+#[reflect(Component)]
+#[serde(default)]
+pub struct Resistances {
+    pub physical: f32,
+    pub magic: f32,
+    pub fire: f32,
+}
+
+impl Default for Resistances {
+    fn default() -> Self {
+        Self {
+            physical: 1.0,
+            magic: 1.0,
+            fire: 1.0,
+        }
+    }
+}
+
+impl Resistances {
+    pub fn multiplier(&self, damage_type: DamageType) -> f32 {
+        match damage_type {
+            DamageType::Physical => self.physical,
+            DamageType::Magic => self.magic,
+            DamageType::Fire => self.fire,
+        }
+    }
+}
+
+/// The damage a hit of `damage` actually deals. Resistances scale the hit first, then armor
+/// takes its flat cut. Armor can never bring a hit below 1, so every attack at least chips away,
+/// and true damage ignores armor entirely.
+pub fn mitigate(
+    damage: i32,
+    damage_type: DamageType,
+    true_damage: bool,
+    (armor, resistances): (Option<&Armor>, Option<&Resistances>),
+) -> i32 {
+    let multiplier = resistances
+        .map(|resistances| resistances.multiplier(damage_type))
+        .unwrap_or(1.0);
+    let damage = (damage as f32 * multiplier).round() as i32;
+
+    if damage <= 0 {
+        return 0;
+    }
+
+    match armor {
+        Some(armor) if !true_damage => (damage - armor.value).max(1),
+        _ => damage,
+    }
+}
+
+/// Soaks up hits before `Health` does. Once the enemy has gone `regen_delay` seconds without
+/// being hit it refills at `regen` points per second, up to `max`. Poison goes straight to
+/// health.
+#[derive(Reflect, Component, Default, Clone, Debug)]
+#[reflect(Component)]
+pub struct Shield {
+    pub amount: i32,
+    pub max: i32,
+    pub regen: f32,
+    pub regen_delay: f32,
+    /// Seconds since the last hit.
+    pub idle: f32,
+    /// Fractional regen carried over between frames.
+    pub pending: f32,
+}
+
+impl Shield {
+    pub fn new(amount: i32, regen: f32) -> Self {
+        Self {
+            amount,
+            max: amount,
+            regen,
+            regen_delay: 2.0,
+            idle: 0.0,
+            pending: 0.0,
+        }
+    }
+
+    /// Takes as much of `damage` as the shield has left, returning the rest for `Health`. Any
+    /// hit, even one the shield can't absorb, restarts the regen delay.
+    pub fn absorb(&mut self, damage: i32) -> i32 {
+        self.idle = 0.0;
+        self.pending = 0.0;
+
+        let absorbed = damage.clamp(0, self.amount);
+        self.amount -= absorbed;
+        damage - absorbed
+    }
+
+    pub fn tick(&mut self, delta_seconds: f32) {
+        self.idle += delta_seconds;
+
+        if self.idle < self.regen_delay || self.amount >= self.max {
+            return;
+        }
+
+        self.pending += self.regen * delta_seconds;
+        let regained = self.pending.floor();
+        self.pending -= regained;
+        self.amount = (self.amount + regained as i32).min(self.max);
+    }
+}
+
+/// Deals `amount` to an enemy, through its shield first if it has one.
+pub fn apply_damage(health: &mut Health, shield: Option<&mut Shield>, amount: i32) {
+    let amount = match shield {
+        Some(shield) => shield.absorb(amount),
+        None => amount,
+    };
+
+    health.value -= amount;
+}
+
+pub struct DamagePlugin;
+
+impl Plugin for DamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Armor>()
+            .register_type::<Resistances>()
+            .register_type::<Shield>()
+            .add_system_to_stage(GameStep, shield_regen);
+    }
+}
+
+fn shield_regen(mut shields: Query<&mut Shield>, time: Res<GameTime>) {
+    for mut shield in &mut shields {
+        shield.tick(time.delta_seconds());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn armor_takes_a_flat_cut_that_true_damage_ignores() {
+        let armor = Armor { value: 3 };
+
+        assert_eq!(
+            mitigate(5, DamageType::Physical, false, (Some(&armor), None)),
+            2
+        );
+        assert_eq!(
+            mitigate(5, DamageType::Physical, true, (Some(&armor), None)),
+            5
+        );
+        // However heavy the armor, a hit always chips away.
+        assert_eq!(
+            mitigate(2, DamageType::Physical, false, (Some(&armor), None)),
+            1
+        );
+    }
+
+    #[test]
+    fn resistances_scale_only_their_own_damage_type() {
+        let resistances = Resistances {
+            fire: 0.5,
+            ..default()
+        };
+
+        assert_eq!(
+            mitigate(6, DamageType::Fire, false, (None, Some(&resistances))),
+            3
+        );
+        assert_eq!(
+            mitigate(6, DamageType::Physical, false, (None, Some(&resistances))),
+            6
+        );
+    }
+
+    #[test]
+    fn shields_soak_damage_first_and_regen_once_left_alone() {
+        let mut health = Health { value: 10, max: 10 };
+        let mut shield = Shield::new(4, 2.0);
+
+        apply_damage(&mut health, Some(&mut shield), 3);
+        assert_eq!((shield.amount, health.value), (1, 10));
+
+        apply_damage(&mut health, Some(&mut shield), 3);
+        assert_eq!((shield.amount, health.value), (0, 8));
+
+        // Nothing comes back until the regen delay has passed without a hit.
+        for _ in 0..19 {
+            shield.tick(0.1);
+        }
+        assert_eq!(shield.amount, 0);
+
+        for _ in 0..13 {
+            shield.tick(0.1);
+        }
+        assert_eq!(shield.amount, 2);
+
+        for _ in 0..20 {
+            shield.tick(0.1);
+        }
+        assert_eq!(shield.amount, shield.max);
+    }
+}
@@ -0,0 +1,274 @@
+use bevy::{
+    prelude::*,
+    render::{render_resource::PrimitiveTopology, view::NoFrustumCulling},
+};
+
+use crate::*;
+
+const DEBUG_RING_SEGMENTS: u32 = 48;
+/// Lifts the lines off the ground so they aren't hidden inside it.
+const DEBUG_LINE_HEIGHT: f32 = 0.03;
+
+/// Draws every tower's range, every lane and where each enemy is heading, for working on the
+/// game or for players who want to see everything. Separate from the selection ring, which
+/// keeps showing as usual.
+#[derive(Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+/// The segments the overlay draws this frame, gathered fresh every frame while it's enabled.
+#[derive(Default)]
+pub struct DebugLines {
+    pub segments: Vec<(Vec3, Vec3, Color)>,
+}
+
+impl DebugLines {
+    pub fn line(&mut self, start: Vec3, end: Vec3, color: Color) {
+        self.segments.push((start, end, color));
+    }
+
+    /// A flat circle around `center`, made of `DEBUG_RING_SEGMENTS` segments.
+    pub fn circle(&mut self, center: Vec3, radius: f32, color: Color) {
+        let point = |segment: u32| {
+            let angle = segment as f32 / DEBUG_RING_SEGMENTS as f32 * std::f32::consts::TAU;
+            let (sin, cos) = angle.sin_cos();
+            center + Vec3::new(cos * radius, 0.0, sin * radius)
+        };
+
+        for segment in 0..DEBUG_RING_SEGMENTS {
+            self.line(point(segment), point(segment + 1), color);
+        }
+    }
+}
+
+/// The one entity every debug line is drawn through, its mesh rebuilt each frame.
+#[derive(Component)]
+pub struct DebugOverlayMesh;
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CollectDebugLines;
+
+/// Purely visual, so this is only added when there is something to draw on.
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>()
+            .init_resource::<DebugLines>()
+            .add_startup_system(spawn_debug_overlay_mesh)
+            .add_system(toggle_debug_overlay)
+            .add_system_set(
+                SystemSet::on_update(GameState::Next)
+                    .with_system(collect_debug_lines.label(CollectDebugLines))
+                    .with_system(draw_debug_lines.after(CollectDebugLines)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Next).with_system(hide_debug_overlay));
+    }
+}
+
+fn line_mesh(segments: &[(Vec3, Vec3, Color)]) -> Mesh {
+    let mut positions = Vec::with_capacity(segments.len() * 2);
+    let mut colors = Vec::with_capacity(segments.len() * 2);
+
+    for (start, end, color) in segments {
+        positions.push(start.to_array());
+        positions.push(end.to_array());
+        colors.push(color.as_linear_rgba_f32());
+        colors.push(color.as_linear_rgba_f32());
+    }
+
+    let count = positions.len();
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; count]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; count]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh
+}
+
+fn spawn_debug_overlay_mesh(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands
+        .spawn_bundle(PbrBundle {
+            mesh: meshes.add(line_mesh(&[])),
+            // White and unlit, so the lines show their vertex colors as they are.
+            material: materials.add(StandardMaterial {
+                base_color: Color::WHITE,
+                unlit: true,
+                ..default()
+            }),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        // The bounds are worked out once from the first mesh, so they'd go stale as the lines
+        // change.
+        .insert(NoFrustumCulling)
+        .insert(DebugOverlayMesh)
+        .insert(Name::new("DebugOverlay"));
+}
+
+fn toggle_debug_overlay(
+    mut overlay: ResMut<DebugOverlay>,
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+) {
+    if bindings.is_action_just_pressed(&keyboard, Action::ToggleDebugOverlay) {
+        overlay.enabled = !overlay.enabled;
+        info!(enabled = overlay.enabled, "Toggled debug overlay");
+    }
+}
+
+/// Ranges are cyan, lanes yellow, and each enemy gets a magenta line to where it will be in a
+/// second at its current speed.
+fn collect_debug_lines(
+    mut lines: ResMut<DebugLines>,
+    towers: Query<(&Tower, &GlobalTransform)>,
+    targets: Query<(&GlobalTransform, &PathFollower), With<Target>>,
+    overlay: Res<DebugOverlay>,
+    paths: Res<Paths>,
+) {
+    lines.segments.clear();
+
+    if !overlay.enabled {
+        return;
+    }
+
+    let lift = Vec3::Y * DEBUG_LINE_HEIGHT;
+
+    for (tower, transform) in &towers {
+        let ground = transform.translation() * Vec3::new(1.0, 0.0, 1.0);
+        lines.circle(ground + lift, tower.range, Color::CYAN);
+    }
+
+    for path in paths.iter() {
+        for segment in path.waypoints.windows(2) {
+            lines.line(segment[0] + lift, segment[1] + lift, Color::YELLOW);
+        }
+    }
+
+    for (transform, follower) in &targets {
+        let position = transform.translation();
+        let velocity = paths.for_follower(follower).velocity(follower, position);
+        lines.line(position, position + velocity, Color::FUCHSIA);
+    }
+}
+
+fn draw_debug_lines(
+    mut overlays: Query<(&Handle<Mesh>, &mut Visibility), With<DebugOverlayMesh>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    lines: Res<DebugLines>,
+) {
+    for (handle, mut visibility) in &mut overlays {
+        // An empty mesh has nothing to draw, and hiding it skips the upload too.
+        let visible = !lines.segments.is_empty();
+        if visibility.is_visible != visible {
+            visibility.is_visible = visible;
+        }
+
+        if visible {
+            if let Some(mesh) = meshes.get_mut(handle) {
+                *mesh = line_mesh(&lines.segments);
+            }
+        }
+    }
+}
+
+/// The lines are only gathered during play, so the last frame's would otherwise hang over the
+/// menus.
+fn hide_debug_overlay(
+    mut overlays: Query<&mut Visibility, With<DebugOverlayMesh>>,
+    mut lines: ResMut<DebugLines>,
+) {
+    lines.segments.clear();
+
+    for mut visibility in &mut overlays {
+        visibility.is_visible = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+
+        app.insert_resource(Paths::new(vec![Path::new(
+            "Bend",
+            vec![
+                Vec3::ZERO,
+                Vec3::new(5.0, 0.0, 0.0),
+                Vec3::new(5.0, 0.0, 5.0),
+            ],
+        )]))
+        .init_resource::<Input<KeyCode>>()
+        .init_resource::<KeyBindings>()
+        .init_resource::<DebugOverlay>()
+        .init_resource::<DebugLines>()
+        .add_system(toggle_debug_overlay.before(collect_debug_lines))
+        .add_system(collect_debug_lines);
+
+        app.world
+            .spawn()
+            .insert(Tower::new(
+                TowerType::Cannon,
+                &config,
+                &GameAssets::headless(),
+            ))
+            .insert(GlobalTransform::from_translation(Vec3::new(2.0, 0.5, 2.0)));
+        app.world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .insert(PathFollower {
+                path: 0,
+                index: 1,
+                speed: 1.0,
+                progress: 1.0,
+            })
+            .insert(GlobalTransform::from_translation(Vec3::X));
+
+        app
+    }
+
+    fn tap_f3(app: &mut App) {
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::F3);
+        app.update();
+
+        let mut keyboard = app.world.resource_mut::<Input<KeyCode>>();
+        keyboard.release(KeyCode::F3);
+        keyboard.clear();
+    }
+
+    fn segments(app: &App) -> usize {
+        app.world.resource::<DebugLines>().segments.len()
+    }
+
+    #[test]
+    fn the_overlay_draws_ranges_lanes_and_headings_while_toggled_on() {
+        let mut app = app();
+
+        app.update();
+        assert_eq!(segments(&app), 0);
+
+        // One range ring, the lane's two legs and the one enemy's heading.
+        tap_f3(&mut app);
+        assert!(app.world.resource::<DebugOverlay>().enabled);
+        assert_eq!(segments(&app), DEBUG_RING_SEGMENTS as usize + 2 + 1);
+
+        app.update();
+        assert_eq!(segments(&app), DEBUG_RING_SEGMENTS as usize + 2 + 1);
+
+        tap_f3(&mut app);
+        assert_eq!(segments(&app), 0);
+    }
+}
@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// Makes a tower put down a `Decoy` at its feet every `interval` seconds instead of shooting.
+#[derive(Component)]
+pub struct DecoyEmitter {
+    pub timer: Timer,
+    pub duration: f32,
+    pub radius: f32,
+}
+
+impl DecoyEmitter {
+    pub fn new(interval: f32, duration: f32, radius: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(interval, true),
+            duration,
+            radius,
+        }
+    }
+}
+
+/// Draws ground enemies within `radius` off their path toward it until `duration` runs out.
+#[derive(Component)]
+pub struct Decoy {
+    pub duration: Timer,
+    pub radius: f32,
+}
+
+/// Put on an enemy walking toward `decoy` instead of along its path. Its `PathFollower` is left
+/// alone meanwhile, so once the decoy goes it heads back to the waypoint it was making for.
+#[derive(Component)]
+pub struct Lured {
+    pub decoy: Entity,
+    pub position: Vec3,
+}
+
+struct DecoyAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub struct DecoyPlugin;
+
+impl Plugin for DecoyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_decoy_assets)
+            .add_system_set_to_stage(
+                GameStep,
+                SystemSet::new()
+                    .with_system(emit_decoys)
+                    .with_system(expire_decoys)
+                    .with_system(lure_targets.after(emit_decoys).after(expire_decoys)),
+            );
+    }
+}
+
+fn setup_decoy_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(DecoyAssets {
+        mesh: meshes.add(Mesh::from(shape::UVSphere {
+            radius: 0.15,
+            ..default()
+        })),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgb(1.0, 0.4, 0.8),
+            emissive: Color::rgb(1.0, 0.4, 0.8),
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+fn emit_decoys(
+    mut commands: Commands,
+    mut emitters: Query<(&mut DecoyEmitter, &GlobalTransform)>,
+    decoy_assets: Res<DecoyAssets>,
+    time: Res<GameTime>,
+) {
+    for (mut emitter, transform) in &mut emitters {
+        emitter.timer.tick(time.delta());
+
+        if !emitter.timer.just_finished() {
+            continue;
+        }
+
+        let position = transform.translation() * Vec3::new(1.0, 0.0, 1.0) + Vec3::Y * 0.15;
+
+        commands
+            .spawn_bundle(PbrBundle {
+                mesh: decoy_assets.mesh.clone(),
+                material: decoy_assets.material.clone(),
+                transform: Transform::from_translation(position),
+                ..default()
+            })
+            .insert(Decoy {
+                duration: Timer::from_seconds(emitter.duration, false),
+                radius: emitter.radius,
+            })
+            .insert(Gameplay)
+            .insert(Name::new("Decoy"));
+    }
+}
+
+fn expire_decoys(
+    mut commands: Commands,
+    mut decoys: Query<(Entity, &mut Decoy)>,
+    time: Res<GameTime>,
+) {
+    for (entity, mut decoy) in &mut decoys {
+        decoy.duration.tick(time.delta());
+
+        if decoy.duration.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Lures ground enemies that wander into a decoy's radius, and lets go of any whose decoy has
+/// gone. Flyers aren't fooled.
+fn lure_targets(
+    mut commands: Commands,
+    targets: Query<(Entity, &Transform, Option<&Lured>), (With<Target>, Without<Flying>)>,
+    decoys: Query<(Entity, &Decoy, &Transform)>,
+) {
+    for (entity, transform, lured) in &targets {
+        if let Some(lured) = lured {
+            if !decoys.contains(lured.decoy) {
+                commands.entity(entity).remove::<Lured>();
+            }
+            continue;
+        }
+
+        let decoy = decoys.iter().find(|(_, decoy, decoy_transform)| {
+            !decoy.duration.finished()
+                && Vec3::distance(transform.translation, decoy_transform.translation)
+                    <= decoy.radius
+        });
+
+        if let Some((decoy, _, decoy_transform)) = decoy {
+            commands.entity(entity).insert(Lured {
+                decoy,
+                position: decoy_transform.translation * Vec3::new(1.0, 0.0, 1.0),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn spawn_target(app: &mut App, position: Vec3) -> Entity {
+        app.world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .insert(Transform::from_translation(position))
+            .id()
+    }
+
+    #[test]
+    fn decoys_lure_nearby_ground_enemies_until_they_expire() {
+        let mut app = App::new();
+        app.insert_resource(GameTime::stepped(Duration::from_secs_f32(0.1)))
+            .add_system(expire_decoys)
+            .add_system(lure_targets.after(expire_decoys));
+
+        app.world
+            .spawn()
+            .insert(Decoy {
+                duration: Timer::from_seconds(1.0, false),
+                radius: 2.0,
+            })
+            .insert(Transform::from_xyz(0.0, 0.15, 0.0));
+        let near = spawn_target(&mut app, Vec3::new(1.0, 0.0, 0.0));
+        let far = spawn_target(&mut app, Vec3::new(5.0, 0.0, 0.0));
+        let flyer = spawn_target(&mut app, Vec3::new(0.0, 1.0, 0.0));
+        app.world.entity_mut(flyer).insert(Flying { altitude: 1.0 });
+
+        app.update();
+        let lured = app
+            .world
+            .get::<Lured>(near)
+            .expect("nearby enemy wasn't lured");
+        assert_eq!(lured.position, Vec3::ZERO);
+        assert!(app.world.get::<Lured>(far).is_none());
+        assert!(app.world.get::<Lured>(flyer).is_none());
+
+        for _ in 0..15 {
+            app.update();
+        }
+        assert!(app.world.get::<Lured>(near).is_none());
+    }
+}
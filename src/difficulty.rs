@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::*;
+
+const DIFFICULTY_FILE: &str = "difficulty.ron";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+impl Difficulty {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Easy => Self::Normal,
+            Self::Normal => Self::Hard,
+            Self::Hard => Self::Easy,
+        }
+    }
+
+    /// The last difficulty the player picked, or `Normal` if there isn't a readable one.
+    pub fn load() -> Self {
+        fs::read_to_string(DIFFICULTY_FILE)
+            .ok()
+            .and_then(|contents| ron::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(self) {
+        let contents = match ron::to_string(&self) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!(?error, "Failed to serialize difficulty");
+                return;
+            }
+        };
+
+        if let Err(error) = fs::write(DIFFICULTY_FILE, contents) {
+            warn!(?error, "Failed to save difficulty");
+        }
+    }
+}
+
+/// Everything a `Difficulty` changes. It takes effect when a new game starts, through
+/// `reset_game_resources`, so switching mid-game waits for the next one. `Normal` matches the
+/// plain defaults of the resources it sets.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DifficultySettings {
+    pub difficulty: Difficulty,
+    pub starting_gold: u32,
+    pub lives: u32,
+    pub enemy_health: f32,
+    pub enemy_speed: f32,
+    pub reward: f32,
+    /// Seconds between waves.
+    pub countdown: f32,
+}
+
+impl Default for DifficultySettings {
+    fn default() -> Self {
+        Self::new(Difficulty::default())
+    }
+}
+
+impl DifficultySettings {
+    pub fn new(difficulty: Difficulty) -> Self {
+        match difficulty {
+            Difficulty::Easy => Self {
+                difficulty,
+                starting_gold: 150,
+                lives: 20,
+                enemy_health: 0.75,
+                enemy_speed: 0.9,
+                reward: 1.2,
+                countdown: 12.0,
+            },
+            Difficulty::Normal => Self {
+                difficulty,
+                starting_gold: 100,
+                lives: 10,
+                enemy_health: 1.0,
+                enemy_speed: 1.0,
+                reward: 1.0,
+                countdown: 10.0,
+            },
+            Difficulty::Hard => Self {
+                difficulty,
+                starting_gold: 75,
+                lives: 5,
+                enemy_health: 1.3,
+                enemy_speed: 1.15,
+                reward: 0.85,
+                countdown: 6.0,
+            },
+        }
+    }
+
+    /// Applies the difficulty on top of a wave's own scaling.
+    pub fn scale(&self, scaling: EnemyScaling) -> EnemyScaling {
+        EnemyScaling {
+            health: scaling.health * self.enemy_health,
+            speed: scaling.speed * self.enemy_speed,
+            reward: scaling.reward * self.reward,
+        }
+    }
+
+    pub fn countdown(&self) -> WaveCountdown {
+        WaveCountdown {
+            timer: Timer::from_seconds(self.countdown, false),
+            ..default()
+        }
+    }
+}
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DifficultySettings::new(Difficulty::load()))
+            .add_system(save_difficulty)
+            // Starting from the main menu doesn't pass through `Restarting`, so the difficulty
+            // picked there is applied on the way out.
+            .add_system_set(
+                SystemSet::on_exit(GameState::MainMenu).with_system(reset_game_resources),
+            );
+    }
+}
+
+fn save_difficulty(settings: Res<DifficultySettings>) {
+    if settings.is_changed() && !settings.is_added() {
+        settings.difficulty.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Starts a new game on `difficulty` and returns the lives it starts with, along with the
+    /// multiplier its enemies' health is scaled by.
+    fn new_game(difficulty: Difficulty) -> (u32, f32) {
+        let mut app = App::new();
+        app.insert_resource(DifficultySettings::new(difficulty))
+            .insert_resource(GameRng::new(0))
+            .init_resource::<Currency>()
+            .init_resource::<Income>()
+            .init_resource::<Lives>()
+            .init_resource::<Score>()
+            .init_resource::<Wave>()
+            .init_resource::<WaveCountdown>()
+            .init_resource::<WaveStats>()
+            .init_resource::<Selection>()
+            .init_resource::<BulletPool>()
+            .init_resource::<ParticleSystem>()
+            .init_resource::<GridMap>()
+            .init_resource::<BuildSelection>()
+            .init_resource::<GameSpeed>()
+            .init_resource::<PlacementHistory>()
+            .add_system(reset_game_resources);
+
+        app.update();
+
+        let health = app
+            .world
+            .resource::<DifficultySettings>()
+            .scale(EnemyScaling::default())
+            .health;
+        (app.world.resource::<Lives>().count, health)
+    }
+
+    #[test]
+    fn hard_starts_with_fewer_lives_against_tougher_enemies() {
+        let (easy_lives, easy_health) = new_game(Difficulty::Easy);
+        let (hard_lives, hard_health) = new_game(Difficulty::Hard);
+
+        assert!(hard_lives < easy_lives);
+        assert!(hard_health > easy_health);
+    }
+}
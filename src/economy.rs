@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+pub struct Currency {
+    pub gold: u32,
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Self { gold: 100 }
+    }
+}
+
+impl Currency {
+    pub fn can_afford(&self, cost: u32) -> bool {
+        self.gold >= cost
+    }
+
+    /// Deducts `cost` if affordable, returning whether the purchase went through.
+    pub fn spend(&mut self, cost: u32) -> bool {
+        if !self.can_afford(cost) {
+            return false;
+        }
+
+        self.gold -= cost;
+        true
+    }
+}
+
+/// Gold that trickles in over time on top of kill rewards. Fractions carry over between ticks
+/// in `pending` until they add up to a whole coin.
+pub struct Income {
+    pub per_second: f32,
+    pub timer: Timer,
+    pub pending: f32,
+}
+
+impl Default for Income {
+    fn default() -> Self {
+        Self {
+            per_second: 1.0,
+            timer: Timer::from_seconds(0.5, true),
+            pending: 0.0,
+        }
+    }
+}
+
+pub struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Currency>()
+            .init_resource::<Income>()
+            .add_system_set_to_stage(
+                GameStep,
+                SystemSet::new()
+                    .with_system(award_kill_gold)
+                    .with_system(passive_income),
+            );
+    }
+}
+
+fn award_kill_gold(mut death_events: EventReader<TargetDeath>, mut currency: ResMut<Currency>) {
+    for death in death_events.iter() {
+        currency.gold += death.reward;
+    }
+}
+
+/// Only runs in `Next`, so no income builds up while paused or on the game over screen.
+fn passive_income(mut income: ResMut<Income>, mut currency: ResMut<Currency>, time: Res<GameTime>) {
+    income.timer.tick(time.delta());
+
+    let ticks = income.timer.times_finished();
+
+    if ticks == 0 {
+        return;
+    }
+
+    income.pending += income.per_second * income.timer.duration().as_secs_f32() * ticks as f32;
+
+    let whole = income.pending.floor();
+    income.pending -= whole;
+    currency.gold += whole as u32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn income_trickles_in_over_time() {
+        let mut app = App::new();
+
+        app.init_resource::<Currency>()
+            .init_resource::<Income>()
+            .insert_resource(GameTime::stepped(Duration::from_secs_f32(0.25)))
+            .add_system(passive_income);
+
+        // Four seconds at a gold a second.
+        for _ in 0..16 {
+            app.update();
+        }
+
+        assert_eq!(app.world.resource::<Currency>().gold, 104);
+    }
+}
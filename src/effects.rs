@@ -0,0 +1,278 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+pub const SLOW_DURATION: f32 = 2.0;
+pub const POISON_TICK: f32 = 0.5;
+pub const STUN_IMMUNITY: f32 = 2.0;
+
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct Slow {
+    pub factor: f32,
+    pub timer: Timer,
+}
+
+impl Slow {
+    pub fn new(factor: f32) -> Self {
+        Self {
+            factor,
+            timer: Timer::from_seconds(SLOW_DURATION, false),
+        }
+    }
+}
+
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct Poison {
+    pub dps: f32,
+    pub timer: Timer,
+    pub tick: Timer,
+    /// Fractional damage carried between ticks so the total always adds up to `dps * duration`.
+    pub pending: f32,
+}
+
+impl Poison {
+    pub fn new(dps: f32, duration: f32) -> Self {
+        Self {
+            dps,
+            timer: Timer::from_seconds(duration, false),
+            tick: Timer::from_seconds(POISON_TICK, true),
+            pending: 0.0,
+        }
+    }
+
+    /// Re-applies poison to an already poisoned target. Damage never stacks, only the duration.
+    pub fn refresh(&mut self, dps: f32, duration: f32, mode: PoisonRefresh) {
+        self.dps = self.dps.max(dps);
+
+        match mode {
+            PoisonRefresh::Extend => {
+                let extended = self.timer.duration() + std::time::Duration::from_secs_f32(duration);
+                self.timer.set_duration(extended);
+            }
+            PoisonRefresh::Reset => {
+                self.timer
+                    .set_duration(std::time::Duration::from_secs_f32(duration));
+                self.timer.reset();
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PoisonRefresh {
+    /// Adds the new duration on top of whatever is left.
+    Extend,
+    /// Restarts the poison with the new duration.
+    Reset,
+}
+
+/// Holds an enemy in place until `timer` runs out. It then can't be stunned again for
+/// `STUN_IMMUNITY` seconds, so stun towers can't keep it pinned down forever.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct Stun {
+    pub timer: Timer,
+}
+
+impl Stun {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(duration, false),
+        }
+    }
+}
+
+/// Shrugs off stuns until `timer` runs out. Put on an enemy as soon as its `Stun` ends.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct StunImmune {
+    pub timer: Timer,
+}
+
+pub struct PoisonSettings {
+    pub refresh: PoisonRefresh,
+}
+
+impl Default for PoisonSettings {
+    fn default() -> Self {
+        Self {
+            refresh: PoisonRefresh::Extend,
+        }
+    }
+}
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Slow>()
+            .register_type::<Poison>()
+            .register_type::<Stun>()
+            .register_type::<StunImmune>()
+            .init_resource::<PoisonSettings>()
+            .add_system_set_to_stage(
+                GameStep,
+                SystemSet::new()
+                    .with_system(slow_expire)
+                    .with_system(poison_tick)
+                    .with_system(stun_expire)
+                    .with_system(stun_immunity_expire),
+            );
+    }
+}
+
+fn slow_expire(
+    mut commands: Commands,
+    mut slowed: Query<(Entity, &mut Slow)>,
+    time: Res<GameTime>,
+) {
+    for (entity, mut slow) in &mut slowed {
+        slow.timer.tick(time.delta());
+
+        if slow.timer.finished() {
+            commands.entity(entity).remove::<Slow>();
+        }
+    }
+}
+
+/// Poison keeps running out on an invulnerable enemy, but the ticks that land while it's
+/// invulnerable do nothing.
+fn poison_tick(
+    mut commands: Commands,
+    mut poisoned: Query<(Entity, &mut Poison, &mut Health, Option<&Invulnerable>)>,
+    time: Res<GameTime>,
+) {
+    for (entity, mut poison, mut health, invulnerable) in &mut poisoned {
+        poison.tick.tick(time.delta());
+        poison.timer.tick(time.delta());
+
+        if invulnerable.is_none() {
+            for _ in 0..poison.tick.times_finished() {
+                poison.pending += poison.dps * POISON_TICK;
+            }
+
+            let damage = poison.pending.floor();
+            poison.pending -= damage;
+            health.value -= damage as i32;
+        }
+
+        if poison.timer.finished() {
+            commands.entity(entity).remove::<Poison>();
+        }
+    }
+}
+
+fn stun_expire(
+    mut commands: Commands,
+    mut stunned: Query<(Entity, &mut Stun)>,
+    time: Res<GameTime>,
+) {
+    for (entity, mut stun) in &mut stunned {
+        stun.timer.tick(time.delta());
+
+        if stun.timer.finished() {
+            commands.entity(entity).remove::<Stun>().insert(StunImmune {
+                timer: Timer::from_seconds(STUN_IMMUNITY, false),
+            });
+        }
+    }
+}
+
+fn stun_immunity_expire(
+    mut commands: Commands,
+    mut immune: Query<(Entity, &mut StunImmune)>,
+    time: Res<GameTime>,
+) {
+    for (entity, mut immunity) in &mut immune {
+        immunity.timer.tick(time.delta());
+
+        if immunity.timer.finished() {
+            commands.entity(entity).remove::<StunImmune>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn app() -> App {
+        let mut app = App::new();
+
+        app.insert_resource(GameTime::stepped(Duration::from_secs_f32(POISON_TICK)))
+            .add_system(poison_tick);
+
+        app
+    }
+
+    #[test]
+    fn poison_runs_out_without_hurting_invulnerable_enemies() {
+        let mut app = app();
+        let target = app
+            .world
+            .spawn()
+            .insert(Health { value: 10, max: 10 })
+            .insert(Poison::new(2.0, 2.0))
+            .insert(Invulnerable)
+            .id();
+
+        app.update();
+        app.update();
+
+        assert_eq!(app.world.get::<Health>(target).unwrap().value, 10);
+
+        app.world.entity_mut(target).remove::<Invulnerable>();
+        app.update();
+        app.update();
+
+        assert_eq!(app.world.get::<Health>(target).unwrap().value, 8);
+        assert!(app.world.get::<Poison>(target).is_none());
+    }
+
+    #[test]
+    fn poison_deals_its_dps_for_its_whole_duration() {
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+        let (dps, duration) = config.tower(TowerType::Poison).poison.unwrap();
+        let mut app = app();
+        let target = app
+            .world
+            .spawn()
+            .insert(Health { value: 50, max: 50 })
+            .insert(Poison::new(dps, duration))
+            .id();
+
+        // Well past the end, to show it stops once it runs out.
+        for _ in 0..(duration / POISON_TICK) as usize * 2 {
+            app.update();
+        }
+
+        let dealt = 50 - app.world.get::<Health>(target).unwrap().value;
+        assert_eq!(dealt, (dps * duration) as i32);
+        assert!(app.world.get::<Poison>(target).is_none());
+    }
+
+    #[test]
+    fn stuns_wear_off_into_a_spell_of_immunity() {
+        let mut app = App::new();
+        app.insert_resource(GameTime::stepped(Duration::from_millis(500)))
+            .add_system(stun_expire)
+            .add_system(stun_immunity_expire);
+        let target = app.world.spawn().insert(Stun::new(1.0)).id();
+
+        app.update();
+        assert!(app.world.get::<Stun>(target).is_some());
+
+        app.update();
+        assert!(app.world.get::<Stun>(target).is_none());
+        assert!(app.world.get::<StunImmune>(target).is_some());
+
+        // `STUN_IMMUNITY` is two seconds.
+        for _ in 0..4 {
+            app.update();
+        }
+        assert!(app.world.get::<StunImmune>(target).is_none());
+    }
+}
@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// A UI label pinned to a point in the world that drifts by `velocity` and fades out over its
+/// `Lifetime`.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct FloatingText {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    pub initial_lifetime: f32,
+}
+
+pub struct FloatingTextSettings {
+    pub lifetime: f32,
+    pub rise_speed: f32,
+    pub font_size: f32,
+    pub color: Color,
+    pub crit_color: Color,
+    pub heal_color: Color,
+    pub gold_color: Color,
+}
+
+impl Default for FloatingTextSettings {
+    fn default() -> Self {
+        Self {
+            lifetime: 0.8,
+            rise_speed: 1.0,
+            font_size: 24.0,
+            color: Color::WHITE,
+            crit_color: Color::YELLOW,
+            heal_color: Color::GREEN,
+            gold_color: Color::GOLD,
+        }
+    }
+}
+
+pub struct FloatingTextPlugin;
+
+impl Plugin for FloatingTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<FloatingText>()
+            .init_resource::<FloatingTextSettings>()
+            .add_system_set_to_stage(
+                GameStep,
+                SystemSet::new()
+                    .with_system(spawn_damage_numbers.after(BulletSystem::Collision))
+                    .with_system(floating_text_update),
+            );
+    }
+}
+
+fn spawn_damage_numbers(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageDealt>,
+    settings: Res<FloatingTextSettings>,
+    assets: Res<GameAssets>,
+) {
+    for damage in damage_events.iter() {
+        let color = if damage.crit {
+            settings.crit_color
+        } else {
+            settings.color
+        };
+
+        spawn_floating_text(
+            &mut commands,
+            &assets,
+            &settings,
+            damage.amount.to_string(),
+            color,
+            damage.position,
+        );
+    }
+}
+
+/// Spawns a label rising from `position`, styled and timed by `settings`.
+pub fn spawn_floating_text(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    settings: &FloatingTextSettings,
+    text: String,
+    color: Color,
+    position: Vec3,
+) -> Entity {
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                text,
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: settings.font_size,
+                    color,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            }),
+        )
+        .insert(FloatingText {
+            position,
+            velocity: Vec3::Y * settings.rise_speed,
+            initial_lifetime: settings.lifetime,
+        })
+        .insert(Lifetime {
+            timer: Timer::from_seconds(settings.lifetime, false),
+        })
+        .insert(Gameplay)
+        .insert(Name::new("FloatingText"))
+        .id()
+}
+
+/// Moves each label through the world and projects it back onto the screen, hiding it while its
+/// point is behind the camera.
+fn floating_text_update(
+    mut texts: Query<(
+        &mut FloatingText,
+        &mut Style,
+        &mut Text,
+        &mut Visibility,
+        &Lifetime,
+    )>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    time: Res<GameTime>,
+) {
+    let (camera, camera_transform) = match cameras.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+
+    for (mut floating, mut style, mut text, mut visibility, lifetime) in &mut texts {
+        floating.position += floating.velocity * time.delta_seconds();
+
+        match camera.world_to_viewport(camera_transform, floating.position) {
+            Some(screen) => {
+                visibility.is_visible = true;
+                style.position.left = Val::Px(screen.x);
+                style.position.bottom = Val::Px(screen.y);
+            }
+            None => visibility.is_visible = false,
+        }
+
+        let remaining = lifetime.timer.duration().as_secs_f32() - lifetime.timer.elapsed_secs();
+        let alpha = (remaining / floating.initial_lifetime).clamp(0.0, 1.0);
+
+        for section in &mut text.sections {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hits_float_up_the_damage_they_dealt() {
+        let mut app = App::new();
+
+        app.insert_resource(GameAssets::headless())
+            .init_resource::<FloatingTextSettings>()
+            .add_event::<DamageDealt>()
+            .add_system(spawn_damage_numbers);
+
+        let target = app.world.spawn().id();
+        app.world
+            .resource_mut::<Events<DamageDealt>>()
+            .send(DamageDealt {
+                target,
+                amount: 7,
+                position: Vec3::new(1.0, 0.5, 2.0),
+                crit: false,
+            });
+        app.update();
+
+        let labels: Vec<_> = app
+            .world
+            .query::<(&FloatingText, &Text)>()
+            .iter(&app.world)
+            .map(|(floating, text)| (floating.position, text.sections[0].value.clone()))
+            .collect();
+
+        assert_eq!(labels, vec![(Vec3::new(1.0, 0.5, 2.0), "7".to_string())]);
+    }
+}
@@ -0,0 +1,232 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+pub struct Lives {
+    pub count: u32,
+}
+
+impl Default for Lives {
+    fn default() -> Self {
+        Self { count: 10 }
+    }
+}
+
+#[derive(Component)]
+pub struct GameOverUi;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameOverButton {
+    Restart,
+}
+
+/// Tags every top-level entity that belongs to a running game so it can be swept away on restart.
+#[derive(Component)]
+pub struct Gameplay;
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Lives>()
+            .add_system_set(SystemSet::on_update(GameState::Next).with_system(check_game_over))
+            .add_system_set(
+                SystemSet::on_enter(GameState::GameOver)
+                    .with_system(spawn_game_over_ui.after(RecordRun)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::GameOver).with_system(game_over_buttons),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver)
+                    .with_system(despawn_game_over_ui)
+                    .with_system(cleanup_gameplay_entities)
+                    .with_system(reset_game_resources),
+            );
+    }
+}
+
+fn check_game_over(lives: Res<Lives>, mut state: ResMut<State<GameState>>) {
+    if lives.count == 0 {
+        if let Err(error) = state.set(GameState::GameOver) {
+            warn!(?error, "Failed to enter game over state");
+        }
+    }
+}
+
+fn spawn_game_over_ui(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    leaderboard: Res<Leaderboard>,
+) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(GameOverUi)
+        .insert(Name::new("GameOverUi"))
+        .with_children(|commands| {
+            commands.spawn_bundle(TextBundle::from_section(
+                "Game Over",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 80.0,
+                    color: Color::WHITE,
+                },
+            ));
+            spawn_button(commands, &assets.font, "Restart", GameOverButton::Restart);
+            spawn_leaderboard(commands, &assets.font, &leaderboard);
+        });
+}
+
+fn game_over_buttons(
+    buttons: Query<(&Interaction, &GameOverButton), Changed<Interaction>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        match button {
+            GameOverButton::Restart => {
+                if let Err(error) = state.set(GameState::Next) {
+                    warn!(?error, "Failed to restart game");
+                }
+            }
+        }
+    }
+}
+
+pub fn cleanup_gameplay_entities(mut commands: Commands, entities: Query<Entity, With<Gameplay>>) {
+    for entity in &entities {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Puts every per-run resource back to its starting value, including the wave spawn timer.
+pub fn reset_game_resources(
+    mut currency: ResMut<Currency>,
+    mut income: ResMut<Income>,
+    mut lives: ResMut<Lives>,
+    mut score: ResMut<Score>,
+    mut wave: ResMut<Wave>,
+    mut countdown: ResMut<WaveCountdown>,
+    mut wave_stats: ResMut<WaveStats>,
+    mut selection: ResMut<Selection>,
+    mut pool: ResMut<BulletPool>,
+    mut particles: ResMut<ParticleSystem>,
+    mut grid: ResMut<GridMap>,
+    mut build: ResMut<BuildSelection>,
+    mut speed: ResMut<GameSpeed>,
+    mut rng: ResMut<GameRng>,
+    mut history: ResMut<PlacementHistory>,
+    difficulty: Res<DifficultySettings>,
+) {
+    *currency = Currency {
+        gold: difficulty.starting_gold,
+    };
+    *income = Income::default();
+    *lives = Lives {
+        count: difficulty.lives,
+    };
+    *score = Score::default();
+    *wave = Wave::default();
+    *countdown = difficulty.countdown();
+    *wave_stats = WaveStats::default();
+    *selection = Selection::default();
+    *pool = BulletPool::default();
+    *particles = ParticleSystem::default();
+    grid.clear_occupied();
+    *build = BuildSelection::default();
+    *speed = GameSpeed::default();
+    rng.reseed();
+    history.clear();
+}
+
+fn despawn_game_over_ui(mut commands: Commands, ui: Query<Entity, With<GameOverUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn running_out_of_lives_ends_the_game() {
+        let mut app = App::new();
+        app.add_state(GameState::Next)
+            .init_resource::<Lives>()
+            .add_system_set(SystemSet::on_update(GameState::Next).with_system(check_game_over));
+
+        app.update();
+        assert_eq!(
+            app.world.resource::<State<GameState>>().current(),
+            &GameState::Next
+        );
+
+        app.world.resource_mut::<Lives>().count = 0;
+        app.update();
+
+        assert_eq!(
+            app.world.resource::<State<GameState>>().current(),
+            &GameState::GameOver
+        );
+    }
+
+    #[test]
+    fn restarting_after_game_over_starts_a_fresh_run() {
+        let mut app = headless_app(Headless {
+            step: 0.1,
+            ..default()
+        });
+        app.update();
+        app.world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .insert(Gameplay);
+        app.world.resource_mut::<Currency>().gold = 3;
+        app.world.resource_mut::<Lives>().count = 0;
+
+        app.update();
+        assert_eq!(
+            app.world.resource::<State<GameState>>().current(),
+            &GameState::GameOver
+        );
+
+        app.world
+            .spawn()
+            .insert(GameOverButton::Restart)
+            .insert(Interaction::Clicked);
+        app.update();
+
+        let difficulty = app.world.resource::<DifficultySettings>();
+        let (gold, lives) = (difficulty.starting_gold, difficulty.lives);
+        assert_eq!(
+            app.world.resource::<State<GameState>>().current(),
+            &GameState::Next
+        );
+        assert_eq!(app.world.resource::<Lives>().count, lives);
+        assert_eq!(app.world.resource::<Currency>().gold, gold);
+        assert_eq!(
+            app.world
+                .query_filtered::<(), With<Target>>()
+                .iter(&app.world)
+                .count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,417 @@
+use bevy::{ecs::schedule::ShouldRun, prelude::*, transform::TransformSystem};
+use std::time::Duration;
+
+use crate::*;
+
+const SPEEDS: [f32; 3] = [1.0, 2.0, 4.0];
+
+pub struct GameSpeed {
+    pub multiplier: f32,
+}
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self { multiplier: 1.0 }
+    }
+}
+
+impl GameSpeed {
+    /// The next speed in the 1x, 2x, 4x cycle.
+    pub fn next(&self) -> f32 {
+        SPEEDS
+            .iter()
+            .copied()
+            .find(|speed| *speed > self.multiplier)
+            .unwrap_or(SPEEDS[0])
+    }
+
+    /// How many steps to take each frame when steps don't follow the clock, as in headless runs
+    /// and replays.
+    pub fn steps_per_frame(&self) -> u32 {
+        self.multiplier.round().max(1.0) as u32
+    }
+}
+
+/// Gameplay runs in the `GameStep` stage, once for every step owed this frame, and `delta` is
+/// the length of one step. Gameplay systems read this instead of `Time`, so every step moves
+/// things by the same amount, and fast-forward takes more steps rather than longer ones.
+#[derive(Default)]
+pub struct GameTime {
+    delta: Duration,
+    remaining: u32,
+    alpha: f32,
+}
+
+impl GameTime {
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    /// How far the clock has got from the last step toward the next, from 0 to 1.
+    /// `Interpolated` transforms are drawn this far between their last two steps.
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    /// A clock on a single step of `delta`, for running gameplay systems on their own.
+    #[cfg(test)]
+    pub fn stepped(delta: Duration) -> Self {
+        Self {
+            delta,
+            remaining: 1,
+            alpha: 1.0,
+        }
+    }
+
+    fn begin_frame(&mut self, step: Duration, steps: u32, alpha: f32) {
+        self.delta = step;
+        self.remaining = steps;
+        self.alpha = alpha;
+    }
+
+    /// Counts off one of this frame's steps, if any are left.
+    fn take_step(&mut self) -> ShouldRun {
+        match self.remaining {
+            0 => ShouldRun::No,
+            1 => {
+                self.remaining = 0;
+                ShouldRun::Yes
+            }
+            _ => {
+                self.remaining -= 1;
+                ShouldRun::YesAndCheckAgain
+            }
+        }
+    }
+}
+
+/// Advances gameplay in whole steps of `step` seconds rather than by however long the frame
+/// took, carrying the remainder over to the next frame. Frame rate then only decides how many
+/// steps land in each frame, not how long they are. `None` follows the frame time directly,
+/// one step per frame.
+pub struct FixedTimestep {
+    pub step: Option<f32>,
+    /// The most steps taken in one frame at 1x. Time past this after a long stall is dropped
+    /// rather than caught up on.
+    pub max_steps: u32,
+    accumulator: Duration,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self {
+            step: Some(1.0 / 60.0),
+            max_steps: 5,
+            accumulator: Duration::ZERO,
+        }
+    }
+}
+
+impl FixedTimestep {
+    /// Adds `elapsed` sped up by `speed` to the time owed and takes as many whole steps out of
+    /// it as fit, returning the step and how many were taken.
+    pub fn advance(&mut self, elapsed: Duration, speed: f32) -> (Duration, u32) {
+        let elapsed = elapsed.mul_f32(speed);
+        let step = match self.fixed_step() {
+            Some(step) => step,
+            None => return (elapsed, 1),
+        };
+
+        self.accumulator += elapsed;
+
+        let owed = (self.accumulator.as_secs_f64() / step.as_secs_f64()) as u32;
+        let steps = owed.min(self.max_steps * speed.ceil().max(1.0) as u32);
+
+        if owed > steps {
+            self.accumulator = Duration::ZERO;
+        } else {
+            self.accumulator -= step * steps;
+        }
+
+        (step, steps)
+    }
+
+    /// How much of the next step is already owed, from 0 to 1.
+    pub fn alpha(&self) -> f32 {
+        match self.fixed_step() {
+            Some(step) => (self.accumulator.as_secs_f32() / step.as_secs_f32()).min(1.0),
+            None => 1.0,
+        }
+    }
+
+    fn fixed_step(&self) -> Option<Duration> {
+        match self.step {
+            Some(step) if step > 0.0 => Some(Duration::from_secs_f32(step)),
+            _ => None,
+        }
+    }
+}
+
+/// Where something that moves during gameplay was after each of the last two steps. It's drawn
+/// part of the way between them by `GameTime::alpha`, so motion stays smooth on frames that run
+/// no step or several.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+}
+
+impl Interpolated {
+    pub fn new(translation: Vec3) -> Self {
+        Self {
+            previous: translation,
+            current: translation,
+        }
+    }
+}
+
+/// The stage gameplay runs in, right after `Update`. It runs once for each step in `GameTime`,
+/// and not at all outside of `GameState::Next`, so systems added to it need no state run
+/// criteria. Anything that changes the state has to stay in `Update`, where the state driver is.
+#[derive(StageLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameStep;
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UpdateGameTime;
+
+pub struct GameSpeedPlugin;
+
+impl Plugin for GameSpeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameSpeed>()
+            .init_resource::<GameTime>()
+            .init_resource::<FixedTimestep>()
+            .add_stage_after(
+                CoreStage::Update,
+                GameStep,
+                SystemStage::parallel().with_run_criteria(run_game_steps),
+            )
+            .add_system_to_stage(
+                GameStep,
+                restore_stepped_translations.exclusive_system().at_start(),
+            )
+            .add_system_to_stage(
+                GameStep,
+                store_stepped_translations.exclusive_system().at_end(),
+            )
+            .add_system_to_stage(CoreStage::PreUpdate, update_game_time.label(UpdateGameTime))
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                interpolate_translations.before(TransformSystem::TransformPropagate),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Next).with_system(toggle_game_speed));
+    }
+}
+
+/// Works out this frame's steps. Headless runs take a fixed number of steps every frame
+/// instead, so they don't wait on the wall clock, and so do replays while recording or playing
+/// back, so they come out the same every time. Otherwise time goes through `FixedTimestep`.
+/// Time outside of `Next` isn't owed, so pausing doesn't bank steps.
+fn update_game_time(
+    mut game_time: ResMut<GameTime>,
+    mut timestep: ResMut<FixedTimestep>,
+    speed: Res<GameSpeed>,
+    time: Res<Time>,
+    headless: Option<Res<Headless>>,
+    replay: Res<Replay>,
+    state: Res<State<GameState>>,
+) {
+    if *state.current() != GameState::Next {
+        game_time.remaining = 0;
+        return;
+    }
+
+    let fixed = match headless {
+        Some(headless) => Some(Duration::from_secs_f32(headless.step)),
+        None => replay.fixed_step(),
+    };
+
+    match fixed {
+        Some(step) => game_time.begin_frame(step, speed.steps_per_frame(), 1.0),
+        None => {
+            let (step, steps) = timestep.advance(time.delta(), speed.multiplier);
+            game_time.begin_frame(step, steps, timestep.alpha());
+        }
+    }
+}
+
+fn run_game_steps(mut game_time: ResMut<GameTime>) -> ShouldRun {
+    game_time.take_step()
+}
+
+/// Puts interpolated entities back where the last step left them before the next one moves them
+/// on.
+fn restore_stepped_translations(mut interpolated: Query<(&mut Interpolated, &mut Transform)>) {
+    for (mut interpolated, mut transform) in &mut interpolated {
+        transform.translation = interpolated.current;
+        interpolated.previous = interpolated.current;
+    }
+}
+
+fn store_stepped_translations(mut interpolated: Query<(&mut Interpolated, &Transform)>) {
+    for (mut interpolated, transform) in &mut interpolated {
+        interpolated.current = transform.translation;
+    }
+}
+
+fn interpolate_translations(
+    mut interpolated: Query<(&Interpolated, &mut Transform)>,
+    time: Res<GameTime>,
+) {
+    for (interpolated, mut transform) in &mut interpolated {
+        transform.translation = interpolated
+            .previous
+            .lerp(interpolated.current, time.alpha());
+    }
+}
+
+fn toggle_game_speed(
+    mut speed: ResMut<GameSpeed>,
+    mut replay: ResMut<Replay>,
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+) {
+    if !replay.accepts_input() {
+        return;
+    }
+
+    if bindings.is_action_just_pressed(&keyboard, Action::FastForward) {
+        speed.multiplier = speed.next();
+        replay.record(ReplayInput::SetSpeed {
+            multiplier: speed.multiplier,
+        });
+        info!("Game speed set to {}x", speed.multiplier);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::utils::Instant;
+
+    const STEP: f32 = 1.0 / 64.0;
+
+    fn timestep() -> FixedTimestep {
+        FixedTimestep {
+            step: Some(STEP),
+            max_steps: 8,
+            ..default()
+        }
+    }
+
+    fn drift(mut movers: Query<&mut Transform, With<Interpolated>>, time: Res<GameTime>) {
+        for mut transform in &mut movers {
+            transform.translation.x += time.delta_seconds();
+        }
+    }
+
+    fn run(frames: &[f32]) -> (Interpolated, Vec3) {
+        run_at(frames, 1.0)
+    }
+
+    /// Runs gameplay at `multiplier` for the given frame times and returns where the mover ended
+    /// up after its last step, along with how far along it's drawn.
+    fn run_at(frames: &[f32], multiplier: f32) -> (Interpolated, Vec3) {
+        let mut app = App::new();
+
+        app.add_state(GameState::Next)
+            .insert_resource(Time::default())
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<Replay>()
+            .add_plugin(GameSpeedPlugin)
+            .insert_resource(timestep())
+            .insert_resource(GameSpeed { multiplier })
+            .add_system_to_stage(GameStep, drift);
+
+        let mover = app
+            .world
+            .spawn()
+            .insert(Transform::default())
+            .insert(Interpolated::new(Vec3::ZERO))
+            .id();
+
+        let mut now = Instant::now();
+        app.world.resource_mut::<Time>().update_with_instant(now);
+
+        for frame in frames {
+            now += Duration::from_secs_f32(*frame);
+            app.world.resource_mut::<Time>().update_with_instant(now);
+            app.update();
+        }
+
+        let entity = app.world.entity(mover);
+        (
+            *entity.get::<Interpolated>().unwrap(),
+            entity.get::<Transform>().unwrap().translation,
+        )
+    }
+
+    #[test]
+    fn frame_pacing_does_not_change_the_steps_taken() {
+        let mut even = timestep();
+        let mut uneven = timestep();
+        let mut even_steps = 0;
+        let mut uneven_steps = 0;
+
+        for _ in 0..96 {
+            even_steps += even.advance(Duration::from_secs_f32(STEP), 1.0).1;
+        }
+        for frame in [STEP / 2.0, STEP * 1.5, STEP * 3.0, STEP * 3.0].repeat(12) {
+            uneven_steps += uneven.advance(Duration::from_secs_f32(frame), 1.0).1;
+        }
+
+        assert_eq!(even_steps, 96);
+        assert_eq!(uneven_steps, 96);
+    }
+
+    #[test]
+    fn fast_forward_takes_more_steps_of_the_same_length() {
+        let mut timestep = timestep();
+
+        let (step, steps) = timestep.advance(Duration::from_secs_f32(STEP), 4.0);
+
+        assert_eq!(step, Duration::from_secs_f32(STEP));
+        assert_eq!(steps, 4);
+    }
+
+    #[test]
+    fn long_stalls_are_dropped_past_max_steps() {
+        let mut timestep = timestep();
+
+        assert_eq!(timestep.advance(Duration::from_secs(5), 1.0).1, 8);
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+
+    #[test]
+    fn gameplay_is_the_same_whatever_the_frame_rate() {
+        let (fast, _) = run(&[STEP / 2.0; 192]);
+        let (slow, _) = run(&[STEP * 2.0; 48]);
+        let (jittery, _) = run(&[STEP / 2.0, STEP * 1.5, STEP * 3.0, STEP * 3.0].repeat(12));
+
+        assert_eq!(fast.current, slow.current);
+        assert_eq!(fast.current, jittery.current);
+        assert_eq!(fast.current.x, 96.0 * STEP);
+    }
+
+    #[test]
+    fn frames_between_steps_still_move_the_drawn_position() {
+        let (stepped, drawn) = run(&[STEP, STEP / 2.0]);
+
+        assert_eq!(stepped.current.x, STEP);
+        assert_eq!(drawn.x, STEP * 0.5);
+    }
+
+    #[test]
+    fn double_speed_covers_twice_the_ground_in_the_same_time() {
+        let (normal, _) = run_at(&[STEP; 32], 1.0);
+        let (doubled, _) = run_at(&[STEP; 32], 2.0);
+
+        assert_eq!(normal.current.x, 32.0 * STEP);
+        assert_eq!(doubled.current.x, 64.0 * STEP);
+    }
+}
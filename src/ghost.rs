@@ -0,0 +1,241 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// The translucent stand-in that follows the cursor while building. `valid` says whether a
+/// click would actually place a tower on the cell underneath it.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct PlacementGhost {
+    pub valid: bool,
+    /// Range of the tower that would be built, for its `ShowRange` ring.
+    pub range: f32,
+}
+
+/// Follows the cursor while building, naming the selected tower type and its cost.
+#[derive(Component)]
+pub struct BuildLabel;
+
+pub struct PlacementGhostPlugin;
+
+impl Plugin for PlacementGhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PlacementGhost>().add_system_set(
+            SystemSet::on_update(GameState::Next)
+                .with_system(select_build_type)
+                .with_system(spawn_placement_ghost.after(select_build_type))
+                .with_system(update_placement_ghost)
+                .with_system(update_build_label),
+        );
+    }
+}
+
+/// Pressing a tower's key selects it for building, pressing it again cancels.
+fn select_build_type(
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut build: ResMut<BuildSelection>,
+) {
+    for tower_type in TowerType::ALL {
+        if bindings.is_action_just_pressed(&keyboard, Action::SelectTower(tower_type)) {
+            build.tower_type = if build.tower_type == Some(tower_type) {
+                None
+            } else {
+                Some(tower_type)
+            };
+        }
+    }
+}
+
+/// Respawns the ghost and its label whenever the build selection changes, and despawns them
+/// when building ends, whether by building, cancelling or leaving the game.
+fn spawn_placement_ghost(
+    mut commands: Commands,
+    ghosts: Query<Entity, Or<(With<PlacementGhost>, With<BuildLabel>)>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    build: Res<BuildSelection>,
+    grid: Res<GridMap>,
+    config: Res<GameConfig>,
+    assets: Res<GameAssets>,
+) {
+    if !build.is_changed() {
+        return;
+    }
+
+    for ghost in &ghosts {
+        commands.entity(ghost).despawn_recursive();
+    }
+
+    let tower_type = match build.tower_type {
+        Some(tower_type) => tower_type,
+        None => return,
+    };
+
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                format!("{:?} ({} gold)", tower_type, config.tower(tower_type).cost),
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 24.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                ..default()
+            }),
+        )
+        .insert(BuildLabel)
+        .insert(Gameplay)
+        .insert(Name::new("BuildLabel"));
+
+    commands
+        .spawn_bundle(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Box::new(
+                grid.cell_size * 0.8,
+                1.5,
+                grid.cell_size * 0.8,
+            ))),
+            material: materials.add(StandardMaterial {
+                base_color: Color::rgba(0.2, 1.0, 0.2, 0.4),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            }),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(PlacementGhost {
+            valid: false,
+            range: 0.0,
+        })
+        .insert(ShowRange)
+        .insert(Gameplay)
+        .insert(Name::new("PlacementGhost"));
+}
+
+fn update_placement_ghost(
+    mut ghosts: Query<(
+        &mut PlacementGhost,
+        &mut Transform,
+        &mut Visibility,
+        &Handle<StandardMaterial>,
+    )>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    windows: Res<Windows>,
+    grid: Res<GridMap>,
+    currency: Res<Currency>,
+    config: Res<GameConfig>,
+    build: Res<BuildSelection>,
+) {
+    let tower_type = match build.tower_type {
+        Some(tower_type) => tower_type,
+        None => return,
+    };
+
+    let (camera, camera_transform) = match cameras.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+
+    let hit = cursor_to_ground(&windows, camera, camera_transform);
+
+    for (mut ghost, mut transform, mut visibility, material) in &mut ghosts {
+        let hit = match hit {
+            Some(hit) => hit,
+            None => {
+                visibility.is_visible = false;
+                ghost.valid = false;
+                continue;
+            }
+        };
+
+        let cell = grid.world_to_cell(hit);
+        let tower_config = config.tower(tower_type);
+        ghost.valid = can_build(&grid, &currency, tower_config.cost, cell);
+        ghost.range = tower_config.range;
+        visibility.is_visible = true;
+        transform.translation = grid.cell_to_world(cell) + Vec3::Y * 0.75;
+
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color = if ghost.valid {
+                Color::rgba(0.2, 1.0, 0.2, 0.4)
+            } else {
+                Color::rgba(1.0, 0.2, 0.2, 0.4)
+            };
+        }
+    }
+}
+
+/// Whether a tower costing `cost` could go on `cell` right now.
+fn can_build(grid: &GridMap, currency: &Currency, cost: u32, cell: IVec2) -> bool {
+    grid.is_free(cell) && currency.can_afford(cost)
+}
+
+/// Keeps the label just below and to the right of the cursor, hiding it when the cursor leaves
+/// the window.
+fn update_build_label(mut labels: Query<&mut Style, With<BuildLabel>>, windows: Res<Windows>) {
+    let cursor = windows
+        .get_primary()
+        .and_then(|window| Some((window.cursor_position()?, window.height())));
+
+    for mut style in &mut labels {
+        match cursor {
+            // The cursor is measured from the bottom of the window, UI from the top.
+            Some((cursor, height)) => {
+                style.display = Display::Flex;
+                style.position = UiRect {
+                    left: Val::Px(cursor.x + 16.0),
+                    top: Val::Px(height - cursor.y + 16.0),
+                    ..default()
+                };
+            }
+            None => style.display = Display::None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ghosts_over_blocked_cells_are_invalid() {
+        let mut grid = GridMap::default();
+        let currency = Currency { gold: 100 };
+        grid.blocked.insert(IVec2::new(1, 0));
+
+        assert!(!can_build(&grid, &currency, 50, IVec2::new(1, 0)));
+        assert!(can_build(&grid, &currency, 50, IVec2::new(1, 1)));
+        assert!(!can_build(&grid, &currency, 150, IVec2::new(1, 1)));
+    }
+
+    /// Taps `key_code` and returns what's selected for building afterward.
+    fn tap(app: &mut App, key_code: KeyCode) -> Option<TowerType> {
+        let mut keyboard = app.world.resource_mut::<Input<KeyCode>>();
+        keyboard.release(key_code);
+        keyboard.clear();
+        keyboard.press(key_code);
+        app.update();
+
+        app.world.resource::<BuildSelection>().tower_type
+    }
+
+    #[test]
+    fn number_keys_select_their_tower_for_building() {
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<BuildSelection>()
+            .add_system(select_build_type);
+
+        assert_eq!(tap(&mut app, KeyCode::Key2), Some(TowerType::Sniper));
+        assert_eq!(tap(&mut app, KeyCode::Key1), Some(TowerType::Cannon));
+        // Pressing the selected tower's key again cancels building.
+        assert_eq!(tap(&mut app, KeyCode::Key1), None);
+    }
+}
@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// Makes a tower pay out gold on a timer instead of shooting. Each payout is `amount` per tower
+/// level, so upgrades raise the yield.
+#[derive(Component)]
+pub struct GoldMine {
+    pub amount: u32,
+    pub timer: Timer,
+}
+
+impl GoldMine {
+    pub fn new(amount: u32, interval: f32) -> Self {
+        Self {
+            amount,
+            timer: Timer::from_seconds(interval, true),
+        }
+    }
+
+    pub fn payout(&self, level: u8) -> u32 {
+        self.amount * u32::from(level)
+    }
+}
+
+pub struct GoldMined {
+    pub tower: Entity,
+    pub amount: u32,
+    pub position: Vec3,
+}
+
+pub struct GoldMinePlugin;
+
+impl Plugin for GoldMinePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GoldMined>().add_system_set_to_stage(
+            GameStep,
+            SystemSet::new()
+                .with_system(gold_mine_production)
+                .with_system(spawn_coin_popups.after(gold_mine_production)),
+        );
+    }
+}
+
+/// Only runs in `Next`, so mines stop producing while paused or on the game over screen.
+fn gold_mine_production(
+    mut mines: Query<(Entity, &Tower, &mut GoldMine, &GlobalTransform)>,
+    mut currency: ResMut<Currency>,
+    mut mined_events: EventWriter<GoldMined>,
+    time: Res<GameTime>,
+) {
+    for (entity, tower, mut mine, transform) in &mut mines {
+        mine.timer.tick(time.delta());
+
+        let payouts = mine.timer.times_finished();
+        if payouts == 0 {
+            continue;
+        }
+
+        let amount = mine.payout(tower.level) * payouts;
+        currency.gold += amount;
+        mined_events.send(GoldMined {
+            tower: entity,
+            amount,
+            position: transform.translation(),
+        });
+    }
+}
+
+fn spawn_coin_popups(
+    mut commands: Commands,
+    mut mined_events: EventReader<GoldMined>,
+    settings: Res<FloatingTextSettings>,
+    assets: Res<GameAssets>,
+) {
+    for mined in mined_events.iter() {
+        spawn_floating_text(
+            &mut commands,
+            &assets,
+            &settings,
+            format!("+{} gold", mined.amount),
+            settings.gold_color,
+            mined.position + Vec3::Y * 0.5,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn mines_pay_their_yield_each_time_the_timer_fires() {
+        let mut app = App::new();
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+        app.insert_resource(GameTime::stepped(Duration::from_millis(250)))
+            .init_resource::<Currency>()
+            .add_event::<GoldMined>()
+            .add_system(gold_mine_production);
+
+        let mut tower = Tower::new(TowerType::Cannon, &config, &GameAssets::headless());
+        tower.level = 2;
+        app.world
+            .spawn()
+            .insert(tower)
+            .insert(GoldMine::new(5, 1.0))
+            .insert(GlobalTransform::default());
+        let start = app.world.resource::<Currency>().gold;
+
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(app.world.resource::<Currency>().gold, start);
+
+        // A level 2 mine pays out twice its base amount.
+        app.update();
+        assert_eq!(app.world.resource::<Currency>().gold, start + 10);
+    }
+}
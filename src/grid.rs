@@ -0,0 +1,259 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::*;
+
+/// Which ground cells hold a tower and which can never be built on. Cells are centred on whole
+/// multiples of `cell_size` along X and Z.
+pub struct GridMap {
+    pub cell_size: f32,
+    /// How wide a strip along the enemy path is kept clear of towers.
+    pub path_width: f32,
+    pub occupied: HashMap<IVec2, Entity>,
+    pub blocked: HashSet<IVec2>,
+}
+
+impl Default for GridMap {
+    fn default() -> Self {
+        Self {
+            cell_size: 1.0,
+            path_width: 1.0,
+            occupied: HashMap::default(),
+            blocked: HashSet::default(),
+        }
+    }
+}
+
+impl GridMap {
+    pub fn world_to_cell(&self, position: Vec3) -> IVec2 {
+        IVec2::new(
+            (position.x / self.cell_size).round() as i32,
+            (position.z / self.cell_size).round() as i32,
+        )
+    }
+
+    /// The centre of `cell` on the ground plane.
+    pub fn cell_to_world(&self, cell: IVec2) -> Vec3 {
+        Vec3::new(
+            cell.x as f32 * self.cell_size,
+            0.0,
+            cell.y as f32 * self.cell_size,
+        )
+    }
+
+    pub fn is_free(&self, cell: IVec2) -> bool {
+        !self.blocked.contains(&cell) && !self.occupied.contains_key(&cell)
+    }
+
+    pub fn occupant(&self, cell: IVec2) -> Option<Entity> {
+        self.occupied.get(&cell).copied()
+    }
+
+    pub fn occupy(&mut self, cell: IVec2, entity: Entity) {
+        self.occupied.insert(cell, entity);
+    }
+
+    pub fn clear(&mut self, cell: IVec2) {
+        self.occupied.remove(&cell);
+    }
+
+    /// Frees every cell, leaving blocked cells alone.
+    pub fn clear_occupied(&mut self) {
+        self.occupied.clear();
+    }
+
+    /// Blocks every cell that overlaps a strip `path_width` wide along each segment of `path`.
+    /// The strip has rounded ends, so the cells around each corner are covered by both of the
+    /// segments that meet there.
+    pub fn block_path(&mut self, path: &Path) {
+        let reach = (self.path_width + self.cell_size) / 2.0;
+
+        for segment in path.waypoints.windows(2) {
+            let (from, to) = (
+                segment[0] * Vec3::new(1.0, 0.0, 1.0),
+                segment[1] * Vec3::new(1.0, 0.0, 1.0),
+            );
+            let min = self.world_to_cell(from.min(to) - Vec3::splat(reach));
+            let max = self.world_to_cell(from.max(to) + Vec3::splat(reach));
+
+            for x in min.x..=max.x {
+                for z in min.y..=max.y {
+                    let cell = IVec2::new(x, z);
+
+                    if distance_to_segment(self.cell_to_world(cell), from, to) < reach {
+                        self.blocked.insert(cell);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn distance_to_segment(point: Vec3, from: Vec3, to: Vec3) -> f32 {
+    let segment = to - from;
+    let length_squared = segment.length_squared();
+
+    if length_squared <= f32::EPSILON {
+        return point.distance(from);
+    }
+
+    let t = ((point - from).dot(segment) / length_squared).clamp(0.0, 1.0);
+    point.distance(from + segment * t)
+}
+
+#[derive(Component)]
+pub struct BlockedCellTint;
+
+/// The tower type the player is about to build, if any. While set, the blocked cells and the
+/// `PlacementGhost` are shown and clicking empty ground builds that type.
+#[derive(Default)]
+pub struct BuildSelection {
+    pub tower_type: Option<TowerType>,
+}
+
+impl BuildSelection {
+    pub fn is_active(&self) -> bool {
+        self.tower_type.is_some()
+    }
+}
+
+pub struct GridPlugin;
+
+impl Plugin for GridPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GridMap>()
+            .init_resource::<BuildSelection>()
+            .add_system(register_tower_cells)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Next)
+                    .with_system(spawn_blocked_cell_tints.after(LoadMap)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Next).with_system(show_blocked_cell_tints),
+            );
+    }
+}
+
+fn spawn_blocked_cell_tints(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    grid: Res<GridMap>,
+    build: Res<BuildSelection>,
+) {
+    let mesh = meshes.add(Mesh::from(shape::Plane {
+        size: grid.cell_size,
+    }));
+    let material = materials.add(StandardMaterial {
+        base_color: Color::rgba(1.0, 0.2, 0.2, 0.3),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+
+    for cell in &grid.blocked {
+        commands
+            .spawn_bundle(PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                // Lifted slightly so the tint doesn't z-fight with the ground.
+                transform: Transform::from_translation(grid.cell_to_world(*cell) + Vec3::Y * 0.01),
+                visibility: Visibility {
+                    is_visible: build.is_active(),
+                },
+                ..default()
+            })
+            .insert(BlockedCellTint)
+            .insert(Gameplay)
+            .insert(Name::new("BlockedCellTint"));
+    }
+}
+
+fn show_blocked_cell_tints(
+    mut tints: Query<&mut Visibility, With<BlockedCellTint>>,
+    build: Res<BuildSelection>,
+) {
+    if !build.is_changed() {
+        return;
+    }
+
+    for mut visibility in &mut tints {
+        visibility.is_visible = build.is_active();
+    }
+}
+
+/// Marks the cell under every newly spawned tower, however it was spawned.
+fn register_tower_cells(
+    towers: Query<(Entity, &Transform), Added<TowerBase>>,
+    mut grid: ResMut<GridMap>,
+) {
+    for (entity, transform) in &towers {
+        let cell = grid.world_to_cell(transform.translation);
+        grid.occupy(cell, entity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn world_positions_round_to_the_nearest_cell() {
+        let grid = GridMap {
+            cell_size: 2.0,
+            ..default()
+        };
+
+        assert_eq!(grid.world_to_cell(Vec3::new(0.9, 3.0, -0.9)), IVec2::ZERO);
+        assert_eq!(
+            grid.world_to_cell(Vec3::new(3.1, 0.0, -4.9)),
+            IVec2::new(2, -2)
+        );
+        assert_eq!(
+            grid.cell_to_world(IVec2::new(2, -2)),
+            Vec3::new(4.0, 0.0, -4.0)
+        );
+    }
+
+    #[test]
+    fn occupied_cells_are_not_free_until_cleared() {
+        let mut world = World::new();
+        let tower = world.spawn().id();
+        let mut grid = GridMap::default();
+        let cell = IVec2::new(1, 2);
+
+        grid.occupy(cell, tower);
+        assert!(!grid.is_free(cell));
+        assert_eq!(grid.occupant(cell), Some(tower));
+
+        grid.clear(cell);
+        assert!(grid.is_free(cell));
+    }
+
+    #[test]
+    fn cells_along_the_path_are_blocked() {
+        let mut grid = GridMap::default();
+        grid.block_path(&Path::new(
+            "Corner",
+            vec![
+                Vec3::ZERO,
+                Vec3::new(4.0, 0.0, 0.0),
+                Vec3::new(4.0, 0.0, 4.0),
+            ],
+        ));
+
+        for cell in [
+            IVec2::new(0, 0),
+            IVec2::new(2, 0),
+            IVec2::new(4, 0),
+            IVec2::new(4, 3),
+        ] {
+            assert!(!grid.is_free(cell), "{cell} should be blocked");
+        }
+        for cell in [IVec2::new(2, 1), IVec2::new(2, -1), IVec2::new(3, 3)] {
+            assert!(grid.is_free(cell), "{cell} should be free");
+        }
+    }
+}
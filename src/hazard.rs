@@ -0,0 +1,274 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::*;
+
+/// The ground a hazard covers, centred on its position. Only the horizontal plane counts.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum HazardShape {
+    Rect { half_extents: (f32, f32) },
+    Circle { radius: f32 },
+}
+
+impl HazardShape {
+    pub fn contains(&self, center: Vec3, position: Vec3) -> bool {
+        let offset = Vec2::new(position.x - center.x, position.z - center.z);
+
+        match *self {
+            HazardShape::Rect { half_extents } => {
+                offset.x.abs() <= half_extents.0 && offset.y.abs() <= half_extents.1
+            }
+            HazardShape::Circle { radius } => offset.length() <= radius,
+        }
+    }
+}
+
+/// A patch of ground that hurts ground enemies standing in it by `dps`, and slows them to
+/// `slow` of their speed if set, like spikes or a tar pit.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Hazard {
+    pub dps: f32,
+    pub slow: Option<f32>,
+    pub shape: HazardShape,
+}
+
+/// A hazard as laid out in a map file.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct HazardDefinition {
+    pub position: (f32, f32),
+    pub shape: HazardShape,
+    #[serde(default)]
+    pub dps: f32,
+    #[serde(default)]
+    pub slow: Option<f32>,
+}
+
+impl HazardDefinition {
+    pub fn hazard(&self) -> Hazard {
+        Hazard {
+            dps: self.dps,
+            slow: self.slow,
+            shape: self.shape,
+        }
+    }
+}
+
+/// Fractional hazard damage an enemy has built up, carried between frames so slow damage
+/// still adds up.
+#[derive(Component, Default)]
+pub struct HazardExposure {
+    pub pending: f32,
+}
+
+struct HazardAssets {
+    damaging: Handle<StandardMaterial>,
+    slowing: Handle<StandardMaterial>,
+}
+
+pub struct HazardPlugin;
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_hazard_assets)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Next).with_system(spawn_hazards.after(LoadMap)),
+            )
+            .add_system_to_stage(GameStep, hazard_apply);
+    }
+}
+
+fn setup_hazard_assets(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    commands.insert_resource(HazardAssets {
+        damaging: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.8, 0.2, 0.1, 0.6),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+        slowing: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.1, 0.08, 0.05, 0.8),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+/// Lays the selected map's hazards just above the ground.
+fn spawn_hazards(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    hazard_assets: Res<HazardAssets>,
+    maps: Res<Maps>,
+) {
+    for definition in &maps.current().hazards {
+        let (mesh, transform) = match definition.shape {
+            HazardShape::Rect { half_extents } => (
+                Mesh::from(shape::Plane { size: 1.0 }),
+                Transform::from_scale(Vec3::new(half_extents.0 * 2.0, 1.0, half_extents.1 * 2.0)),
+            ),
+            HazardShape::Circle { radius } => (
+                Mesh::from(shape::Circle::new(radius)),
+                Transform::from_rotation(Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)),
+            ),
+        };
+        let material = if definition.dps > 0.0 {
+            hazard_assets.damaging.clone()
+        } else {
+            hazard_assets.slowing.clone()
+        };
+
+        commands
+            .spawn_bundle(PbrBundle {
+                mesh: meshes.add(mesh),
+                material,
+                transform: transform.with_translation(Vec3::new(
+                    definition.position.0,
+                    0.02,
+                    definition.position.1,
+                )),
+                ..default()
+            })
+            .insert(definition.hazard())
+            .insert(Gameplay)
+            .insert(Name::new("Hazard"));
+    }
+}
+
+/// Overlapping hazards add their damage together, while the strongest slow among them wins.
+/// A slow already on the enemy is only replaced by a stronger one.
+fn hazard_apply(
+    mut commands: Commands,
+    hazards: Query<(&Hazard, &Transform)>,
+    mut targets: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Health,
+            Option<&mut Slow>,
+            Option<&mut HazardExposure>,
+        ),
+        (With<Target>, Without<Flying>, Without<Invulnerable>),
+    >,
+    time: Res<GameTime>,
+) {
+    if hazards.is_empty() {
+        return;
+    }
+
+    for (entity, transform, mut health, slow, exposure) in &mut targets {
+        let mut dps = 0.0;
+        let mut slowest: Option<f32> = None;
+
+        for (hazard, hazard_transform) in &hazards {
+            if !hazard
+                .shape
+                .contains(hazard_transform.translation, transform.translation)
+            {
+                continue;
+            }
+
+            dps += hazard.dps;
+            if let Some(factor) = hazard.slow {
+                slowest = Some(slowest.map_or(factor, |slowest: f32| slowest.min(factor)));
+            }
+        }
+
+        if let Some(factor) = slowest {
+            match slow {
+                Some(mut slow) if slow.factor >= factor => {
+                    slow.factor = factor;
+                    slow.timer.reset();
+                }
+                Some(_) => {}
+                None => {
+                    commands.entity(entity).insert(Slow::new(factor));
+                }
+            }
+        }
+
+        if dps <= 0.0 {
+            continue;
+        }
+
+        let mut exposure = match exposure {
+            Some(exposure) => exposure,
+            None => {
+                commands.entity(entity).insert(HazardExposure {
+                    pending: dps * time.delta_seconds(),
+                });
+                continue;
+            }
+        };
+
+        exposure.pending += dps * time.delta_seconds();
+        let damage = exposure.pending.floor();
+        exposure.pending -= damage;
+        health.value -= damage as i32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn spawn_target(app: &mut App, position: Vec3) -> Entity {
+        app.world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .insert(Health { value: 10, max: 10 })
+            .insert(Transform::from_translation(position))
+            .id()
+    }
+
+    fn health(app: &App, target: Entity) -> i32 {
+        app.world.get::<Health>(target).unwrap().value
+    }
+
+    #[test]
+    fn enemies_standing_in_spikes_are_worn_down() {
+        let mut app = App::new();
+        app.insert_resource(GameTime::stepped(Duration::from_secs_f32(0.1)))
+            .add_system(hazard_apply);
+        app.world
+            .spawn()
+            .insert(Hazard {
+                dps: 2.0,
+                slow: None,
+                shape: HazardShape::Rect {
+                    half_extents: (1.0, 0.5),
+                },
+            })
+            .insert(Transform::default());
+        let inside = spawn_target(&mut app, Vec3::new(0.8, 0.5, 0.3));
+        let outside = spawn_target(&mut app, Vec3::new(0.8, 0.5, 0.7));
+        let flyer = spawn_target(&mut app, Vec3::new(0.0, 2.0, 0.0));
+        app.world.entity_mut(flyer).insert(Flying { altitude: 2.0 });
+
+        // Two seconds at 2 damage a second, give or take the step rounding loses.
+        for _ in 0..20 {
+            app.update();
+        }
+
+        assert!((6..=7).contains(&health(&app, inside)));
+        assert_eq!(health(&app, outside), 10);
+        assert_eq!(health(&app, flyer), 10);
+
+        for _ in 0..10 {
+            app.update();
+        }
+        assert!(health(&app, inside) < 6);
+    }
+
+    #[test]
+    fn circles_only_cover_the_ground_within_their_radius() {
+        let circle = HazardShape::Circle { radius: 1.0 };
+
+        assert!(circle.contains(Vec3::new(2.0, 0.0, 2.0), Vec3::new(2.6, 5.0, 2.6)));
+        assert!(!circle.contains(Vec3::new(2.0, 0.0, 2.0), Vec3::new(2.8, 0.0, 2.8)));
+    }
+}
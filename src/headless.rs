@@ -0,0 +1,225 @@
+use bevy::{
+    app::AppExit, asset::AssetPlugin, hierarchy::HierarchyPlugin, input::InputPlugin, prelude::*,
+    transform::TransformPlugin, window::WindowPlugin,
+};
+
+use crate::*;
+
+/// Cells the headless simulation builds on, with the tower type for each, so every run tests the
+/// same defence.
+const LAYOUT: [(IVec2, TowerType); 3] = [
+    (IVec2::new(-2, 0), TowerType::Cannon),
+    (IVec2::new(0, 3), TowerType::Sniper),
+    (IVec2::new(4, -1), TowerType::Mortar),
+];
+
+/// Present only when running with `--headless`. The simulation advances by a fixed `step` every
+/// update instead of real time, so it runs as fast as the machine allows and gives the same
+/// result every time for a given seed.
+pub struct Headless {
+    /// How many waves to play before reporting, capped at the number of waves configured.
+    pub waves: usize,
+    pub step: f32,
+}
+
+impl Default for Headless {
+    fn default() -> Self {
+        Self {
+            waves: usize::MAX,
+            step: 1.0 / 60.0,
+        }
+    }
+}
+
+/// Reads `--headless` and an optional `--waves N` from the command line.
+pub fn headless_from_args(args: &[String]) -> Option<Headless> {
+    if !args.iter().any(|arg| arg == "--headless") {
+        return None;
+    }
+
+    let mut headless = Headless::default();
+
+    if let Some(waves) = args
+        .iter()
+        .position(|arg| arg == "--waves")
+        .and_then(|index| args.get(index + 1))
+        .and_then(|waves| waves.parse().ok())
+    {
+        headless.waves = waves;
+    }
+
+    Some(headless)
+}
+
+/// Runs the game with no window, rendering or audio, printing a `HeadlessSummary` as JSON once
+/// the waves are done or the player runs out of lives. Only the plugins that need a renderer or
+/// an audio device are left out; everything else runs exactly as it does in a normal game.
+pub fn run_headless(headless: Headless) {
+    headless_app(headless).run();
+}
+
+/// The app `run_headless` runs, ready to be updated.
+pub fn headless_app(headless: Headless) -> App {
+    let mut app = App::new();
+
+    app.insert_resource(headless)
+        .add_plugins(MinimalPlugins)
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(InputPlugin)
+        .add_plugin(WindowPlugin {
+            add_primary_window: false,
+            exit_on_all_closed: false,
+            close_when_requested: false,
+        })
+        .add_plugin(AssetPlugin)
+        .add_asset::<Mesh>()
+        .add_asset::<StandardMaterial>()
+        .add_asset::<Scene>()
+        .add_asset::<Font>()
+        .insert_resource(GameAssets::headless())
+        .add_state(GameState::Next);
+
+    add_game(&mut app);
+
+    // Always play on the plain defaults, whatever difficulty was last picked, so runs compare.
+    app.insert_resource(DifficultySettings::default());
+
+    // There's no `AssetLoading` to leave, so enemy stats are built as soon as the config is in.
+    app.add_startup_system_to_stage(StartupStage::PostStartup, setup_enemy_stats)
+        .add_system_set(
+            SystemSet::on_enter(GameState::Next).with_system(build_layout.after(LoadMap)),
+        )
+        .add_system_to_stage(CoreStage::Last, report_summary);
+
+    app
+}
+
+/// What a headless run reports when it finishes. Inserted as a resource once it's printed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HeadlessSummary {
+    pub waves: usize,
+    pub lives: u32,
+    pub gold: u32,
+    pub score: u64,
+}
+
+impl HeadlessSummary {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"waves\":{},\"lives\":{},\"gold\":{},\"score\":{}}}",
+            self.waves, self.lives, self.gold, self.score
+        )
+    }
+}
+
+/// Places the `LAYOUT` for free, so the result depends on the towers rather than on how quickly
+/// gold came in.
+fn build_layout(
+    mut commands: Commands,
+    mut grid: ResMut<GridMap>,
+    config: Res<GameConfig>,
+    assets: Res<GameAssets>,
+) {
+    for (cell, tower_type) in LAYOUT {
+        if !grid.is_free(cell) {
+            warn!(
+                ?cell,
+                ?tower_type,
+                "Headless layout cell is blocked, skipping"
+            );
+            continue;
+        }
+
+        let tower = Tower::new(tower_type, &config, &assets);
+        let position = grid.cell_to_world(cell);
+        let tower = spawn_tower(&mut commands, &assets, &config, position, tower);
+        grid.occupy(cell, tower);
+    }
+}
+
+fn report_summary(
+    mut commands: Commands,
+    mut exit_events: EventWriter<AppExit>,
+    headless: Res<Headless>,
+    wave: Res<Wave>,
+    config: Res<WaveConfig>,
+    lives: Res<Lives>,
+    currency: Res<Currency>,
+    score: Res<Score>,
+) {
+    let waves = headless.waves.min(config.waves.len());
+
+    if wave.index < waves && lives.count > 0 {
+        return;
+    }
+
+    let summary = HeadlessSummary {
+        waves: wave.index,
+        lives: lives.count,
+        gold: currency.gold,
+        score: score.value,
+    };
+
+    println!("{}", summary.to_json());
+    commands.insert_resource(summary);
+    exit_events.send(AppExit);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headless_args_are_parsed() {
+        let args: Vec<String> = ["tower_def", "--headless", "--waves", "2"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let headless = headless_from_args(&args).expect("--headless was passed");
+
+        assert_eq!(headless.waves, 2);
+        assert!(headless_from_args(&args[..1]).is_none());
+    }
+
+    #[test]
+    fn summary_is_written_as_json() {
+        let summary = HeadlessSummary {
+            waves: 3,
+            lives: 7,
+            gold: 120,
+            score: 450,
+        };
+
+        assert_eq!(
+            summary.to_json(),
+            "{\"waves\":3,\"lives\":7,\"gold\":120,\"score\":450}"
+        );
+    }
+
+    #[test]
+    fn short_headless_run_reports_a_summary() {
+        let mut app = headless_app(Headless {
+            waves: 1,
+            ..default()
+        });
+
+        for _ in 0..20_000 {
+            app.update();
+
+            if app.world.contains_resource::<HeadlessSummary>() {
+                break;
+            }
+        }
+
+        let summary = *app
+            .world
+            .get_resource::<HeadlessSummary>()
+            .expect("headless run never finished");
+
+        assert!(summary.waves == 1 || summary.lives == 0);
+        assert!(summary
+            .to_json()
+            .starts_with(&format!("{{\"waves\":{},", summary.waves)));
+    }
+}
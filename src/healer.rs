@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// Restores `amount` health to every other enemy within `radius` each time `timer` fires, never
+/// past their maximum.
+#[derive(Reflect, Component, Default, Clone, Debug)]
+#[reflect(Component)]
+pub struct Healer {
+    pub timer: Timer,
+    pub radius: f32,
+    pub amount: i32,
+}
+
+impl Healer {
+    pub fn new(interval: f32, radius: f32, amount: i32) -> Self {
+        Self {
+            timer: Timer::from_seconds(interval, true),
+            radius,
+            amount,
+        }
+    }
+}
+
+pub struct TargetHealed {
+    pub target: Entity,
+    /// Health actually restored, after clamping to the maximum.
+    pub amount: i32,
+    pub position: Vec3,
+}
+
+pub struct HealerPlugin;
+
+impl Plugin for HealerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Healer>()
+            .add_event::<TargetHealed>()
+            .add_system_set_to_stage(
+                GameStep,
+                SystemSet::new()
+                    .with_system(healer_pulse.after(RebuildSpatialGrid))
+                    .with_system(spawn_heal_numbers.after(healer_pulse)),
+            );
+    }
+}
+
+/// Enemies already at zero health are left alone, so a pulse can't save something a tower has
+/// just killed.
+fn healer_pulse(
+    mut healers: Query<(Entity, &mut Healer, &GlobalTransform)>,
+    mut targets: Query<(&mut Health, &GlobalTransform), With<Target>>,
+    mut healed_events: EventWriter<TargetHealed>,
+    grid: Res<SpatialGrid>,
+    time: Res<GameTime>,
+) {
+    for (healer_entity, mut healer, transform) in &mut healers {
+        healer.timer.tick(time.delta());
+
+        for _ in 0..healer.timer.times_finished() {
+            for entity in grid.query_radius(transform.translation(), healer.radius) {
+                if entity == healer_entity {
+                    continue;
+                }
+
+                let (mut health, target_transform) = match targets.get_mut(entity) {
+                    Ok(target) => target,
+                    Err(_) => continue,
+                };
+
+                if health.value <= 0 || health.value >= health.max {
+                    continue;
+                }
+
+                let amount = healer.amount.min(health.max - health.value);
+                health.value += amount;
+                healed_events.send(TargetHealed {
+                    target: entity,
+                    amount,
+                    position: target_transform.translation(),
+                });
+            }
+        }
+    }
+}
+
+fn spawn_heal_numbers(
+    mut commands: Commands,
+    mut healed_events: EventReader<TargetHealed>,
+    settings: Res<FloatingTextSettings>,
+    assets: Res<GameAssets>,
+) {
+    for healed in healed_events.iter() {
+        spawn_floating_text(
+            &mut commands,
+            &assets,
+            &settings,
+            format!("+{}", healed.amount),
+            settings.heal_color,
+            healed.position,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn spawn_enemy(app: &mut App, position: Vec3, value: i32) -> Entity {
+        let enemy = app
+            .world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .insert(Health { value, max: 10 })
+            .insert(GlobalTransform::from_translation(position))
+            .id();
+        app.world
+            .resource_mut::<SpatialGrid>()
+            .insert(enemy, position);
+
+        enemy
+    }
+
+    #[test]
+    fn healers_heal_nearby_enemies_up_to_their_max() {
+        let mut app = App::new();
+        app.init_resource::<SpatialGrid>()
+            .insert_resource(GameTime::stepped(Duration::from_secs_f32(1.0)))
+            .add_event::<TargetHealed>()
+            .add_system(healer_pulse);
+        let healer = spawn_enemy(&mut app, Vec3::ZERO, 5);
+        app.world
+            .entity_mut(healer)
+            .insert(Healer::new(1.0, 2.0, 3));
+        let wounded = spawn_enemy(&mut app, Vec3::X, 4);
+        let scratched = spawn_enemy(&mut app, Vec3::Z, 9);
+        let distant = spawn_enemy(&mut app, Vec3::X * 5.0, 4);
+
+        app.update();
+
+        let health = |entity| app.world.get::<Health>(entity).unwrap().value;
+        assert_eq!(health(wounded), 7);
+        assert_eq!(health(scratched), 10);
+        assert_eq!(health(distant), 4);
+        // Healers never heal themselves.
+        assert_eq!(health(healer), 5);
+        assert_eq!(app.world.resource::<Events<TargetHealed>>().len(), 2);
+    }
+}
@@ -0,0 +1,447 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+const BAR_WIDTH: f32 = 0.6;
+const BAR_HEIGHT: f32 = 0.08;
+const ICON_SIZE: f32 = 0.1;
+const ICON_SPACING: f32 = 0.02;
+
+#[derive(Reflect, Component)]
+#[reflect(Component)]
+pub struct HealthBarOffset {
+    pub offset: Vec3,
+}
+
+impl Default for HealthBarOffset {
+    fn default() -> Self {
+        Self {
+            offset: Vec3::new(0.0, 0.6, 0.0),
+        }
+    }
+}
+
+#[derive(Reflect, Component)]
+#[reflect(Component)]
+pub struct HealthBarScale {
+    pub scale: f32,
+}
+
+impl Default for HealthBarScale {
+    fn default() -> Self {
+        Self { scale: 1.0 }
+    }
+}
+
+#[derive(Component)]
+pub struct HealthBar {
+    pub target: Entity,
+    pub fill: Entity,
+    /// The blue strip drawn over the fill for enemies with a `Shield`.
+    pub shield: Option<Entity>,
+    pub offset: Vec3,
+    /// Icons shown above the bar, one per active effect, in `StatusEffect::ALL` order.
+    pub icons: Vec<(StatusEffect, Entity)>,
+}
+
+#[derive(Component)]
+pub struct HealthBarFill;
+
+/// An effect on an enemy that gets an icon over its health bar.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StatusEffect {
+    Slow,
+    Poison,
+    Shield,
+    Stun,
+}
+
+impl StatusEffect {
+    pub const ALL: [StatusEffect; 4] = [Self::Slow, Self::Poison, Self::Shield, Self::Stun];
+}
+
+#[derive(Component)]
+pub struct EffectIcon;
+
+struct HealthBarAssets {
+    mesh: Handle<Mesh>,
+    background: Handle<StandardMaterial>,
+    shield: Handle<StandardMaterial>,
+    icon_mesh: Handle<Mesh>,
+    slow_icon: Handle<StandardMaterial>,
+    poison_icon: Handle<StandardMaterial>,
+    shield_icon: Handle<StandardMaterial>,
+    stun_icon: Handle<StandardMaterial>,
+}
+
+impl HealthBarAssets {
+    fn icon(&self, effect: StatusEffect) -> Handle<StandardMaterial> {
+        match effect {
+            StatusEffect::Slow => self.slow_icon.clone(),
+            StatusEffect::Poison => self.poison_icon.clone(),
+            StatusEffect::Shield => self.shield_icon.clone(),
+            StatusEffect::Stun => self.stun_icon.clone(),
+        }
+    }
+}
+
+pub struct HealthBarPlugin;
+
+impl Plugin for HealthBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<HealthBarOffset>()
+            .register_type::<HealthBarScale>()
+            .add_startup_system(setup_health_bar_assets)
+            .add_system_set(
+                SystemSet::on_update(GameState::Next)
+                    .with_system(spawn_health_bars)
+                    .with_system(update_health_bars)
+                    .with_system(effect_icons),
+            );
+    }
+}
+
+fn setup_health_bar_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(HealthBarAssets {
+        mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::new(
+            BAR_WIDTH, BAR_HEIGHT,
+        )))),
+        background: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.1, 0.1, 0.1),
+            unlit: true,
+            ..default()
+        }),
+        shield: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.2, 0.5, 1.0),
+            unlit: true,
+            ..default()
+        }),
+        icon_mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::splat(ICON_SIZE)))),
+        slow_icon: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.5, 0.8, 1.0),
+            unlit: true,
+            ..default()
+        }),
+        poison_icon: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.4, 0.9, 0.2),
+            unlit: true,
+            ..default()
+        }),
+        shield_icon: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.2, 0.5, 1.0),
+            unlit: true,
+            ..default()
+        }),
+        stun_icon: materials.add(StandardMaterial {
+            base_color: Color::rgb(1.0, 1.0, 0.5),
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+fn spawn_health_bars(
+    mut commands: Commands,
+    targets: Query<
+        (
+            Entity,
+            Option<&HealthBarOffset>,
+            Option<&HealthBarScale>,
+            Option<&Shield>,
+        ),
+        (With<Target>, Added<Health>),
+    >,
+    bar_assets: Res<HealthBarAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (target, offset, scale, shield) in &targets {
+        let background = commands
+            .spawn_bundle(PbrBundle {
+                mesh: bar_assets.mesh.clone(),
+                material: bar_assets.background.clone(),
+                ..default()
+            })
+            .id();
+
+        let fill = commands
+            .spawn_bundle(PbrBundle {
+                mesh: bar_assets.mesh.clone(),
+                material: materials.add(StandardMaterial {
+                    base_color: Color::GREEN,
+                    unlit: true,
+                    ..default()
+                }),
+                transform: Transform::from_xyz(0.0, 0.0, 0.001),
+                ..default()
+            })
+            .insert(HealthBarFill)
+            .id();
+
+        let shield = shield.map(|_| {
+            commands
+                .spawn_bundle(PbrBundle {
+                    mesh: bar_assets.mesh.clone(),
+                    material: bar_assets.shield.clone(),
+                    transform: Transform::from_xyz(0.0, 0.0, 0.002)
+                        .with_scale(Vec3::new(1.0, 0.5, 1.0)),
+                    ..default()
+                })
+                .insert(HealthBarFill)
+                .id()
+        });
+
+        let scale = scale.map(|scale| scale.scale).unwrap_or(1.0);
+
+        commands
+            .spawn_bundle(SpatialBundle {
+                transform: Transform::from_scale(Vec3::splat(scale)),
+                ..default()
+            })
+            .insert(HealthBar {
+                target,
+                fill,
+                shield,
+                offset: offset
+                    .map(|offset| offset.offset)
+                    .unwrap_or_else(|| HealthBarOffset::default().offset),
+                icons: Vec::new(),
+            })
+            .insert(Gameplay)
+            .insert(Name::new("HealthBar"))
+            .push_children(&[background, fill])
+            .push_children(shield.as_slice());
+    }
+}
+
+fn update_health_bars(
+    mut commands: Commands,
+    mut bars: Query<(Entity, &HealthBar, &mut Transform), Without<HealthBarFill>>,
+    mut fills: Query<(&mut Transform, &Handle<StandardMaterial>), With<HealthBarFill>>,
+    targets: Query<(&GlobalTransform, &Health, Option<&Shield>)>,
+    cameras: Query<&GlobalTransform, With<GameCamera>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let camera_rotation = match cameras.get_single() {
+        Ok(camera) => camera.compute_transform().rotation,
+        Err(_) => return,
+    };
+
+    for (bar_ent, bar, mut transform) in &mut bars {
+        let (target_transform, health, shield) = match targets.get(bar.target) {
+            Ok(target) => target,
+            Err(_) => {
+                commands.entity(bar_ent).despawn_recursive();
+                continue;
+            }
+        };
+
+        transform.translation = target_transform.translation() + bar.offset;
+        transform.rotation = camera_rotation;
+
+        if let Ok((mut fill_transform, material)) = fills.get_mut(bar.fill) {
+            let ratio = (health.value.max(0) as f32 / health.max.max(1) as f32).min(1.0);
+
+            fill_transform.scale.x = ratio;
+            fill_transform.translation.x = -(1.0 - ratio) * BAR_WIDTH / 2.0;
+
+            if let Some(material) = materials.get_mut(material) {
+                material.base_color = Color::rgb(1.0 - ratio, ratio, 0.0);
+            }
+        }
+
+        if let (Some(shield_fill), Some(shield)) = (bar.shield, shield) {
+            if let Ok((mut fill_transform, _)) = fills.get_mut(shield_fill) {
+                let ratio = (shield.amount.max(0) as f32 / shield.max.max(1) as f32).min(1.0);
+
+                fill_transform.scale.x = ratio;
+                fill_transform.translation.x = -(1.0 - ratio) * BAR_WIDTH / 2.0;
+            }
+        }
+    }
+}
+
+/// Where the icon at `index` of `count` sits over the bar, centred as a row.
+fn icon_position(index: usize, count: usize) -> Vec3 {
+    let width = count as f32 * ICON_SIZE + count.saturating_sub(1) as f32 * ICON_SPACING;
+    let x = -width / 2.0 + ICON_SIZE / 2.0 + index as f32 * (ICON_SIZE + ICON_SPACING);
+
+    Vec3::new(x, BAR_HEIGHT / 2.0 + ICON_SPACING + ICON_SIZE / 2.0, 0.001)
+}
+
+/// Keeps each bar's icons matching its enemy's effects. Comparing the effects is cheap, so it's
+/// done every frame; icons are only spawned, despawned or moved when the set actually changes.
+/// A shield only counts while it has something left.
+fn effect_icons(
+    mut commands: Commands,
+    mut bars: Query<(Entity, &mut HealthBar)>,
+    mut icon_transforms: Query<&mut Transform, With<EffectIcon>>,
+    targets: Query<(
+        Option<&Slow>,
+        Option<&Poison>,
+        Option<&Shield>,
+        Option<&Stun>,
+    )>,
+    bar_assets: Res<HealthBarAssets>,
+) {
+    for (bar_ent, mut bar) in &mut bars {
+        let (slow, poison, shield, stun) = match targets.get(bar.target) {
+            Ok(effects) => effects,
+            Err(_) => continue,
+        };
+
+        let wanted: Vec<StatusEffect> = StatusEffect::ALL
+            .into_iter()
+            .filter(|effect| match effect {
+                StatusEffect::Slow => slow.is_some(),
+                StatusEffect::Poison => poison.is_some(),
+                StatusEffect::Shield => shield.map_or(false, |shield| shield.amount > 0),
+                StatusEffect::Stun => stun.is_some(),
+            })
+            .collect();
+
+        if bar
+            .icons
+            .iter()
+            .map(|(effect, _)| *effect)
+            .eq(wanted.iter().copied())
+        {
+            continue;
+        }
+
+        let mut icons = Vec::with_capacity(wanted.len());
+
+        for (effect, icon) in std::mem::take(&mut bar.icons) {
+            if wanted.contains(&effect) {
+                icons.push((effect, icon));
+            } else {
+                commands.entity(icon).despawn_recursive();
+            }
+        }
+
+        for (index, effect) in wanted.iter().enumerate() {
+            let position = icon_position(index, wanted.len());
+
+            match icons.iter().find(|(kept, _)| kept == effect) {
+                Some((_, icon)) => {
+                    if let Ok(mut transform) = icon_transforms.get_mut(*icon) {
+                        transform.translation = position;
+                    }
+                }
+                None => {
+                    let icon = commands
+                        .spawn_bundle(PbrBundle {
+                            mesh: bar_assets.icon_mesh.clone(),
+                            material: bar_assets.icon(*effect),
+                            transform: Transform::from_translation(position),
+                            ..default()
+                        })
+                        .insert(EffectIcon)
+                        .id();
+                    commands.entity(bar_ent).add_child(icon);
+                    icons.push((*effect, icon));
+                }
+            }
+        }
+
+        icons.sort_by_key(|(effect, _)| wanted.iter().position(|wanted| wanted == effect));
+        bar.icons = icons;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::AssetPlugin;
+
+    fn app() -> App {
+        let mut app = App::new();
+
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Mesh>()
+            .add_asset::<StandardMaterial>()
+            .add_startup_system(setup_health_bar_assets)
+            .add_system(spawn_health_bars)
+            .add_system(update_health_bars.after(spawn_health_bars))
+            .add_system(effect_icons.after(spawn_health_bars));
+        app.world
+            .spawn()
+            .insert(GameCamera)
+            .insert(GlobalTransform::default());
+
+        app
+    }
+
+    fn spawn_target(app: &mut App, health: Health) -> Entity {
+        app.world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .insert(health)
+            .insert(GlobalTransform::from_translation(Vec3::new(2.0, 0.0, 1.0)))
+            .id()
+    }
+
+    fn bar(app: &mut App, target: Entity) -> (Entity, Vec3, Entity) {
+        app.world
+            .query::<(Entity, &HealthBar, &Transform)>()
+            .iter(&app.world)
+            .find(|(_, bar, _)| bar.target == target)
+            .map(|(entity, bar, transform)| (entity, transform.translation, bar.fill))
+            .expect("target has no health bar")
+    }
+
+    #[test]
+    fn bars_float_over_their_enemy_and_show_its_health() {
+        let mut app = app();
+        let target = spawn_target(&mut app, Health { value: 5, max: 10 });
+
+        app.update();
+        app.update();
+
+        let (_, translation, fill) = bar(&mut app, target);
+        assert_eq!(
+            translation,
+            Vec3::new(2.0, 0.0, 1.0) + HealthBarOffset::default().offset
+        );
+        assert_eq!(app.world.get::<Transform>(fill).unwrap().scale.x, 0.5);
+
+        app.world.despawn(target);
+        app.update();
+        app.update();
+
+        assert_eq!(app.world.query::<&HealthBar>().iter(&app.world).count(), 0);
+    }
+
+    fn icons(app: &mut App) -> usize {
+        app.world
+            .query_filtered::<(), With<EffectIcon>>()
+            .iter(&app.world)
+            .count()
+    }
+
+    #[test]
+    fn each_active_effect_gets_one_icon() {
+        let mut app = app();
+        let target = spawn_target(&mut app, Health { value: 5, max: 10 });
+        app.world
+            .entity_mut(target)
+            .insert(Slow::new(0.5))
+            .insert(Poison::new(1.0, 3.0));
+
+        app.update();
+        app.update();
+        app.update();
+        assert_eq!(icons(&mut app), 2);
+
+        app.world.entity_mut(target).remove::<Slow>();
+        app.update();
+        assert_eq!(icons(&mut app), 1);
+    }
+}
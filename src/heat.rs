@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+const HEAT_BAR_WIDTH: f32 = 0.5;
+const HEAT_BAR_HEIGHT: f32 = 0.04;
+
+/// Builds up by `per_shot` each time the tower fires and drains at `cooldown_rate` per second.
+/// Reaching `max` overheats the tower, which then holds fire until heat falls back to
+/// `resume_fraction` of `max`.
+#[derive(Reflect, Component, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct Heat {
+    pub current: f32,
+    pub max: f32,
+    pub cooldown_rate: f32,
+    pub per_shot: f32,
+    pub resume_fraction: f32,
+    pub overheated: bool,
+}
+
+impl Heat {
+    pub fn new(max: f32, per_shot: f32, cooldown_rate: f32) -> Self {
+        Self {
+            current: 0.0,
+            max,
+            cooldown_rate,
+            per_shot,
+            resume_fraction: 0.5,
+            overheated: false,
+        }
+    }
+
+    pub fn can_fire(&self) -> bool {
+        !self.overheated
+    }
+
+    pub fn add_shot(&mut self) {
+        self.current = (self.current + self.per_shot).min(self.max);
+
+        if self.current >= self.max {
+            self.overheated = true;
+        }
+    }
+
+    pub fn dissipate(&mut self, seconds: f32) {
+        self.current = (self.current - self.cooldown_rate * seconds).max(0.0);
+
+        if self.overheated && self.current <= self.max * self.resume_fraction {
+            self.overheated = false;
+        }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.max <= 0.0 {
+            return 0.0;
+        }
+
+        self.current / self.max
+    }
+}
+
+pub struct HeatBarSettings {
+    pub offset: Vec3,
+}
+
+impl Default for HeatBarSettings {
+    fn default() -> Self {
+        Self {
+            offset: Vec3::new(0.0, 1.2, 0.0),
+        }
+    }
+}
+
+/// Shows how hot `tower` is, sitting just above its cooldown bar.
+#[derive(Component)]
+pub struct HeatBar {
+    pub tower: Entity,
+    pub fill: Entity,
+}
+
+#[derive(Component)]
+pub struct HeatBarFill;
+
+/// A red halo around an overheated tower, hidden the rest of the time.
+#[derive(Component)]
+pub struct HeatGlow;
+
+struct HeatAssets {
+    bar_mesh: Handle<Mesh>,
+    background: Handle<StandardMaterial>,
+    fill: Handle<StandardMaterial>,
+    overheated_fill: Handle<StandardMaterial>,
+    glow_mesh: Handle<Mesh>,
+    glow: Handle<StandardMaterial>,
+}
+
+pub struct HeatPlugin;
+
+impl Plugin for HeatPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Heat>()
+            .init_resource::<HeatBarSettings>()
+            .add_startup_system(setup_heat_assets)
+            .add_system_to_stage(GameStep, heat_dissipate)
+            .add_system_set(
+                SystemSet::on_update(GameState::Next)
+                    .with_system(spawn_heat_visuals)
+                    .with_system(update_heat_visuals),
+            );
+    }
+}
+
+fn setup_heat_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(HeatAssets {
+        bar_mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::new(
+            HEAT_BAR_WIDTH,
+            HEAT_BAR_HEIGHT,
+        )))),
+        background: materials.add(StandardMaterial {
+            base_color: Color::rgb(0.1, 0.1, 0.1),
+            unlit: true,
+            ..default()
+        }),
+        fill: materials.add(StandardMaterial {
+            base_color: Color::rgb(1.0, 0.6, 0.1),
+            unlit: true,
+            ..default()
+        }),
+        overheated_fill: materials.add(StandardMaterial {
+            base_color: Color::RED,
+            unlit: true,
+            ..default()
+        }),
+        glow_mesh: meshes.add(Mesh::from(shape::UVSphere {
+            radius: 0.45,
+            ..default()
+        })),
+        glow: materials.add(StandardMaterial {
+            base_color: Color::rgba(1.0, 0.1, 0.0, 0.35),
+            emissive: Color::rgb(1.0, 0.1, 0.0),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+fn heat_dissipate(mut heated: Query<&mut Heat>, time: Res<GameTime>) {
+    for mut heat in &mut heated {
+        if heat.current > 0.0 {
+            heat.dissipate(time.delta_seconds());
+        }
+    }
+}
+
+fn spawn_heat_visuals(
+    mut commands: Commands,
+    towers: Query<Entity, (With<Tower>, Added<Heat>)>,
+    heat_assets: Res<HeatAssets>,
+) {
+    for tower in &towers {
+        let background = commands
+            .spawn_bundle(PbrBundle {
+                mesh: heat_assets.bar_mesh.clone(),
+                material: heat_assets.background.clone(),
+                ..default()
+            })
+            .id();
+
+        let fill = commands
+            .spawn_bundle(PbrBundle {
+                mesh: heat_assets.bar_mesh.clone(),
+                material: heat_assets.fill.clone(),
+                transform: Transform::from_xyz(0.0, 0.0, 0.001),
+                ..default()
+            })
+            .insert(HeatBarFill)
+            .id();
+
+        commands
+            .spawn_bundle(SpatialBundle::default())
+            .insert(HeatBar { tower, fill })
+            .insert(Gameplay)
+            .insert(Name::new("HeatBar"))
+            .push_children(&[background, fill]);
+
+        let glow = commands
+            .spawn_bundle(PbrBundle {
+                mesh: heat_assets.glow_mesh.clone(),
+                material: heat_assets.glow.clone(),
+                visibility: Visibility { is_visible: false },
+                ..default()
+            })
+            .insert(HeatGlow)
+            .insert(Name::new("HeatGlow"))
+            .id();
+        commands.entity(tower).add_child(glow);
+    }
+}
+
+/// Bars are kept facing the camera like cooldown bars, and hidden along with them when zoomed
+/// out. The glow is a child of the tower, so it only needs switching on and off.
+fn update_heat_visuals(
+    mut commands: Commands,
+    mut bars: Query<(Entity, &HeatBar, &mut Transform, &mut Visibility), Without<HeatBarFill>>,
+    mut fills: Query<
+        (&mut Transform, &mut Handle<StandardMaterial>),
+        (With<HeatBarFill>, Without<HeatBar>),
+    >,
+    mut glows: Query<&mut Visibility, (With<HeatGlow>, Without<HeatBar>)>,
+    towers: Query<(&GlobalTransform, &Heat, &Children)>,
+    cameras: Query<(&GlobalTransform, &CameraController), With<GameCamera>>,
+    heat_assets: Res<HeatAssets>,
+    settings: Res<HeatBarSettings>,
+    cooldown_settings: Res<CooldownBarSettings>,
+) {
+    let (camera_rotation, zoomed_out) = match cameras.get_single() {
+        Ok((camera, controller)) => (
+            camera.compute_transform().rotation,
+            controller.distance > cooldown_settings.hide_distance,
+        ),
+        Err(_) => return,
+    };
+
+    for (bar_ent, bar, mut transform, mut visibility) in &mut bars {
+        let (tower_transform, heat, children) = match towers.get(bar.tower) {
+            Ok(tower) => tower,
+            Err(_) => {
+                commands.entity(bar_ent).despawn_recursive();
+                continue;
+            }
+        };
+
+        for child in children {
+            if let Ok(mut glow_visibility) = glows.get_mut(*child) {
+                if glow_visibility.is_visible != heat.overheated {
+                    glow_visibility.is_visible = heat.overheated;
+                }
+            }
+        }
+
+        visibility.is_visible = !zoomed_out;
+        if zoomed_out {
+            continue;
+        }
+
+        transform.translation = tower_transform.translation() + settings.offset;
+        transform.rotation = camera_rotation;
+
+        if let Ok((mut fill_transform, mut material)) = fills.get_mut(bar.fill) {
+            let ratio = heat.fraction();
+
+            fill_transform.scale.x = ratio;
+            fill_transform.translation.x = -(1.0 - ratio) * HEAT_BAR_WIDTH / 2.0;
+
+            let wanted = if heat.overheated {
+                &heat_assets.overheated_fill
+            } else {
+                &heat_assets.fill
+            };
+            if *material != *wanted {
+                *material = wanted.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,436 @@
+use bevy::{prelude::*, utils::FloatOrd};
+
+use crate::*;
+
+/// Makes a tower hit its target the instant it fires instead of launching a projectile. Damage,
+/// range and targeting all come from the `Tower` itself, so upgrades apply as usual.
+#[derive(Reflect, Component)]
+#[reflect(Component)]
+pub struct Hitscan {
+    /// How long the beam stays on screen after each shot.
+    pub beam_duration: f32,
+}
+
+impl Default for Hitscan {
+    fn default() -> Self {
+        Self { beam_duration: 0.1 }
+    }
+}
+
+/// Makes a tower strike its target instantly and then arc on to up to `jumps` more targets, each
+/// the nearest one not yet hit within `jump_range` of the last. Every jump loses `falloff` of
+/// the damage, so 0.25 keeps three quarters each time, but always deals at least 1.
+#[derive(Reflect, Component)]
+#[reflect(Component)]
+pub struct ChainLightning {
+    pub jumps: u32,
+    pub jump_range: f32,
+    pub falloff: f32,
+}
+
+impl Default for ChainLightning {
+    fn default() -> Self {
+        Self {
+            jumps: 3,
+            jump_range: 1.5,
+            falloff: 0.25,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Beam;
+
+struct BeamAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub struct HitscanPlugin;
+
+impl Plugin for HitscanPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Hitscan>()
+            .register_type::<ChainLightning>()
+            .add_startup_system(setup_beam_assets)
+            .add_system_set_to_stage(
+                GameStep,
+                SystemSet::new()
+                    .with_system(hitscan_shooting.after(RebuildSpatialGrid))
+                    .with_system(chain_lightning_shooting.after(RebuildSpatialGrid)),
+            );
+    }
+}
+
+fn setup_beam_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(BeamAssets {
+        // One unit long along Z, stretched to fit each shot.
+        mesh: meshes.add(Mesh::from(shape::Box::new(0.04, 0.04, 1.0))),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgb(1.0, 0.3, 0.3),
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+/// Spawns a beam stretched between `from` and `to` that disappears after `duration`.
+pub fn spawn_beam(
+    commands: &mut Commands,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    from: Vec3,
+    to: Vec3,
+    duration: f32,
+) -> Entity {
+    let length = Vec3::distance(from, to);
+    let transform = Transform::from_translation((from + to) / 2.0)
+        .looking_at(to, Vec3::Y)
+        .with_scale(Vec3::new(1.0, 1.0, length));
+
+    commands
+        .spawn_bundle(PbrBundle {
+            mesh,
+            material,
+            transform,
+            ..default()
+        })
+        .insert(Beam)
+        .insert(Lifetime {
+            timer: Timer::from_seconds(duration, false),
+        })
+        .insert(Gameplay)
+        .insert(Name::new("Beam"))
+        .id()
+}
+
+fn hitscan_shooting(
+    mut commands: Commands,
+    mut towers: Query<(
+        Entity,
+        &mut Tower,
+        &GlobalTransform,
+        &Hitscan,
+        Option<&CanTargetAir>,
+        Option<&Buffed>,
+    )>,
+    mut targets: ParamSet<(
+        Query<(&Health, &PathFollower), With<Target>>,
+        Query<
+            (
+                &mut Health,
+                (Option<&Armor>, Option<&Resistances>),
+                Option<&mut Shield>,
+            ),
+            (With<Target>, Without<Invulnerable>),
+        >,
+    )>,
+    mut damage_events: EventWriter<DamageDealt>,
+    mut fired_events: EventWriter<TowerFired>,
+    beam_assets: Res<BeamAssets>,
+    grid: Res<SpatialGrid>,
+    paths: Res<Paths>,
+    time: Res<GameTime>,
+) {
+    for (entity, mut tower, transform, hitscan, anti_air, buffed) in &mut towers {
+        let delta = buffed.map_or(time.delta(), |buffed| buffed.cooldown_delta(time.delta()));
+        tower.shooting_timer.tick(delta);
+
+        if !tower.shooting_timer.finished() {
+            continue;
+        }
+
+        let origin = transform.translation() + tower.muzzle(Vec3::ZERO);
+
+        let target = select_target(
+            tower.targeting,
+            tower.current_target,
+            &grid,
+            origin,
+            tower.range,
+            anti_air.is_some(),
+            &paths,
+            &targets.p0(),
+            |_| true,
+        );
+        tower.current_target = target.map(|(target, _)| target);
+
+        let (target, target_position) = match target {
+            Some(target) => target,
+            None => continue,
+        };
+
+        tower.shooting_timer.reset();
+        let barrel = transform.translation() + tower.muzzle(target_position - origin);
+        tower.cycle_muzzle();
+        fired_events.send(TowerFired {
+            tower: entity,
+            position: barrel,
+        });
+
+        if let Ok((mut health, defenses, mut shield)) = targets.p1().get_mut(target) {
+            let damage = buffed.map_or(tower.damage, |buffed| buffed.damage(tower.damage));
+            let amount = mitigate(damage, tower.damage_type, tower.true_damage, defenses);
+            apply_damage(&mut health, shield.as_deref_mut(), amount);
+            damage_events.send(DamageDealt {
+                target,
+                amount,
+                position: target_position,
+                crit: false,
+            });
+        }
+
+        spawn_beam(
+            &mut commands,
+            beam_assets.mesh.clone(),
+            beam_assets.material.clone(),
+            barrel,
+            target_position,
+            hitscan.beam_duration,
+        );
+    }
+}
+
+fn chain_lightning_shooting(
+    mut commands: Commands,
+    mut towers: Query<(
+        Entity,
+        &mut Tower,
+        &GlobalTransform,
+        &ChainLightning,
+        Option<&CanTargetAir>,
+        Option<&Buffed>,
+    )>,
+    mut targets: ParamSet<(
+        Query<(&Health, &PathFollower), With<Target>>,
+        Query<
+            (
+                &mut Health,
+                (Option<&Armor>, Option<&Resistances>),
+                Option<&mut Shield>,
+            ),
+            (With<Target>, Without<Invulnerable>),
+        >,
+    )>,
+    mut damage_events: EventWriter<DamageDealt>,
+    mut fired_events: EventWriter<TowerFired>,
+    beam_assets: Res<BeamAssets>,
+    grid: Res<SpatialGrid>,
+    paths: Res<Paths>,
+    time: Res<GameTime>,
+) {
+    for (entity, mut tower, transform, chain, anti_air, buffed) in &mut towers {
+        let delta = buffed.map_or(time.delta(), |buffed| buffed.cooldown_delta(time.delta()));
+        tower.shooting_timer.tick(delta);
+
+        if !tower.shooting_timer.finished() {
+            continue;
+        }
+
+        let origin = transform.translation() + tower.muzzle(Vec3::ZERO);
+
+        let target = select_target(
+            tower.targeting,
+            tower.current_target,
+            &grid,
+            origin,
+            tower.range,
+            anti_air.is_some(),
+            &paths,
+            &targets.p0(),
+            |_| true,
+        );
+        tower.current_target = target.map(|(target, _)| target);
+
+        let (mut target, mut target_position) = match target {
+            Some(target) => target,
+            None => continue,
+        };
+
+        tower.shooting_timer.reset();
+        let barrel = transform.translation() + tower.muzzle(target_position - origin);
+        tower.cycle_muzzle();
+        fired_events.send(TowerFired {
+            tower: entity,
+            position: barrel,
+        });
+
+        let mut hit = vec![target];
+        let mut from = barrel;
+        let mut damage = buffed.map_or(tower.damage, |buffed| buffed.damage(tower.damage));
+
+        loop {
+            if let Ok((mut health, defenses, mut shield)) = targets.p1().get_mut(target) {
+                let amount = mitigate(damage, tower.damage_type, tower.true_damage, defenses);
+                apply_damage(&mut health, shield.as_deref_mut(), amount);
+                damage_events.send(DamageDealt {
+                    target,
+                    amount,
+                    position: target_position,
+                    crit: false,
+                });
+            }
+
+            spawn_beam(
+                &mut commands,
+                beam_assets.mesh.clone(),
+                beam_assets.material.clone(),
+                from,
+                target_position,
+                0.15,
+            );
+
+            if hit.len() > chain.jumps as usize {
+                break;
+            }
+
+            let next = grid
+                .query_radius_positions(target_position, chain.jump_range)
+                .filter(|(entity, _)| {
+                    !hit.contains(entity) && grid.is_targetable(*entity, anti_air.is_some())
+                })
+                .min_by_key(|(_, position)| FloatOrd(position.distance(target_position)));
+
+            let (next, next_position) = match next {
+                Some(next) => next,
+                None => break,
+            };
+
+            hit.push(next);
+            from = target_position;
+            target = next;
+            target_position = next_position;
+            damage = ((damage as f32 * (1.0 - chain.falloff)).round() as i32).max(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn app() -> App {
+        let mut app = App::new();
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+
+        app.insert_resource(config)
+            .insert_resource(GameAssets::headless())
+            .insert_resource(Paths::new(vec![Path::new(
+                "Straight",
+                vec![Vec3::ZERO, Vec3::new(10.0, 0.0, 0.0)],
+            )]))
+            .insert_resource(GameTime::stepped(Duration::from_secs_f32(0.1)))
+            .insert_resource(BeamAssets {
+                mesh: Handle::default(),
+                material: Handle::default(),
+            })
+            .init_resource::<SpatialGrid>()
+            .add_event::<DamageDealt>()
+            .add_event::<TowerFired>()
+            .add_system(hitscan_shooting)
+            .add_system(chain_lightning_shooting);
+
+        app
+    }
+
+    /// A tower of `tower_type` at the origin with its first shot ready.
+    fn spawn_loaded_tower(app: &mut App, tower_type: TowerType) -> Entity {
+        let mut tower = Tower::new(
+            tower_type,
+            app.world.resource::<GameConfig>(),
+            app.world.resource::<GameAssets>(),
+        );
+        let cooldown = tower.shooting_timer.duration();
+        tower.shooting_timer.tick(cooldown);
+
+        app.world
+            .spawn()
+            .insert(tower)
+            .insert(GlobalTransform::default())
+            .id()
+    }
+
+    /// A target `distance` along +X from `tower`'s muzzle.
+    fn spawn_target_from(app: &mut App, tower: Entity, distance: f32, progress: f32) -> Entity {
+        let position =
+            app.world.get::<Tower>(tower).unwrap().muzzle(Vec3::ZERO) + Vec3::X * distance;
+        let target = app
+            .world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .insert(Health { value: 10, max: 10 })
+            .insert(PathFollower {
+                progress,
+                ..default()
+            })
+            .insert(GlobalTransform::from_translation(position))
+            .id();
+        app.world
+            .resource_mut::<SpatialGrid>()
+            .insert(target, position);
+
+        target
+    }
+
+    fn damage_dealt(app: &App) -> Vec<(Entity, i32)> {
+        let events = app.world.resource::<Events<DamageDealt>>();
+        events
+            .get_reader()
+            .iter(events)
+            .map(|damage| (damage.target, damage.amount))
+            .collect()
+    }
+
+    #[test]
+    fn hitscan_damages_its_target_on_the_frame_it_fires() {
+        let mut app = app();
+        let tower = spawn_loaded_tower(&mut app, TowerType::Sniper);
+        app.world.entity_mut(tower).insert(Hitscan::default());
+        let target = spawn_target_from(&mut app, tower, 3.0, 0.0);
+        let damage = app.world.get::<Tower>(tower).unwrap().damage;
+
+        app.update();
+
+        assert_eq!(app.world.get::<Health>(target).unwrap().value, 10 - damage);
+        assert_eq!(damage_dealt(&app), vec![(target, damage)]);
+        assert_eq!(
+            app.world
+                .query_filtered::<(), With<Bullet>>()
+                .iter(&app.world)
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn chain_lightning_jumps_through_a_cluster_losing_damage() {
+        let mut app = app();
+        let tower = spawn_loaded_tower(&mut app, TowerType::Tesla);
+        app.world
+            .entity_mut(tower)
+            .insert(ChainLightning::default());
+        // The nearest target is struck first, so the arc runs outward from there.
+        let first = spawn_target_from(&mut app, tower, 2.0, 1.0);
+        let jumped: Vec<_> = [3.0, 4.0, 5.0]
+            .into_iter()
+            .map(|distance| spawn_target_from(&mut app, tower, distance, 0.0))
+            .collect();
+        let out_of_jumps = spawn_target_from(&mut app, tower, 6.0, 0.0);
+
+        app.update();
+
+        assert_eq!(
+            damage_dealt(&app),
+            vec![(first, 4), (jumped[0], 3), (jumped[1], 2), (jumped[2], 2)]
+        );
+        assert_eq!(app.world.get::<Health>(out_of_jumps).unwrap().value, 10);
+    }
+}
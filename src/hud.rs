@@ -0,0 +1,286 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+#[derive(Component)]
+pub struct WaveHudUi;
+
+#[derive(Component)]
+pub struct WaveText;
+
+#[derive(Component)]
+pub struct StartWaveButton;
+
+/// The "wave cleared" overlay. It goes away on its own after a few seconds, or when clicked.
+#[derive(Component)]
+pub struct WaveSummary;
+
+/// What the wave HUD shows, worked out separately from the text so it stays easy to reason about.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct WaveHud {
+    /// One-based wave number, capped at `total` once every wave is done.
+    pub wave: usize,
+    pub total: usize,
+    /// Enemies still alive plus those the current wave has yet to spawn.
+    pub remaining: u32,
+    /// Whole seconds until the next wave starts, while waiting between waves.
+    pub countdown: Option<u32>,
+}
+
+impl WaveHud {
+    pub fn new(wave: &Wave, config: &WaveConfig, countdown: &WaveCountdown, alive: usize) -> Self {
+        let total = config.waves.len();
+        let unspawned = match config.waves.get(wave.index) {
+            Some(definition) if wave.active => definition.count.saturating_sub(wave.spawned),
+            _ => 0,
+        };
+
+        Self {
+            wave: (wave.index + 1).min(total),
+            total,
+            remaining: alive as u32 + unspawned,
+            countdown: (!wave.active && wave.index < total)
+                .then(|| countdown.remaining_secs().ceil() as u32),
+        }
+    }
+}
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Next).with_system(spawn_wave_hud))
+            .add_system_set(
+                SystemSet::on_update(GameState::Next)
+                    .with_system(update_wave_hud)
+                    .with_system(start_wave_button)
+                    .with_system(spawn_wave_summary)
+                    .with_system(dismiss_wave_summary),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Next).with_system(despawn_wave_hud));
+    }
+}
+
+fn spawn_wave_hud(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::FlexStart,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(WaveHudUi)
+        .insert(Name::new("WaveHudUi"))
+        .with_children(|commands| {
+            commands
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 32.0,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(WaveText);
+
+            spawn_button(commands, &assets.font, "Start wave", StartWaveButton);
+        });
+}
+
+fn update_wave_hud(
+    mut texts: Query<&mut Text, With<WaveText>>,
+    mut buttons: Query<&mut Style, With<StartWaveButton>>,
+    targets: Query<(), With<Target>>,
+    wave: Res<Wave>,
+    config: Res<WaveConfig>,
+    countdown: Res<WaveCountdown>,
+) {
+    let hud = WaveHud::new(&wave, &config, &countdown, targets.iter().count());
+    let mut contents = format!(
+        "Wave {} / {}\nEnemies left: {}",
+        hud.wave, hud.total, hud.remaining
+    );
+
+    if let Some(seconds) = hud.countdown {
+        contents.push_str(&format!("\nNext wave in {}s", seconds));
+    }
+
+    for mut text in &mut texts {
+        if text.sections[0].value != contents {
+            text.sections[0].value = contents.clone();
+        }
+    }
+
+    // Only offer the early start between waves, hiding it from layout so it can't be clicked.
+    let display = if hud.countdown.is_some() {
+        Display::Flex
+    } else {
+        Display::None
+    };
+
+    for mut style in &mut buttons {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
+
+fn start_wave_button(
+    buttons: Query<&Interaction, (Changed<Interaction>, With<StartWaveButton>)>,
+    mut requests: EventWriter<StartWaveEarly>,
+    replay: Res<Replay>,
+) {
+    if !replay.accepts_input() {
+        return;
+    }
+
+    for interaction in &buttons {
+        if *interaction == Interaction::Clicked {
+            requests.send(StartWaveEarly);
+        }
+    }
+}
+
+fn spawn_wave_summary(
+    mut commands: Commands,
+    mut completed_events: EventReader<WaveCompleted>,
+    summaries: Query<Entity, With<WaveSummary>>,
+    stats: Res<WaveStats>,
+    assets: Res<GameAssets>,
+) {
+    let completed = match completed_events.iter().last() {
+        Some(completed) => completed,
+        None => return,
+    };
+
+    for summary in &summaries {
+        commands.entity(summary).despawn_recursive();
+    }
+
+    let contents = format!(
+        "Wave {} cleared! +{} gold\nKilled: {}  Leaked: {}  Gold earned: {}",
+        completed.index + 1,
+        stats.bonus,
+        stats.killed,
+        stats.leaked,
+        stats.gold_earned
+    );
+
+    commands
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(140.0),
+                    left: Val::Percent(35.0),
+                    ..default()
+                },
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+            ..default()
+        })
+        .insert(WaveSummary)
+        .insert(Lifetime {
+            timer: Timer::from_seconds(4.0, false),
+        })
+        .insert(Gameplay)
+        .insert(Name::new("WaveSummary"))
+        .with_children(|commands| {
+            commands.spawn_bundle(TextBundle::from_section(
+                contents,
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 32.0,
+                    color: Color::GOLD,
+                },
+            ));
+        });
+}
+
+fn dismiss_wave_summary(
+    mut commands: Commands,
+    summaries: Query<(Entity, &Interaction), (Changed<Interaction>, With<WaveSummary>)>,
+) {
+    for (entity, interaction) in &summaries {
+        if *interaction == Interaction::Clicked {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn despawn_wave_hud(mut commands: Commands, ui: Query<Entity, With<WaveHudUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> WaveConfig {
+        WaveConfig {
+            waves: (0..3)
+                .map(|_| WaveDefinition {
+                    count: 5,
+                    spawn_interval: 1.0,
+                    enemies: vec![EnemyType::Normal],
+                    lanes: vec![],
+                    boss: false,
+                })
+                .collect(),
+            health_scaling: 0.0,
+            reward_scaling: 0.0,
+        }
+    }
+
+    #[test]
+    fn remaining_counts_the_living_and_the_unspawned() {
+        let wave = Wave {
+            index: 1,
+            spawned: 4,
+            active: true,
+            ..default()
+        };
+
+        let hud = WaveHud::new(&wave, &config(), &WaveCountdown::default(), 2);
+
+        assert_eq!(
+            hud,
+            WaveHud {
+                wave: 2,
+                total: 3,
+                remaining: 3,
+                countdown: None,
+            }
+        );
+    }
+
+    #[test]
+    fn the_countdown_shows_between_waves() {
+        let wave = Wave {
+            index: 2,
+            ..default()
+        };
+        let mut countdown = WaveCountdown::default();
+        countdown
+            .timer
+            .tick(std::time::Duration::from_secs_f32(6.5));
+
+        let hud = WaveHud::new(&wave, &config(), &countdown, 0);
+
+        assert_eq!(hud.remaining, 0);
+        assert_eq!(hud.countdown, Some(4));
+    }
+}
@@ -0,0 +1,298 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs};
+
+use crate::*;
+
+const KEY_BINDINGS_FILE: &str = "keybindings.ron";
+
+/// Everything the player can do from the keyboard.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Pause,
+    Sell,
+    FastForward,
+    CycleTargeting,
+    MuteMusic,
+    QuickSave,
+    QuickLoad,
+    ToggleDebugOverlay,
+    /// Only counts with Ctrl held.
+    Undo,
+    ToggleRecording,
+    PlayReplay,
+    SelectTower(TowerType),
+}
+
+impl Action {
+    /// Every action in the order the settings menu lists them.
+    pub fn all() -> Vec<Action> {
+        let mut actions = vec![
+            Self::Pause,
+            Self::Sell,
+            Self::FastForward,
+            Self::CycleTargeting,
+            Self::MuteMusic,
+            Self::QuickSave,
+            Self::QuickLoad,
+            Self::ToggleDebugOverlay,
+            Self::Undo,
+            Self::ToggleRecording,
+            Self::PlayReplay,
+        ];
+        actions.extend(TowerType::ALL.map(Self::SelectTower));
+        actions
+    }
+
+    fn default_key(&self) -> KeyCode {
+        match self {
+            Self::Pause => KeyCode::Escape,
+            Self::Sell => KeyCode::Delete,
+            Self::FastForward => KeyCode::F,
+            Self::CycleTargeting => KeyCode::T,
+            Self::MuteMusic => KeyCode::M,
+            Self::QuickSave => KeyCode::F5,
+            Self::QuickLoad => KeyCode::F9,
+            Self::ToggleDebugOverlay => KeyCode::F3,
+            Self::Undo => KeyCode::Z,
+            Self::ToggleRecording => KeyCode::F6,
+            Self::PlayReplay => KeyCode::F7,
+            Self::SelectTower(TowerType::Cannon) => KeyCode::Key1,
+            Self::SelectTower(TowerType::Sniper) => KeyCode::Key2,
+            Self::SelectTower(TowerType::Mortar) => KeyCode::Key3,
+            Self::SelectTower(TowerType::Shotgun) => KeyCode::Key4,
+            Self::SelectTower(TowerType::Tesla) => KeyCode::Key5,
+            Self::SelectTower(TowerType::Railgun) => KeyCode::Key6,
+            Self::SelectTower(TowerType::Buff) => KeyCode::Key7,
+            Self::SelectTower(TowerType::GoldMine) => KeyCode::Key8,
+            Self::SelectTower(TowerType::Artillery) => KeyCode::Key9,
+            Self::SelectTower(TowerType::Decoy) => KeyCode::Key0,
+            Self::SelectTower(TowerType::Stunner) => KeyCode::Minus,
+            Self::SelectTower(TowerType::Frost) => KeyCode::Equals,
+            Self::SelectTower(TowerType::Poison) => KeyCode::P,
+        }
+    }
+}
+
+/// Which key triggers each `Action`. Input systems go through this rather than naming keys, so
+/// everything can be remapped. Saved to `keybindings.ron` whenever it changes.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct KeyBindings {
+    pub bindings: HashMap<Action, KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            bindings: Action::all()
+                .into_iter()
+                .map(|action| (action, action.default_key()))
+                .collect(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Reads the saved bindings, falling back to the defaults for an unreadable file and for any
+    /// action the file doesn't mention.
+    pub fn load() -> Self {
+        let mut bindings: Self = fs::read_to_string(KEY_BINDINGS_FILE)
+            .ok()
+            .and_then(|contents| ron::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        for action in Action::all() {
+            bindings
+                .bindings
+                .entry(action)
+                .or_insert_with(|| action.default_key());
+        }
+
+        bindings
+    }
+
+    pub fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!(?error, "Failed to serialize key bindings");
+                return;
+            }
+        };
+
+        if let Err(error) = fs::write(KEY_BINDINGS_FILE, contents) {
+            warn!(?error, "Failed to save key bindings");
+        }
+    }
+
+    pub fn key(&self, action: Action) -> KeyCode {
+        self.bindings
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_key())
+    }
+
+    /// Binds `key` to `action`. Whatever action had `key` before takes over `action`'s old key,
+    /// so no two actions ever share a key.
+    pub fn rebind(&mut self, action: Action, key: KeyCode) {
+        let previous = self.key(action);
+
+        if let Some(other) = self
+            .bindings
+            .iter()
+            .find(|(other, bound)| **other != action && **bound == key)
+            .map(|(other, _)| *other)
+        {
+            self.bindings.insert(other, previous);
+        }
+
+        self.bindings.insert(action, key);
+    }
+
+    /// Whether the action's key is held down.
+    pub fn is_action_pressed(&self, keyboard: &Input<KeyCode>, action: Action) -> bool {
+        keyboard.pressed(self.key(action))
+    }
+
+    /// Whether the action's key went down this frame.
+    pub fn is_action_just_pressed(&self, keyboard: &Input<KeyCode>, action: Action) -> bool {
+        keyboard.just_pressed(self.key(action))
+    }
+}
+
+/// The action waiting for a key press in the settings menu, if any.
+#[derive(Default)]
+pub struct Rebinding {
+    pub action: Option<Action>,
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RebindButton {
+    pub action: Action,
+}
+
+pub struct KeyBindingsPlugin;
+
+impl Plugin for KeyBindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(KeyBindings::load())
+            .init_resource::<Rebinding>()
+            .add_system(save_key_bindings)
+            .add_system_set(
+                SystemSet::on_update(GameState::Settings)
+                    .with_system(rebind_buttons)
+                    .with_system(capture_rebind_key)
+                    .with_system(
+                        update_rebind_labels
+                            .after(rebind_buttons)
+                            .after(capture_rebind_key),
+                    ),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Settings).with_system(cancel_rebinding));
+    }
+}
+
+/// Adds a button per action to the settings menu. Clicking one waits for the next key press
+/// and binds it.
+pub fn spawn_rebind_buttons(
+    commands: &mut ChildBuilder,
+    font: &Handle<Font>,
+    bindings: &KeyBindings,
+) {
+    for action in Action::all() {
+        commands
+            .spawn_bundle(ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(280.0), Val::Px(36.0)),
+                    margin: UiRect::all(Val::Px(3.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                color: Color::rgb(0.15, 0.15, 0.15).into(),
+                ..default()
+            })
+            .insert(RebindButton { action })
+            .with_children(|commands| {
+                commands.spawn_bundle(TextBundle::from_section(
+                    rebind_label(action, bindings, false),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                ));
+            });
+    }
+}
+
+fn rebind_label(action: Action, bindings: &KeyBindings, waiting: bool) -> String {
+    let name = match action {
+        Action::SelectTower(tower_type) => format!("Build {:?}", tower_type),
+        action => format!("{:?}", action),
+    };
+
+    if waiting {
+        format!("{}: press a key", name)
+    } else {
+        format!("{}: {:?}", name, bindings.key(action))
+    }
+}
+
+fn save_key_bindings(bindings: Res<KeyBindings>) {
+    if bindings.is_changed() && !bindings.is_added() {
+        bindings.save();
+    }
+}
+
+fn rebind_buttons(
+    buttons: Query<(&Interaction, &RebindButton), Changed<Interaction>>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction == Interaction::Clicked {
+            rebinding.action = Some(button.action);
+        }
+    }
+}
+
+fn capture_rebind_key(
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<KeyBindings>,
+    keyboard: Res<Input<KeyCode>>,
+) {
+    let action = match rebinding.action {
+        Some(action) => action,
+        None => return,
+    };
+
+    if let Some(key) = keyboard.get_just_pressed().next() {
+        bindings.rebind(action, *key);
+        rebinding.action = None;
+    }
+}
+
+fn update_rebind_labels(
+    buttons: Query<(&RebindButton, &Children)>,
+    mut texts: Query<&mut Text>,
+    bindings: Res<KeyBindings>,
+    rebinding: Res<Rebinding>,
+) {
+    if !bindings.is_changed() && !rebinding.is_changed() {
+        return;
+    }
+
+    for (button, children) in &buttons {
+        let waiting = rebinding.action == Some(button.action);
+
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = rebind_label(button.action, &bindings, waiting);
+            }
+        }
+    }
+}
+
+fn cancel_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.action = None;
+}
@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::*;
+
+const LEADERBOARD_FILE: &str = "leaderboard.ron";
+pub const LEADERBOARD_SIZE: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LeaderboardEntry {
+    pub score: u64,
+    pub waves: usize,
+    /// Seconds since the Unix epoch when the run ended.
+    pub timestamp: u64,
+}
+
+/// The best `LEADERBOARD_SIZE` runs, highest score first, saved to `leaderboard.ron` after every
+/// game.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+    /// Where the last run placed, if it made the table. Only kept for this session.
+    #[serde(skip)]
+    pub latest: Option<usize>,
+}
+
+impl Leaderboard {
+    /// Reads the saved table, starting an empty one if the file is missing or unreadable.
+    pub fn load() -> Self {
+        let mut leaderboard: Self = fs::read_to_string(LEADERBOARD_FILE)
+            .ok()
+            .and_then(|contents| ron::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        // A hand-edited file could be out of order or too long.
+        leaderboard.entries.sort_by(|a, b| b.score.cmp(&a.score));
+        leaderboard.entries.truncate(LEADERBOARD_SIZE);
+        leaderboard
+    }
+
+    pub fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!(?error, "Failed to serialize leaderboard");
+                return;
+            }
+        };
+
+        if let Err(error) = fs::write(LEADERBOARD_FILE, contents) {
+            warn!(?error, "Failed to save leaderboard");
+        }
+    }
+
+    /// Adds `entry` in score order and drops whatever falls off the bottom, returning where it
+    /// placed. Ties go below the entries already there.
+    pub fn insert(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        let rank = self
+            .entries
+            .partition_point(|existing| existing.score >= entry.score);
+
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(LEADERBOARD_SIZE);
+        Some(rank)
+    }
+}
+
+/// A run's end date as `YYYY-MM-DD`, in UTC.
+pub fn format_date(timestamp: u64) -> String {
+    // Days since the epoch to a civil date, after Howard Hinnant's `civil_from_days`.
+    let days = (timestamp / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecordRun;
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Leaderboard::load())
+            .add_system_set(
+                SystemSet::on_enter(GameState::GameOver).with_system(record_run.label(RecordRun)),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::Victory).with_system(record_run.label(RecordRun)),
+            );
+    }
+}
+
+fn record_run(mut leaderboard: ResMut<Leaderboard>, score: Res<Score>, wave: Res<Wave>) {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+
+    leaderboard.latest = leaderboard.insert(LeaderboardEntry {
+        score: score.value,
+        waves: wave.index,
+        timestamp,
+    });
+    leaderboard.save();
+}
+
+/// Lists the table as a column of text, with the last run's entry picked out.
+pub fn spawn_leaderboard(
+    commands: &mut ChildBuilder,
+    font: &Handle<Font>,
+    leaderboard: &Leaderboard,
+) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .with_children(|commands| {
+            let style = |color| TextStyle {
+                font: font.clone(),
+                font_size: 24.0,
+                color,
+            };
+
+            commands.spawn_bundle(TextBundle::from_section("High Scores", style(Color::WHITE)));
+
+            if leaderboard.entries.is_empty() {
+                commands.spawn_bundle(TextBundle::from_section("No runs yet", style(Color::GRAY)));
+            }
+
+            for (rank, entry) in leaderboard.entries.iter().enumerate() {
+                let color = if leaderboard.latest == Some(rank) {
+                    Color::YELLOW
+                } else {
+                    Color::WHITE
+                };
+
+                commands.spawn_bundle(TextBundle::from_section(
+                    format!(
+                        "{}. {}  wave {}  {}",
+                        rank + 1,
+                        entry.score,
+                        entry.waves,
+                        format_date(entry.timestamp)
+                    ),
+                    style(color),
+                ));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            score,
+            waves: 1,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn only_the_top_ten_are_kept_best_first() {
+        let mut leaderboard = Leaderboard::default();
+
+        for score in [40, 90, 10, 70, 30, 100, 20, 60, 80, 50, 5, 65] {
+            leaderboard.insert(entry(score));
+        }
+
+        let scores: Vec<u64> = leaderboard
+            .entries
+            .iter()
+            .map(|entry| entry.score)
+            .collect();
+        assert_eq!(scores, [100, 90, 80, 70, 65, 60, 50, 40, 30, 20]);
+
+        assert_eq!(leaderboard.insert(entry(1)), None);
+        assert_eq!(leaderboard.insert(entry(75)), Some(3));
+        assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+        assert_eq!(leaderboard.entries.last().unwrap().score, 30);
+    }
+
+    #[test]
+    fn dates_are_read_in_utc() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+    }
+}
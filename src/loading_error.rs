@@ -0,0 +1,197 @@
+use bevy::{app::AppExit, asset::LoadState, prelude::*};
+
+use crate::*;
+
+/// How long after a retry to keep ignoring the old failure, since the asset server only marks
+/// the file as loading again once its background task gets going.
+const RETRY_GRACE: f64 = 0.5;
+
+/// The asset that stopped `GameAssets` from loading, shown on the error screen.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LoadingError {
+    pub path: String,
+    /// When the player last asked to retry, in seconds since startup.
+    pub retried_at: Option<f64>,
+}
+
+#[derive(Component)]
+pub struct LoadingErrorUi;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LoadingErrorButton {
+    Retry,
+    Quit,
+}
+
+/// `bevy_asset_loader` waits forever on an asset that failed, so this watches the same paths
+/// and gives up on the first failure instead, offering to try again or quit.
+pub struct LoadingErrorPlugin;
+
+impl Plugin for LoadingErrorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::AssetLoading).with_system(watch_asset_loading),
+        )
+        .add_system_set(
+            SystemSet::on_enter(GameState::LoadingError).with_system(spawn_loading_error_ui),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::LoadingError).with_system(loading_error_buttons),
+        )
+        .add_system_set(
+            SystemSet::on_exit(GameState::LoadingError).with_system(despawn_loading_error_ui),
+        );
+    }
+}
+
+/// The first of `paths` that has failed to load, if any.
+pub fn failed_asset<'a>(asset_server: &AssetServer, paths: &[&'a str]) -> Option<&'a str> {
+    paths
+        .iter()
+        .find(|path| asset_server.get_load_state(**path) == LoadState::Failed)
+        .copied()
+}
+
+fn watch_asset_loading(
+    mut commands: Commands,
+    mut state: ResMut<State<GameState>>,
+    asset_server: Res<AssetServer>,
+    previous: Option<Res<LoadingError>>,
+    time: Res<Time>,
+) {
+    let retrying = previous
+        .and_then(|previous| previous.retried_at)
+        .map_or(false, |retried_at| {
+            time.seconds_since_startup() - retried_at < RETRY_GRACE
+        });
+    if retrying {
+        return;
+    }
+
+    let path = match failed_asset(&asset_server, GAME_ASSET_PATHS) {
+        Some(path) => path,
+        None => return,
+    };
+
+    error!(path, "Failed to load asset");
+    commands.insert_resource(LoadingError {
+        path: path.to_string(),
+        retried_at: None,
+    });
+
+    if let Err(error) = state.set(GameState::LoadingError) {
+        warn!(?error, "Failed to enter loading error state");
+    }
+}
+
+/// `GameAssets` never finished loading, so the font is loaded on its own here. If the font is
+/// what failed the message won't show, but the buttons still work.
+fn spawn_loading_error_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    error: Res<LoadingError>,
+) {
+    let font = asset_server.load("fonts/DejaVuSans.ttf");
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(LoadingErrorUi)
+        .insert(Name::new("LoadingErrorUi"))
+        .with_children(|commands| {
+            commands.spawn_bundle(TextBundle::from_section(
+                format!("Failed to load {}", error.path),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 40.0,
+                    color: Color::rgb(1.0, 0.4, 0.4),
+                },
+            ));
+            spawn_button(commands, &font, "Retry", LoadingErrorButton::Retry);
+            spawn_button(commands, &font, "Quit", LoadingErrorButton::Quit);
+        });
+}
+
+/// Retrying reloads the failed file and goes back to `AssetLoading`, where the loader picks up
+/// everything else where it left off.
+fn loading_error_buttons(
+    buttons: Query<(&Interaction, &LoadingErrorButton), Changed<Interaction>>,
+    mut state: ResMut<State<GameState>>,
+    mut exit: EventWriter<AppExit>,
+    mut error: ResMut<LoadingError>,
+    asset_server: Res<AssetServer>,
+    time: Res<Time>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        match button {
+            LoadingErrorButton::Retry => {
+                asset_server.reload_asset(error.path.as_str());
+                error.retried_at = Some(time.seconds_since_startup());
+
+                if let Err(error) = state.set(GameState::AssetLoading) {
+                    warn!(?error, "Failed to retry loading");
+                }
+            }
+            LoadingErrorButton::Quit => exit.send(AppExit),
+        }
+    }
+}
+
+fn despawn_loading_error_ui(mut commands: Commands, ui: Query<Entity, With<LoadingErrorUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn a_failed_load_names_the_asset_on_the_error_screen() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_state(GameState::AssetLoading)
+            .add_system_set(
+                SystemSet::on_update(GameState::AssetLoading).with_system(watch_asset_loading),
+            );
+
+        // Nothing here can read fonts, so the load fails once the asset server gets to it.
+        let _font = app
+            .world
+            .resource::<AssetServer>()
+            .load_untyped("fonts/DejaVuSans.ttf");
+
+        for _ in 0..200 {
+            app.update();
+            if *app.world.resource::<State<GameState>>().current() == GameState::LoadingError {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(
+            *app.world.resource::<State<GameState>>().current(),
+            GameState::LoadingError
+        );
+        assert_eq!(
+            app.world.resource::<LoadingError>().path,
+            "fonts/DejaVuSans.ttf"
+        );
+    }
+}
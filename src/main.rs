@@ -1,211 +1,391 @@
-use bevy::log::{debug, Level, LogSettings};
-use bevy::{prelude::*, utils::FloatOrd};
-use bevy::window::PresentMode;
+// Bevy systems take their dependencies as parameters and queries, which trips these lints often.
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use bevy::log::{Level, LogSettings};
+use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
 use bevy_editor_pls::prelude::*;
 use bevy_embedded_assets::EmbeddedAssetPlugin;
 
+mod audio;
+mod aura;
+mod ballistic;
+mod boss;
+mod bullet;
+mod camera;
+mod config;
+mod cooldown_bar;
+mod damage;
+mod debug_overlay;
+mod decoy;
+mod difficulty;
+mod economy;
+mod effects;
+mod floating_text;
+mod game_over;
+mod game_speed;
+mod ghost;
+mod gold_mine;
+mod grid;
+mod hazard;
+mod headless;
+mod healer;
+mod health_bar;
+mod heat;
+mod hitscan;
+mod hud;
+mod keybindings;
+mod leaderboard;
+mod loading_error;
+mod map;
+mod markers;
+mod menu;
+mod minimap;
+mod muzzle_flash;
+mod particles;
+mod path;
+mod pause;
+mod phase_shift;
+mod range_ring;
+mod replay;
+mod retreat;
+mod rng;
+mod save;
+mod score;
+mod selection_panel;
+mod settings;
+mod spatial;
+mod target;
+mod targeting;
+mod toolbar;
+mod tower;
+mod undo;
+mod upgrade;
+mod victory;
+mod wave;
+
+pub use audio::*;
+pub use aura::*;
+pub use ballistic::*;
+pub use boss::*;
+pub use bullet::*;
+pub use camera::*;
+pub use config::*;
+pub use cooldown_bar::*;
+pub use damage::*;
+pub use debug_overlay::*;
+pub use decoy::*;
+pub use difficulty::*;
+pub use economy::*;
+pub use effects::*;
+pub use floating_text::*;
+pub use game_over::*;
+pub use game_speed::*;
+pub use ghost::*;
+pub use gold_mine::*;
+pub use grid::*;
+pub use hazard::*;
+pub use headless::*;
+pub use healer::*;
+pub use health_bar::*;
+pub use heat::*;
+pub use hitscan::*;
+pub use hud::*;
+pub use keybindings::*;
+pub use leaderboard::*;
+pub use loading_error::*;
+pub use map::*;
+pub use markers::*;
+pub use menu::*;
+pub use minimap::*;
+pub use muzzle_flash::*;
+pub use particles::*;
+pub use path::*;
+pub use pause::*;
+pub use phase_shift::*;
+pub use range_ring::*;
+pub use replay::*;
+pub use retreat::*;
+pub use rng::*;
+pub use save::*;
+pub use score::*;
+pub use selection_panel::*;
+pub use settings::*;
+pub use spatial::*;
+pub use target::*;
+pub use targeting::*;
+pub use toolbar::*;
+pub use tower::*;
+pub use undo::*;
+pub use upgrade::*;
+pub use victory::*;
+pub use wave::*;
+
 fn eul_to_rad(deg: f32) -> f32 {
     deg * std::f32::consts::PI / 180.0
 }
 
-#[derive(Reflect, Component, Default)]
-#[reflect(Component)]
-pub struct Bullet {
-    direction: Vec3,
-    speed: f32,
-}
+#[derive(Component)]
+pub struct GameCamera;
 
-#[derive(Reflect, Component, Default)]
-#[reflect(Component)]
-pub struct Target {
-    speed: f32,
-}
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
 
-#[derive(Reflect, Component, Default)]
-#[reflect(Component)]
-pub struct Health {
-    value: i32,
-}
+    if let Some(headless) = headless_from_args(&args) {
+        run_headless(headless);
+        return;
+    }
 
-#[derive(Reflect, Component, Default)]
-pub struct Lifetime {
-    timer: Timer,
-}
+    let settings = DisplaySettings::load();
+    let (width, height) = settings.resolution;
+    let mut app = App::new();
 
-#[derive(Reflect, Component, Default)]
-#[reflect(Component)]
-pub struct TowerBase {}
+    app.insert_resource(LogSettings {
+        level: Level::DEBUG,
+        ..default()
+    })
+    .insert_resource(WindowDescriptor {
+        width,
+        height,
+        title: "Tower Defense Game".to_string(),
+        present_mode: settings.present_mode(),
+        ..default()
+    })
+    .insert_resource(ClearColor(Color::rgb(0.25, 0.25, 0.25)))
+    .insert_resource(settings.msaa())
+    .insert_resource(settings)
+    .add_loading_state(
+        LoadingState::new(GameState::AssetLoading)
+            .continue_to_state(GameState::MainMenu)
+            .with_collection::<GameAssets>(),
+    )
+    .add_state(GameState::AssetLoading)
+    .add_plugins_with(DefaultPlugins, |group| {
+        group.add_before::<bevy::asset::AssetPlugin, _>(EmbeddedAssetPlugin)
+    })
+    //.add_plugin(WorldInspectorPlugin::new())
+    .add_plugin(DebugOverlayPlugin)
+    .add_plugin(EditorPlugin)
+    .add_plugin(GameAudioPlugin)
+    .add_plugin(LoadingErrorPlugin)
+    .add_plugin(MuzzleFlashPlugin)
+    .add_plugin(SettingsPlugin);
 
-#[derive(Reflect, Component, Default)]
-#[reflect(Component)]
-pub struct Tower {
-    shooting_timer: Timer,
-    bullet_offset: Vec3,
+    add_game(&mut app);
+    app.run();
 }
 
-fn main() {
-    App::new()
-        .insert_resource(LogSettings {
-            level: Level::DEBUG,
-            ..default()
-        })
-        .insert_resource(WindowDescriptor {
-            width: 1920.0,
-            height: 1080.0,
-            title: "Tower Defense Game".to_string(),
-            present_mode: PresentMode::AutoVsync,
-            ..default()
+/// The level and every plugin that doesn't need a window, renderer or audio device, shared by
+/// the normal game and `run_headless`.
+pub fn add_game(app: &mut App) {
+    let maps = Maps::embedded();
+
+    app.insert_resource(maps.current().paths())
+        .insert_resource(maps)
+        .insert_resource(WaveConfig {
+            waves: vec![
+                WaveDefinition {
+                    count: 5,
+                    spawn_interval: 2.0,
+                    enemies: vec![EnemyType::Normal],
+                    lanes: vec![0],
+                    boss: false,
+                },
+                WaveDefinition {
+                    count: 8,
+                    spawn_interval: 1.5,
+                    enemies: vec![EnemyType::Normal, EnemyType::Fast],
+                    lanes: vec![0],
+                    boss: false,
+                },
+                WaveDefinition {
+                    count: 12,
+                    spawn_interval: 1.0,
+                    enemies: vec![
+                        EnemyType::Fast,
+                        EnemyType::Normal,
+                        EnemyType::Tank,
+                        EnemyType::Splitter,
+                        EnemyType::Flyer,
+                        EnemyType::Healer,
+                        EnemyType::Phantom,
+                    ],
+                    lanes: vec![0, 1],
+                    boss: true,
+                },
+            ],
+            health_scaling: 0.2,
+            reward_scaling: 0.1,
         })
-        .insert_resource(ClearColor(Color::rgb(0.25, 0.25, 0.25)))
-        .insert_resource(Msaa { samples: 4 })
-        .add_loading_state(
-            LoadingState::new(GameState::AssetLoading)
-                .continue_to_state(GameState::Next)
-                .with_collection::<GameAssets>(),
+        // First, since the other plugins add their gameplay systems to its `GameStep` stage.
+        .add_plugin(GameSpeedPlugin)
+        .add_plugin(AuraPlugin)
+        .add_plugin(BallisticPlugin)
+        .add_plugin(BossPlugin)
+        .add_plugin(BulletPlugin)
+        .add_plugin(BuildToolbarPlugin)
+        .add_plugin(CameraPlugin)
+        .add_plugin(ConfigPlugin)
+        .add_plugin(CooldownBarPlugin)
+        .add_plugin(DamagePlugin)
+        .add_plugin(DecoyPlugin)
+        .add_plugin(DifficultyPlugin)
+        .add_plugin(EconomyPlugin)
+        .add_plugin(EffectsPlugin)
+        .add_plugin(FloatingTextPlugin)
+        .add_plugin(GameOverPlugin)
+        .add_plugin(PlacementGhostPlugin)
+        .add_plugin(GoldMinePlugin)
+        .add_plugin(GridPlugin)
+        .add_plugin(HazardPlugin)
+        .add_plugin(HealerPlugin)
+        .add_plugin(HealthBarPlugin)
+        .add_plugin(HeatPlugin)
+        .add_plugin(HitscanPlugin)
+        .add_plugin(HudPlugin)
+        .add_plugin(KeyBindingsPlugin)
+        .add_plugin(LeaderboardPlugin)
+        .add_plugin(MapPlugin)
+        .add_plugin(MenuPlugin)
+        .add_plugin(MinimapPlugin)
+        .add_plugin(TargetPlugin)
+        .add_plugin(ParticlePlugin)
+        .add_plugin(PathPlugin)
+        .add_plugin(PathMarkerPlugin)
+        .add_plugin(PausePlugin)
+        .add_plugin(PhaseShiftPlugin)
+        .add_plugin(RangeRingPlugin)
+        .add_plugin(ReplayPlugin)
+        .add_plugin(RetreatPlugin)
+        .add_plugin(RngPlugin)
+        .add_plugin(SavePlugin)
+        .add_plugin(ScorePlugin)
+        .add_plugin(SelectionPanelPlugin)
+        .add_plugin(SpatialPlugin)
+        .add_plugin(TargetingPlugin)
+        .add_plugin(TowerPlugin)
+        .add_plugin(UndoPlugin)
+        .add_plugin(UpgradePlugin)
+        .add_plugin(VictoryPlugin)
+        .add_plugin(WavePlugin)
+        .add_system_set(
+            SystemSet::on_enter(GameState::Next).with_system(spawn_basic_scene.after(LoadMap)),
         )
-        .add_state(GameState::AssetLoading)
-        .add_plugins_with(DefaultPlugins, |group| {
-            group.add_before::<bevy::asset::AssetPlugin, _>(EmbeddedAssetPlugin)
-        })
-        .add_plugin(EditorPlugin)
-        //.add_plugin(WorldInspectorPlugin::new())
-        .add_system_set(SystemSet::on_enter(GameState::Next).with_system(spawn_basic_scene))
-        .add_startup_system(spawn_camera)
-        //.add_system_set(SystemSet::on_update(GameState::Next).with_system(add_barrel))
-        .add_system_set(SystemSet::on_update(GameState::Next).with_system(tower_shooting))
-        .add_system_set(SystemSet::on_update(GameState::Next).with_system(bullet_despawn))
-        .add_system_set(SystemSet::on_update(GameState::Next).with_system(move_targets))
-        .add_system_set(SystemSet::on_update(GameState::Next).with_system(move_bullets))
-        .register_type::<Tower>()
-        .register_type::<Lifetime>()
-        .register_type::<Target>()
-        .run();
+        .add_startup_system(spawn_camera);
 }
 
+/// Every file `GameAssets` loads, watched by `watch_asset_loading`. Keep it in step with the
+/// collection below.
+pub const GAME_ASSET_PATHS: &[&str] = &[
+    "TowerBase.glb#Scene0",
+    "TowerBarrel.glb#Scene0",
+    "Bullet.glb#Scene0",
+    "Enemy.glb#Scene0",
+    "fonts/DejaVuSans.ttf",
+    "audio/shoot.wav",
+    "audio/music.wav",
+];
+
 #[derive(AssetCollection)]
-struct GameAssets {
+pub struct GameAssets {
     #[asset(path = "TowerBase.glb#Scene0")]
     tower_base_scene: Handle<Scene>,
     #[asset(path = "TowerBarrel.glb#Scene0")]
     tower_barrel_scene: Handle<Scene>,
     #[asset(path = "Bullet.glb#Scene0")]
     bullet_scene: Handle<Scene>,
+    // Sniper rounds and mortar shells share the bullet model until they get their own.
+    #[asset(path = "Bullet.glb#Scene0")]
+    sniper_bullet_scene: Handle<Scene>,
+    #[asset(path = "Bullet.glb#Scene0")]
+    mortar_shell_scene: Handle<Scene>,
     #[asset(path = "Enemy.glb#Scene0")]
     target_scene: Handle<Scene>,
+    #[asset(path = "fonts/DejaVuSans.ttf")]
+    font: Handle<Font>,
+    #[asset(path = "audio/shoot.wav")]
+    shoot_sfx: Handle<AudioSource>,
+    #[asset(path = "audio/music.wav")]
+    music: Handle<AudioSource>,
 }
 
-fn move_bullets(mut bullets: Query<(&Bullet, &mut Transform)>, time: Res<Time>) {
-    for (bullet, mut transform) in &mut bullets {
-        transform.translation += bullet.direction.normalize() * bullet.speed * time.delta_seconds();
-    }
-}
-
-fn move_targets(mut targets: Query<(&Target, &mut Transform)>, time: Res<Time>) {
-    for (target, mut transform) in &mut targets {
-        transform.translation.x += target.speed * time.delta_seconds();
-    }
-}
-
-fn tower_shooting(
-    mut commands: Commands,
-    mut towers: Query<(Entity, &mut Tower, &GlobalTransform)>,
-    targets: Query<&GlobalTransform, With<Target>>,
-    assets: Res<GameAssets>,
-    time: Res<Time>,
-) {
-    for (tower_ent, mut tower, transform) in &mut towers {
-        tower.shooting_timer.tick(time.delta());
-
-        if tower.shooting_timer.just_finished() {
-            let bullet_spawn = transform.translation() + tower.bullet_offset;
-
-            let direction = targets
-                .iter()
-                .min_by_key(|target_transform| {
-                    FloatOrd(Vec3::distance(target_transform.translation(), bullet_spawn))
-                })
-                .map(|closest_target| closest_target.translation() - bullet_spawn);
-
-            if let Some(direction) = direction {
-                commands.entity(tower_ent).with_children(|commands| {
-                    commands
-                        .spawn_bundle(SceneBundle {
-                            scene: assets.bullet_scene.clone(),
-                            transform: Transform::from_translation(tower.bullet_offset),
-                            ..Default::default()
-                        })
-                        .insert(Lifetime {
-                            timer: Timer::from_seconds(2.5, false),
-                        })
-                        .insert(Bullet {
-                            direction,
-                            speed: 2.5,
-                        })
-                        .insert(Name::new("Bullet"));
-                });
-                debug!(?direction.x, ?direction.y, ?direction.z);
-            }
+impl GameAssets {
+    /// Empty handles for `run_headless`, where nothing is ever drawn or played.
+    pub fn headless() -> Self {
+        Self {
+            tower_base_scene: default(),
+            tower_barrel_scene: default(),
+            bullet_scene: default(),
+            sniper_bullet_scene: default(),
+            mortar_shell_scene: default(),
+            target_scene: default(),
+            font: default(),
+            shoot_sfx: default(),
+            music: default(),
         }
     }
-}
 
-fn bullet_despawn(
-    mut commands: Commands,
-    mut bullets: Query<(Entity, &mut Lifetime)>,
-    time: Res<Time>,
-) {
-    for (entity, mut lifetime) in &mut bullets {
-        lifetime.timer.tick(time.delta());
-
-        if lifetime.timer.just_finished() {
-            commands.entity(entity).despawn_recursive();
+    pub fn projectile_scene(&self, tower_type: TowerType) -> Handle<Scene> {
+        match tower_type {
+            TowerType::Sniper | TowerType::Railgun => self.sniper_bullet_scene.clone(),
+            TowerType::Mortar | TowerType::Artillery => self.mortar_shell_scene.clone(),
+            TowerType::Cannon
+            | TowerType::Shotgun
+            | TowerType::Tesla
+            | TowerType::Buff
+            | TowerType::GoldMine
+            | TowerType::Decoy
+            | TowerType::Stunner
+            | TowerType::Frost
+            | TowerType::Poison => self.bullet_scene.clone(),
         }
     }
 }
 
 fn spawn_camera(mut commands: Commands) {
+    let controller = CameraController::looking_at(Vec3::new(5.0, 5.0, 5.0), Vec3::ZERO);
+
     commands
         .spawn_bundle(Camera3dBundle {
-            transform: Transform::from_xyz(5.0, 5.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+            transform: controller.transform(),
             ..default()
         })
+        .insert(controller)
+        .insert(GameCamera)
         .insert(Name::new("Camera"));
 }
 
+/// The free starting Cannon goes on the map's `starting_tower` cell, and is left out if the map
+/// has that cell blocked.
 fn spawn_basic_scene(
     assets: Res<GameAssets>,
+    config: Res<GameConfig>,
+    maps: Res<Maps>,
+    mut grid: ResMut<GridMap>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut commands: Commands,
 ) {
     commands
         .spawn_bundle(PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Plane { size: 50.0 })),
+            mesh: meshes.add(Mesh::from(shape::Plane {
+                size: maps.current().ground_size,
+            })),
             material: materials.add(Color::rgb(0.3, 0.3, 0.3).into()),
             ..default()
         })
+        .insert(Gameplay)
         .insert(Name::new("Ground"));
 
-    commands
-        .spawn_bundle(SceneBundle {
-            scene: assets.target_scene.clone(),
-            transform: Transform::from_xyz(-2.0, 0.5, 1.5)
-                .with_rotation(Quat::from_rotation_y(eul_to_rad(90.0))),
-            ..default()
-        })
-        .insert(Target { speed: 0.3 })
-        .insert(Health { value: 3 })
-        .insert(Name::new("Target"));
-
-    commands
-        .spawn_bundle(SceneBundle {
-            scene: assets.tower_base_scene.clone(),
-            transform: Transform::from_xyz(0.0, 0.75, 0.0),
-            ..default()
-        })
-        .insert(TowerBase {})
-        .insert(Name::new("Tower"));
+    let cell = maps.current().starting_cell();
+    if grid.is_free(cell) {
+        let tower = Tower::new(TowerType::Cannon, &config, &assets);
+        let position = grid.cell_to_world(cell);
+        let tower = spawn_tower(&mut commands, &assets, &config, position, tower);
+        grid.occupy(cell, tower);
+    } else {
+        warn!(?cell, "Starting tower cell is blocked, skipping");
+    }
 
     commands
         .spawn_bundle(PointLightBundle {
@@ -218,32 +398,23 @@ fn spawn_basic_scene(
             transform: Transform::from_xyz(-4.0, 8.0, 4.0),
             ..default()
         })
+        .insert(Gameplay)
         .insert(Name::new("Light"));
 }
 
-fn add_barrel(
-    mut commands: Commands,
-    tower_bases: Query<&Transform, With<TowerBase>>,
-    assets: Res<GameAssets>,
-) {
-    for transform in &tower_bases {
-        debug!("Tower base found");
-
-        commands.spawn_bundle(SceneBundle {
-            scene: assets.tower_barrel_scene.clone(),
-            transform: Transform::from_translation(transform.translation),
-            ..default()
-        })
-        .insert(Tower {
-            shooting_timer: Timer::from_seconds(1.0, true),
-            bullet_offset: Vec3::new(0.0, 0.5, 0.0)
-        })
-        .insert(Name::new("TowerBarrel"));
-    }
-}
-
 #[derive(Clone, Eq, PartialEq, Debug, Hash)]
-enum GameState {
+pub enum GameState {
     AssetLoading,
+    MainMenu,
     Next,
+    Paused,
+    GameOver,
+    /// Entered once every wave has been cleared with lives to spare.
+    Victory,
+    /// Passed through for a frame when restarting from the pause menu.
+    Restarting,
+    /// Pushed over `MainMenu` or `Paused` while the settings menu is open.
+    Settings,
+    /// Entered from `AssetLoading` when one of `GameAssets` fails to load.
+    LoadingError,
 }
@@ -0,0 +1,213 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::*;
+
+/// The maps that ship with the game, in the order the menu cycles through them.
+const MAP_FILES: &[&str] = &[
+    include_str!("../assets/maps/crossroads.ron"),
+    include_str!("../assets/maps/switchback.ron"),
+];
+
+/// One lane, walked from `spawn` through each of `waypoints` to `goal`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct MapPath {
+    pub name: String,
+    pub spawn: (f32, f32, f32),
+    #[serde(default)]
+    pub waypoints: Vec<(f32, f32, f32)>,
+    pub goal: (f32, f32, f32),
+}
+
+impl MapPath {
+    pub fn to_path(&self) -> Path {
+        let points = std::iter::once(self.spawn)
+            .chain(self.waypoints.iter().copied())
+            .chain(std::iter::once(self.goal))
+            .map(|(x, y, z)| Vec3::new(x, y, z))
+            .collect();
+
+        Path::new(&self.name, points)
+    }
+}
+
+/// A level as laid out in `assets/maps`. Cells along every path are kept clear of towers, and
+/// `blocked` lists any others that can't be built on. `hazards` are laid over the ground when a
+/// game starts, along with the free tower every game begins with on `starting_tower`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct MapDefinition {
+    pub name: String,
+    pub ground_size: f32,
+    pub paths: Vec<MapPath>,
+    #[serde(default)]
+    pub blocked: Vec<(i32, i32)>,
+    #[serde(default)]
+    pub hazards: Vec<HazardDefinition>,
+    #[serde(default)]
+    pub starting_tower: (i32, i32),
+}
+
+impl MapDefinition {
+    /// Parses a map, rejecting one without any paths.
+    pub fn parse(contents: &str) -> Result<Self, ron::Error> {
+        let map: Self = ron::from_str(contents)?;
+
+        if map.paths.is_empty() {
+            return Err(ron::Error::Message(format!(
+                "map {} has no paths",
+                map.name
+            )));
+        }
+
+        Ok(map)
+    }
+
+    pub fn paths(&self) -> Paths {
+        Paths::new(self.paths.iter().map(MapPath::to_path).collect())
+    }
+
+    /// Replaces the grid's blocked cells with this map's.
+    pub fn block_cells(&self, grid: &mut GridMap) {
+        grid.blocked.clear();
+
+        for path in &self.paths {
+            grid.block_path(&path.to_path());
+        }
+
+        grid.blocked
+            .extend(self.blocked.iter().map(|&(x, z)| IVec2::new(x, z)));
+    }
+
+    pub fn starting_cell(&self) -> IVec2 {
+        IVec2::new(self.starting_tower.0, self.starting_tower.1)
+    }
+}
+
+/// Every map on offer and which one the next game is played on.
+pub struct Maps {
+    pub definitions: Vec<MapDefinition>,
+    pub selected: usize,
+}
+
+impl Maps {
+    /// The maps embedded in the binary. Panics if one of them doesn't parse, since that's a
+    /// broken build rather than something a player can fix.
+    pub fn embedded() -> Self {
+        Self {
+            definitions: MAP_FILES
+                .iter()
+                .map(|contents| MapDefinition::parse(contents).expect("embedded map is invalid"))
+                .collect(),
+            selected: 0,
+        }
+    }
+
+    pub fn current(&self) -> &MapDefinition {
+        &self.definitions[self.selected]
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.definitions.len();
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LoadMap;
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Next).with_system(load_map.label(LoadMap)),
+        );
+    }
+}
+
+/// Lays out the selected map every time a game starts, before anything is spawned on it.
+fn load_map(maps: Res<Maps>, mut paths: ResMut<Paths>, mut grid: ResMut<GridMap>) {
+    let map = maps.current();
+
+    *paths = map.paths();
+    map.block_cells(&mut grid);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_map_starts_its_tower_on_a_free_cell() {
+        for map in Maps::embedded().definitions {
+            let mut grid = GridMap::default();
+            map.block_cells(&mut grid);
+
+            assert!(grid.is_free(map.starting_cell()), "{}", map.name);
+        }
+    }
+
+    fn load(selected: usize) -> App {
+        let mut maps = Maps::embedded();
+        maps.selected = selected;
+
+        let mut app = App::new();
+        app.insert_resource(maps)
+            .insert_resource(Paths::new(vec![Path::new("Old", vec![Vec3::ZERO])]))
+            .init_resource::<GridMap>()
+            .add_system(load_map);
+        app.update();
+
+        app
+    }
+
+    #[test]
+    fn switchback_is_laid_out_as_its_file_describes() {
+        let app = load(1);
+
+        let paths = app.world.resource::<Paths>();
+        assert_eq!(paths.routes.len(), 1);
+        assert_eq!(paths.get(0).name, "Switchback");
+        assert_eq!(
+            paths.get(0).waypoints,
+            vec![
+                Vec3::new(-6.0, 0.5, 5.0),
+                Vec3::new(4.0, 0.5, 5.0),
+                Vec3::new(4.0, 0.5, 2.0),
+                Vec3::new(-4.0, 0.5, 2.0),
+                Vec3::new(-4.0, 0.5, -2.0),
+                Vec3::new(4.0, 0.5, -2.0),
+                Vec3::new(4.0, 0.5, -6.0),
+            ]
+        );
+
+        let grid = app.world.resource::<GridMap>();
+        for rock in [IVec2::new(1, -5), IVec2::new(2, -5), IVec2::new(1, -6)] {
+            assert!(!grid.is_free(rock), "{}", rock);
+        }
+        // A cell along each stretch of the path.
+        for cell in [
+            IVec2::new(0, 5),
+            IVec2::new(4, 3),
+            IVec2::new(0, 2),
+            IVec2::new(-4, 0),
+            IVec2::new(0, -2),
+            IVec2::new(4, -4),
+        ] {
+            assert!(!grid.is_free(cell), "{}", cell);
+        }
+        assert!(grid.is_free(IVec2::new(0, 0)));
+    }
+
+    #[test]
+    fn crossroads_has_two_lanes() {
+        let app = load(0);
+
+        let names: Vec<_> = app
+            .world
+            .resource::<Paths>()
+            .iter()
+            .map(|path| path.name.as_str())
+            .collect();
+        assert_eq!(names, ["South", "North"]);
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// Where enemies come from, sitting on the first waypoint of the `path`th of the `Paths`.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct SpawnPortal {
+    pub path: usize,
+}
+
+/// The base enemies are heading for, sitting on the last waypoint of its path. It pulses for
+/// `pulse`'s duration whenever an enemy leaks there.
+#[derive(Reflect, Component)]
+#[reflect(Component)]
+pub struct Goal {
+    pub path: usize,
+    pub pulse: Timer,
+    /// Extra scale at the peak of a pulse.
+    pub pulse_scale: f32,
+}
+
+impl Default for Goal {
+    fn default() -> Self {
+        let mut pulse = Timer::from_seconds(0.4, false);
+        pulse.set_elapsed(pulse.duration());
+
+        Self {
+            path: 0,
+            pulse,
+            pulse_scale: 0.4,
+        }
+    }
+}
+
+pub struct PathMarkerPlugin;
+
+impl Plugin for PathMarkerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SpawnPortal>()
+            .register_type::<Goal>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Next).with_system(spawn_path_markers.after(LoadMap)),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Next).with_system(follow_path))
+            .add_system_to_stage(GameStep, pulse_goal_on_leak);
+    }
+}
+
+fn spawn_path_markers(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    paths: Res<Paths>,
+) {
+    let portal_mesh = meshes.add(Mesh::from(shape::Torus {
+        radius: 0.5,
+        ring_radius: 0.1,
+        ..default()
+    }));
+    let portal_material = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.6, 0.2, 0.9),
+        emissive: Color::rgb(0.3, 0.1, 0.5),
+        ..default()
+    });
+    let goal_mesh = meshes.add(Mesh::from(shape::Box::new(0.8, 0.6, 0.8)));
+    let goal_material = materials.add(Color::rgb(0.2, 0.4, 0.9).into());
+
+    // Lanes that share an end get a goal each, sitting on top of one another.
+    for (index, path) in paths.iter().enumerate() {
+        commands
+            .spawn_bundle(PbrBundle {
+                mesh: portal_mesh.clone(),
+                material: portal_material.clone(),
+                transform: Transform::from_translation(path.start()),
+                ..default()
+            })
+            .insert(SpawnPortal { path: index })
+            .insert(Gameplay)
+            .insert(Name::new(format!("SpawnPortal {}", path.name)));
+
+        commands
+            .spawn_bundle(PbrBundle {
+                mesh: goal_mesh.clone(),
+                material: goal_material.clone(),
+                transform: Transform::from_translation(path.end()),
+                ..default()
+            })
+            .insert(Goal {
+                path: index,
+                ..default()
+            })
+            .insert(Gameplay)
+            .insert(Name::new(format!("Goal {}", path.name)));
+    }
+}
+
+/// Moves the markers along with the ends of their paths whenever the waypoints change.
+fn follow_path(
+    mut portals: Query<(&SpawnPortal, &mut Transform), Without<Goal>>,
+    mut goals: Query<(&Goal, &mut Transform)>,
+    paths: Res<Paths>,
+) {
+    if !paths.is_changed() {
+        return;
+    }
+
+    for (portal, mut transform) in &mut portals {
+        transform.translation = paths.get(portal.path).start();
+    }
+
+    for (goal, mut transform) in &mut goals {
+        transform.translation = paths.get(goal.path).end();
+    }
+}
+
+fn pulse_goal_on_leak(
+    mut goals: Query<(&mut Goal, &mut Transform)>,
+    mut leak_events: EventReader<TargetLeak>,
+    time: Res<GameTime>,
+) {
+    let leaked: Vec<usize> = leak_events.iter().map(|leak| leak.path).collect();
+
+    for (mut goal, mut transform) in &mut goals {
+        if leaked.contains(&goal.path) {
+            goal.pulse.reset();
+        }
+
+        goal.pulse.tick(time.delta());
+
+        // Swells up and back down once over the pulse.
+        let t = goal.pulse.percent();
+        let swell = (t * std::f32::consts::PI).sin() * goal.pulse_scale;
+        transform.scale = Vec3::splat(1.0 + swell);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::AssetPlugin;
+
+    fn markers(app: &mut App) -> (Vec3, Vec3) {
+        let portal = app
+            .world
+            .query_filtered::<&Transform, With<SpawnPortal>>()
+            .single(&app.world)
+            .translation;
+        let goal = app
+            .world
+            .query_filtered::<&Transform, With<Goal>>()
+            .single(&app.world)
+            .translation;
+
+        (portal, goal)
+    }
+
+    #[test]
+    fn markers_sit_on_the_ends_of_the_path() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Mesh>()
+            .add_asset::<StandardMaterial>()
+            .insert_resource(Paths::new(vec![Path::new(
+                "Bend",
+                vec![
+                    Vec3::new(-5.0, 0.0, 0.0),
+                    Vec3::new(0.0, 0.0, 0.0),
+                    Vec3::new(0.0, 0.0, 5.0),
+                ],
+            )]))
+            .add_startup_system(spawn_path_markers)
+            .add_system(follow_path);
+
+        app.update();
+        assert_eq!(
+            markers(&mut app),
+            (Vec3::new(-5.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 5.0))
+        );
+
+        app.insert_resource(Paths::new(vec![Path::new(
+            "Moved",
+            vec![Vec3::new(1.0, 0.0, 1.0), Vec3::new(2.0, 0.0, 2.0)],
+        )]));
+        app.update();
+        assert_eq!(
+            markers(&mut app),
+            (Vec3::new(1.0, 0.0, 1.0), Vec3::new(2.0, 0.0, 2.0))
+        );
+    }
+}
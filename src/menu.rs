@@ -0,0 +1,224 @@
+use bevy::{app::AppExit, prelude::*};
+
+use crate::*;
+
+#[derive(Component)]
+pub struct MainMenuUi;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MenuButton {
+    Play,
+    Difficulty,
+    Map,
+    Settings,
+    Quit,
+}
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(spawn_main_menu))
+            .add_system_set(
+                SystemSet::on_update(GameState::MainMenu)
+                    .with_system(menu_buttons)
+                    .with_system(update_difficulty_label.after(menu_buttons))
+                    .with_system(update_map_label.after(menu_buttons)),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::MainMenu).with_system(despawn_main_menu));
+    }
+}
+
+pub fn spawn_button<T: Component>(
+    commands: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    button: T,
+) -> Entity {
+    commands
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(220.0), Val::Px(60.0)),
+                margin: UiRect::all(Val::Px(10.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::rgb(0.15, 0.15, 0.15).into(),
+            ..default()
+        })
+        .insert(button)
+        .with_children(|commands| {
+            commands.spawn_bundle(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+            ));
+        })
+        .id()
+}
+
+fn difficulty_label(difficulty: Difficulty) -> String {
+    format!("{:?}", difficulty)
+}
+
+fn map_label(map: &MapDefinition) -> String {
+    format!("Map: {}", map.name)
+}
+
+fn spawn_main_menu(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    difficulty: Res<DifficultySettings>,
+    maps: Res<Maps>,
+    leaderboard: Res<Leaderboard>,
+) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(MainMenuUi)
+        .insert(Name::new("MainMenuUi"))
+        .with_children(|commands| {
+            spawn_button(commands, &assets.font, "Play", MenuButton::Play);
+            spawn_button(
+                commands,
+                &assets.font,
+                &difficulty_label(difficulty.difficulty),
+                MenuButton::Difficulty,
+            );
+            spawn_button(
+                commands,
+                &assets.font,
+                &map_label(maps.current()),
+                MenuButton::Map,
+            );
+            spawn_button(commands, &assets.font, "Settings", MenuButton::Settings);
+            spawn_button(commands, &assets.font, "Quit", MenuButton::Quit);
+            spawn_leaderboard(commands, &assets.font, &leaderboard);
+        });
+}
+
+fn menu_buttons(
+    buttons: Query<(&Interaction, &MenuButton), Changed<Interaction>>,
+    mut state: ResMut<State<GameState>>,
+    mut difficulty: ResMut<DifficultySettings>,
+    mut maps: ResMut<Maps>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        match button {
+            MenuButton::Play => {
+                if let Err(error) = state.set(GameState::Next) {
+                    warn!(?error, "Failed to start game");
+                }
+            }
+            MenuButton::Difficulty => {
+                *difficulty = DifficultySettings::new(difficulty.difficulty.next());
+            }
+            MenuButton::Map => maps.select_next(),
+            MenuButton::Settings => {
+                if let Err(error) = state.push(GameState::Settings) {
+                    warn!(?error, "Failed to open settings");
+                }
+            }
+            MenuButton::Quit => exit.send(AppExit),
+        }
+    }
+}
+
+fn update_difficulty_label(
+    buttons: Query<(&MenuButton, &Children)>,
+    mut texts: Query<&mut Text>,
+    difficulty: Res<DifficultySettings>,
+) {
+    if !difficulty.is_changed() {
+        return;
+    }
+
+    for (button, children) in &buttons {
+        if *button != MenuButton::Difficulty {
+            continue;
+        }
+
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = difficulty_label(difficulty.difficulty);
+            }
+        }
+    }
+}
+
+fn update_map_label(
+    buttons: Query<(&MenuButton, &Children)>,
+    mut texts: Query<&mut Text>,
+    maps: Res<Maps>,
+) {
+    if !maps.is_changed() {
+        return;
+    }
+
+    for (button, children) in &buttons {
+        if *button != MenuButton::Map {
+            continue;
+        }
+
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = map_label(maps.current());
+            }
+        }
+    }
+}
+
+fn despawn_main_menu(mut commands: Commands, ui: Query<Entity, With<MainMenuUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn play_starts_the_game() {
+        let mut app = App::new();
+        app.add_state(GameState::MainMenu)
+            .init_resource::<DifficultySettings>()
+            .insert_resource(Maps::embedded())
+            .add_event::<AppExit>()
+            .add_system_set(SystemSet::on_update(GameState::MainMenu).with_system(menu_buttons))
+            .add_system_set(SystemSet::on_exit(GameState::MainMenu).with_system(despawn_main_menu));
+        let menu = app
+            .world
+            .spawn()
+            .insert(MainMenuUi)
+            .insert(MenuButton::Play)
+            .insert(Interaction::Clicked)
+            .id();
+
+        app.update();
+
+        assert_eq!(
+            app.world.resource::<State<GameState>>().current(),
+            &GameState::Next
+        );
+        assert!(app.world.get_entity(menu).is_none());
+    }
+}
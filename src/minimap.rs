@@ -0,0 +1,317 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::*;
+
+const MINIMAP_TOP: f32 = 60.0;
+const MINIMAP_RIGHT: f32 = 10.0;
+const PATH_DOT_SPACING: f32 = 0.25;
+
+/// The patch of ground the minimap shows, and how big it is on screen. The map is top-down with
+/// +X to the right and -Z at the top.
+pub struct Minimap {
+    pub world_center: Vec2,
+    /// Half the width of the square of ground covered, in world units.
+    pub world_half_extent: f32,
+    /// Width and height of the minimap in pixels.
+    pub size: f32,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self {
+            world_center: Vec2::new(-1.0, -1.0),
+            world_half_extent: 6.0,
+            size: 160.0,
+        }
+    }
+}
+
+impl Minimap {
+    /// Pixel position within the minimap, measured from its top left corner. Height is ignored.
+    pub fn world_to_minimap(&self, position: Vec3) -> Vec2 {
+        let offset = Vec2::new(position.x, position.z) - self.world_center;
+        let scale = self.size / (self.world_half_extent * 2.0);
+
+        (offset + Vec2::splat(self.world_half_extent)) * scale
+    }
+
+    /// The ground position under a pixel of the minimap, at height zero.
+    pub fn minimap_to_world(&self, pixel: Vec2) -> Vec3 {
+        let scale = self.size / (self.world_half_extent * 2.0);
+        let offset = pixel / scale - Vec2::splat(self.world_half_extent) + self.world_center;
+
+        Vec3::new(offset.x, 0.0, offset.y)
+    }
+
+    pub fn contains(&self, pixel: Vec2) -> bool {
+        pixel.x >= 0.0 && pixel.y >= 0.0 && pixel.x <= self.size && pixel.y <= self.size
+    }
+}
+
+#[derive(Component)]
+pub struct MinimapUi;
+
+/// A dot standing in for `source`, moved to its position every frame and removed with it.
+#[derive(Component)]
+pub struct MinimapDot {
+    pub source: Entity,
+}
+
+/// The dots tracing every lane, rebuilt whenever the `Paths` change.
+#[derive(Component)]
+pub struct MinimapPathDot;
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Minimap>()
+            .add_system_set(SystemSet::on_enter(GameState::Next).with_system(spawn_minimap))
+            .add_system_set(
+                SystemSet::on_update(GameState::Next)
+                    .with_system(draw_minimap_path)
+                    .with_system(sync_minimap_dots)
+                    .with_system(minimap_click),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Next).with_system(despawn_minimap));
+    }
+}
+
+fn enemy_color(enemy_type: EnemyType) -> Color {
+    match enemy_type {
+        EnemyType::Normal => Color::rgb(0.9, 0.2, 0.2),
+        EnemyType::Fast => Color::rgb(1.0, 0.6, 0.1),
+        EnemyType::Tank => Color::rgb(0.6, 0.1, 0.1),
+        EnemyType::Boss => Color::rgb(1.0, 0.1, 0.8),
+        EnemyType::Splitter => Color::rgb(0.4, 0.9, 0.3),
+        EnemyType::Flyer => Color::rgb(0.5, 0.9, 1.0),
+        EnemyType::Healer => Color::rgb(0.2, 1.0, 0.5),
+        EnemyType::Phantom => Color::rgb(0.7, 0.7, 1.0),
+    }
+}
+
+fn dot_bundle(minimap: &Minimap, position: Vec3, size: f32, color: Color) -> NodeBundle {
+    NodeBundle {
+        style: dot_style(minimap, position, size),
+        color: color.into(),
+        ..default()
+    }
+}
+
+fn dot_style(minimap: &Minimap, position: Vec3, size: f32) -> Style {
+    let pixel = minimap.world_to_minimap(position);
+
+    Style {
+        size: Size::new(Val::Px(size), Val::Px(size)),
+        position_type: PositionType::Absolute,
+        position: UiRect {
+            left: Val::Px(pixel.x - size / 2.0),
+            top: Val::Px(pixel.y - size / 2.0),
+            ..default()
+        },
+        display: if minimap.contains(pixel) {
+            Display::Flex
+        } else {
+            Display::None
+        },
+        ..default()
+    }
+}
+
+/// The minimap is a button so clicks on it pan the camera and never reach the world underneath.
+fn spawn_minimap(mut commands: Commands, minimap: Res<Minimap>) {
+    commands
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(minimap.size), Val::Px(minimap.size)),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(MINIMAP_TOP),
+                    right: Val::Px(MINIMAP_RIGHT),
+                    ..default()
+                },
+                ..default()
+            },
+            color: Color::rgba(0.05, 0.1, 0.05, 0.8).into(),
+            ..default()
+        })
+        .insert(MinimapUi)
+        .insert(Name::new("Minimap"));
+}
+
+fn draw_minimap_path(
+    mut commands: Commands,
+    roots: Query<Entity, With<MinimapUi>>,
+    new_roots: Query<(), Added<MinimapUi>>,
+    path_dots: Query<Entity, With<MinimapPathDot>>,
+    minimap: Res<Minimap>,
+    paths: Res<Paths>,
+) {
+    if !paths.is_changed() && !minimap.is_changed() && new_roots.is_empty() {
+        return;
+    }
+
+    for dot in &path_dots {
+        commands.entity(dot).despawn_recursive();
+    }
+
+    for root in &roots {
+        commands.entity(root).with_children(|commands| {
+            for path in paths.iter() {
+                draw_path(commands, &minimap, path);
+            }
+        });
+    }
+}
+
+fn draw_path(commands: &mut ChildBuilder, minimap: &Minimap, path: &Path) {
+    for segment in path.waypoints.windows(2) {
+        let steps = (Vec3::distance(segment[0], segment[1]) / PATH_DOT_SPACING).ceil();
+
+        for step in 0..steps as u32 {
+            let position = segment[0].lerp(segment[1], step as f32 / steps);
+            commands
+                .spawn_bundle(dot_bundle(minimap, position, 3.0, Color::GRAY))
+                .insert(MinimapPathDot);
+        }
+    }
+
+    commands
+        .spawn_bundle(dot_bundle(
+            minimap,
+            path.start(),
+            8.0,
+            Color::rgb(0.6, 0.2, 0.9),
+        ))
+        .insert(MinimapPathDot);
+    commands
+        .spawn_bundle(dot_bundle(
+            minimap,
+            path.end(),
+            8.0,
+            Color::rgb(0.2, 0.4, 0.9),
+        ))
+        .insert(MinimapPathDot);
+}
+
+/// Gives every tower and enemy a dot, moves the dots to follow them, and drops the dots of
+/// anything that has gone.
+fn sync_minimap_dots(
+    mut commands: Commands,
+    roots: Query<Entity, With<MinimapUi>>,
+    mut dots: Query<(Entity, &MinimapDot, &mut Style)>,
+    towers: Query<(Entity, &GlobalTransform), With<Tower>>,
+    targets: Query<(Entity, &GlobalTransform, &EnemyType), With<Target>>,
+    minimap: Res<Minimap>,
+) {
+    let root = match roots.iter().next() {
+        Some(root) => root,
+        None => return,
+    };
+
+    let sources: HashMap<Entity, (Vec3, f32, Color)> = towers
+        .iter()
+        .map(|(entity, transform)| (entity, (transform.translation(), 6.0, Color::WHITE)))
+        .chain(targets.iter().map(|(entity, transform, enemy_type)| {
+            (
+                entity,
+                (transform.translation(), 5.0, enemy_color(*enemy_type)),
+            )
+        }))
+        .collect();
+
+    let mut drawn = HashMap::default();
+
+    for (dot, source, mut style) in &mut dots {
+        match sources.get(&source.source) {
+            Some((position, size, _)) => {
+                *style = dot_style(&minimap, *position, *size);
+                drawn.insert(source.source, dot);
+            }
+            None => commands.entity(dot).despawn_recursive(),
+        }
+    }
+
+    for (source, (position, size, color)) in sources {
+        if drawn.contains_key(&source) {
+            continue;
+        }
+
+        let dot = commands
+            .spawn_bundle(dot_bundle(minimap, position, size, color))
+            .insert(MinimapDot { source })
+            .id();
+        commands.entity(root).add_child(dot);
+    }
+}
+
+/// Pans the camera to wherever the minimap is clicked. The minimap's corner is fixed, so the
+/// click is measured from that rather than from the node's layout.
+fn minimap_click(
+    roots: Query<&Interaction, (With<MinimapUi>, Changed<Interaction>)>,
+    mut cameras: Query<&mut CameraController, (With<GameCamera>, Without<CameraFocus>)>,
+    windows: Res<Windows>,
+    minimap: Res<Minimap>,
+) {
+    if !roots
+        .iter()
+        .any(|interaction| *interaction == Interaction::Clicked)
+    {
+        return;
+    }
+
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+
+    let cursor = match window.cursor_position() {
+        Some(cursor) => cursor,
+        None => return,
+    };
+
+    // The cursor is measured from the bottom of the window, UI from the top.
+    let pixel = Vec2::new(
+        cursor.x - (window.width() - MINIMAP_RIGHT - minimap.size),
+        window.height() - cursor.y - MINIMAP_TOP,
+    );
+
+    if !minimap.contains(pixel) {
+        return;
+    }
+
+    let target = minimap.minimap_to_world(pixel);
+    for mut controller in &mut cameras {
+        controller.focus = Vec3::new(target.x, controller.focus.y, target.z);
+    }
+}
+
+fn despawn_minimap(mut commands: Commands, roots: Query<Entity, With<MinimapUi>>) {
+    for entity in &roots {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_goal_lands_on_its_minimap_pixel() {
+        let minimap = Minimap {
+            world_center: Vec2::ZERO,
+            world_half_extent: 10.0,
+            size: 200.0,
+        };
+        let path = Path::new(
+            "Straight",
+            vec![Vec3::new(-8.0, 0.0, 0.0), Vec3::new(5.0, 0.0, -5.0)],
+        );
+
+        // Right of centre and toward the top, ten pixels to the world unit.
+        let goal = minimap.world_to_minimap(path.end());
+        assert_eq!(goal, Vec2::new(150.0, 50.0));
+        assert!(minimap.contains(goal));
+        assert_eq!(minimap.minimap_to_world(goal), path.end());
+    }
+}
@@ -0,0 +1,173 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// A short-lived quad at the end of a tower's barrel, shown whenever it fires. It always faces
+/// the camera and shrinks away over its `Lifetime` through `FadeOut`.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct MuzzleFlash;
+
+struct MuzzleFlashAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    lifetime: f32,
+}
+
+/// Flashes are purely visual, so this is only added when there is something to draw them on.
+/// `DisplaySettings::muzzle_flash` turns them off for slower machines.
+pub struct MuzzleFlashPlugin;
+
+impl Plugin for MuzzleFlashPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<MuzzleFlash>()
+            .add_startup_system(setup_muzzle_flash_assets)
+            .add_system_set(
+                SystemSet::on_update(GameState::Next)
+                    .with_system(spawn_muzzle_flashes)
+                    .with_system(face_camera),
+            );
+    }
+}
+
+fn setup_muzzle_flash_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(MuzzleFlashAssets {
+        mesh: meshes.add(Mesh::from(shape::Quad::new(Vec2::splat(0.35)))),
+        // Unlit and over-bright so it reads as glowing without a real additive blend mode.
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgba(1.0, 0.85, 0.4, 0.9),
+            emissive: Color::rgb(1.0, 0.7, 0.2),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+        lifetime: 0.08,
+    });
+}
+
+/// The flash is parented to the tower so it goes wherever the tower does. Towers aren't rotated
+/// or scaled, so the shot's offset from the tower works as the local position.
+fn spawn_muzzle_flashes(
+    mut commands: Commands,
+    mut fired_events: EventReader<TowerFired>,
+    towers: Query<&GlobalTransform, With<Tower>>,
+    flash_assets: Res<MuzzleFlashAssets>,
+    settings: Res<DisplaySettings>,
+) {
+    for fired in fired_events.iter() {
+        if !settings.muzzle_flash {
+            continue;
+        }
+
+        let tower = match towers.get(fired.tower) {
+            Ok(tower) => tower,
+            Err(_) => continue,
+        };
+
+        let flash = commands
+            .spawn_bundle(PbrBundle {
+                mesh: flash_assets.mesh.clone(),
+                material: flash_assets.material.clone(),
+                transform: Transform::from_translation(fired.position - tower.translation()),
+                ..default()
+            })
+            .insert(MuzzleFlash)
+            .insert(Lifetime {
+                timer: Timer::from_seconds(flash_assets.lifetime, false),
+            })
+            .insert(FadeOut::new(flash_assets.lifetime))
+            .insert(Name::new("MuzzleFlash"))
+            .id();
+
+        commands.entity(fired.tower).add_child(flash);
+    }
+}
+
+fn face_camera(
+    mut flashes: Query<&mut Transform, With<MuzzleFlash>>,
+    cameras: Query<&GlobalTransform, With<GameCamera>>,
+) {
+    let camera = match cameras.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+
+    let (_, rotation, _) = camera.to_scale_rotation_translation();
+
+    for mut transform in &mut flashes {
+        transform.rotation = rotation;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fire_once(settings: DisplaySettings) -> App {
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+        let tower = Tower::new(TowerType::Cannon, &config, &GameAssets::headless());
+        let mut app = App::new();
+        app.insert_resource(MuzzleFlashAssets {
+            mesh: Handle::default(),
+            material: Handle::default(),
+            lifetime: 0.08,
+        })
+        .insert_resource(settings)
+        .add_event::<TowerFired>()
+        .add_system(spawn_muzzle_flashes);
+        let tower = app
+            .world
+            .spawn()
+            .insert(tower)
+            .insert(GlobalTransform::from_translation(Vec3::new(2.0, 0.75, 1.0)))
+            .id();
+
+        app.world
+            .resource_mut::<Events<TowerFired>>()
+            .send(TowerFired {
+                tower,
+                position: Vec3::new(2.0, 1.25, 1.0),
+            });
+        app.update();
+
+        app
+    }
+
+    #[test]
+    fn firing_flashes_briefly_at_the_muzzle() {
+        let mut app = fire_once(DisplaySettings::default());
+
+        let flashes: Vec<_> = app
+            .world
+            .query_filtered::<(&Lifetime, &Transform, &Parent), With<MuzzleFlash>>()
+            .iter(&app.world)
+            .map(|(lifetime, transform, parent)| {
+                (
+                    lifetime.timer.duration().as_secs_f32(),
+                    transform.translation,
+                    app.world.get::<Tower>(parent.get()).is_some(),
+                )
+            })
+            .collect();
+        assert_eq!(flashes.len(), 1);
+        let (lifetime, offset, on_tower) = flashes[0];
+        assert!((lifetime - 0.08).abs() < 1e-6);
+        assert_eq!(offset, Vec3::new(0.0, 0.5, 0.0));
+        assert!(on_tower);
+    }
+
+    #[test]
+    fn flashes_can_be_turned_off() {
+        let mut app = fire_once(DisplaySettings {
+            muzzle_flash: false,
+            ..default()
+        });
+
+        let mut flashes = app.world.query_filtered::<(), With<MuzzleFlash>>();
+        assert_eq!(flashes.iter(&app.world).count(), 0);
+    }
+}
@@ -0,0 +1,346 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// One pooled particle. Inactive particles stay spawned but hidden until `ParticleSystem`
+/// hands them out again.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct Particle {
+    pub velocity: Vec3,
+    pub gravity: f32,
+    pub age: f32,
+    pub lifetime: f32,
+    pub active: bool,
+}
+
+/// What a single `ParticleSystem::emit` sends out: how many particles, how long they last,
+/// how fast they fly and what color they start as.
+#[derive(Clone, Copy, Debug)]
+pub struct ParticleEmitter {
+    pub count: u32,
+    pub lifetime: f32,
+    pub speed: f32,
+    pub gravity: f32,
+    pub color: Color,
+}
+
+pub struct ParticleSettings {
+    pub hit: ParticleEmitter,
+    pub death: ParticleEmitter,
+}
+
+impl Default for ParticleSettings {
+    fn default() -> Self {
+        Self {
+            hit: ParticleEmitter {
+                count: 8,
+                lifetime: 0.5,
+                speed: 2.0,
+                gravity: 9.8,
+                color: Color::ORANGE,
+            },
+            death: ParticleEmitter {
+                count: 16,
+                lifetime: 0.7,
+                speed: 3.0,
+                gravity: 9.8,
+                color: Color::rgb(0.8, 0.1, 0.1),
+            },
+        }
+    }
+}
+
+/// A fixed number of particle entities shared by every effect, used round-robin. Once all of
+/// them exist, emitting more takes over the oldest rather than spawning new ones, so a busy
+/// fight can't grow the particle count without bound.
+pub struct ParticleSystem {
+    pub capacity: usize,
+    /// Particle entities in the order they're reused. Grows up to `capacity` as needed.
+    pub slots: Vec<Entity>,
+    /// The slot handed out next.
+    pub next: usize,
+    /// Bursts waiting to be given particles by `particle_update`.
+    pub pending: Vec<(Vec3, ParticleEmitter)>,
+}
+
+impl Default for ParticleSystem {
+    fn default() -> Self {
+        Self {
+            capacity: 256,
+            slots: Vec::new(),
+            next: 0,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl ParticleSystem {
+    /// Queues a burst of `emitter`'s particles at `position`. They appear once
+    /// `particle_update` next runs.
+    pub fn emit(&mut self, position: Vec3, emitter: ParticleEmitter) {
+        self.pending.push((position, emitter));
+    }
+
+    /// The slot to put the next particle in, and the entity already there if it has one.
+    pub fn take_slot(&mut self) -> (usize, Option<Entity>) {
+        let slot = self.next;
+        self.next = (self.next + 1) % self.capacity;
+        (slot, self.slots.get(slot).copied())
+    }
+}
+
+struct ParticleAssets {
+    mesh: Handle<Mesh>,
+}
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Particle>()
+            .init_resource::<ParticleSettings>()
+            .init_resource::<ParticleSystem>()
+            .add_startup_system(setup_particle_assets)
+            .add_system_set_to_stage(
+                GameStep,
+                SystemSet::new()
+                    .with_system(emit_hit_particles.after(BulletSystem::Collision))
+                    .with_system(emit_death_particles)
+                    .with_system(
+                        particle_update
+                            .after(emit_hit_particles)
+                            .after(emit_death_particles),
+                    ),
+            );
+    }
+}
+
+fn setup_particle_assets(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(ParticleAssets {
+        mesh: meshes.add(Mesh::from(shape::Cube { size: 0.05 })),
+    });
+}
+
+fn emit_hit_particles(
+    mut hit_events: EventReader<BulletHit>,
+    mut particles: ResMut<ParticleSystem>,
+    settings: Res<ParticleSettings>,
+) {
+    for hit in hit_events.iter() {
+        particles.emit(hit.position, settings.hit);
+    }
+}
+
+fn emit_death_particles(
+    mut death_events: EventReader<TargetDeath>,
+    mut particles: ResMut<ParticleSystem>,
+    settings: Res<ParticleSettings>,
+) {
+    for death in death_events.iter() {
+        particles.emit(death.position, settings.death);
+    }
+}
+
+/// Hands pending bursts their particles, then moves and fades every active one, hiding those
+/// that have run out of time. Each particle keeps its own material so it can fade on its own.
+///
+/// No more than `capacity` particles are handed out per run. A particle spawned this run isn't
+/// there to be reused until the commands are applied, so coming round to its slot again would
+/// spawn a second one and lose the first. Whatever doesn't fit is dropped.
+fn particle_update(
+    mut commands: Commands,
+    mut system: ResMut<ParticleSystem>,
+    mut particles: Query<(
+        &mut Particle,
+        &mut Transform,
+        &mut Visibility,
+        &Handle<StandardMaterial>,
+    )>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut rng: ResMut<GameRng>,
+    particle_assets: Res<ParticleAssets>,
+    time: Res<GameTime>,
+) {
+    let mut free = system.capacity;
+
+    for (position, emitter) in std::mem::take(&mut system.pending) {
+        let count = (emitter.count as usize).min(free);
+        free -= count;
+
+        for _ in 0..count {
+            let direction = Vec3::new(
+                rng.range(-0.5, 0.5),
+                rng.range(0.0, 1.0),
+                rng.range(-0.5, 0.5),
+            )
+            .normalize_or_zero();
+
+            let particle = Particle {
+                velocity: direction * emitter.speed,
+                gravity: emitter.gravity,
+                age: 0.0,
+                lifetime: emitter.lifetime,
+                active: true,
+            };
+
+            let (slot, entity) = system.take_slot();
+            let existing = entity.and_then(|entity| particles.get_mut(entity).ok());
+
+            match existing {
+                Some((mut pooled, mut transform, mut visibility, material)) => {
+                    *pooled = particle;
+                    transform.translation = position;
+                    visibility.is_visible = true;
+
+                    if let Some(material) = materials.get_mut(material) {
+                        material.base_color = emitter.color;
+                    }
+                }
+                None => {
+                    let entity = commands
+                        .spawn_bundle(PbrBundle {
+                            mesh: particle_assets.mesh.clone(),
+                            material: materials.add(StandardMaterial {
+                                base_color: emitter.color,
+                                alpha_mode: AlphaMode::Blend,
+                                unlit: true,
+                                ..default()
+                            }),
+                            transform: Transform::from_translation(position),
+                            ..default()
+                        })
+                        .insert(particle)
+                        .insert(Gameplay)
+                        .insert(Name::new("Particle"))
+                        .id();
+
+                    // Fills a slot for the first time, or replaces a particle that has
+                    // been despawned out from under the pool.
+                    if slot < system.slots.len() {
+                        system.slots[slot] = entity;
+                    } else {
+                        system.slots.push(entity);
+                    }
+                }
+            }
+        }
+    }
+
+    for (mut particle, mut transform, mut visibility, material) in &mut particles {
+        if !particle.active {
+            continue;
+        }
+
+        particle.age += time.delta_seconds();
+
+        if particle.age >= particle.lifetime {
+            particle.active = false;
+            visibility.is_visible = false;
+            continue;
+        }
+
+        particle.velocity.y -= particle.gravity * time.delta_seconds();
+        transform.translation += particle.velocity * time.delta_seconds();
+
+        if let Some(material) = materials.get_mut(material) {
+            material
+                .base_color
+                .set_a(1.0 - particle.age / particle.lifetime);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::AssetPlugin;
+    use std::time::Duration;
+
+    fn app(capacity: usize) -> App {
+        let mut app = App::new();
+
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Mesh>()
+            .add_asset::<StandardMaterial>()
+            .insert_resource(ParticleAssets {
+                mesh: Handle::default(),
+            })
+            .insert_resource(ParticleSystem {
+                capacity,
+                ..default()
+            })
+            .insert_resource(GameRng::new(0))
+            .insert_resource(GameTime::stepped(Duration::from_secs_f32(1.0 / 60.0)))
+            .add_system(particle_update);
+
+        app
+    }
+
+    fn burst(app: &mut App, count: u32) {
+        app.world.resource_mut::<ParticleSystem>().emit(
+            Vec3::ZERO,
+            ParticleEmitter {
+                count,
+                ..ParticleSettings::default().hit
+            },
+        );
+    }
+
+    fn particle_count(app: &mut App) -> usize {
+        app.world.query::<&Particle>().iter(&app.world).count()
+    }
+
+    #[test]
+    fn pool_never_spawns_past_capacity_in_one_go() {
+        let mut app = app(4);
+
+        burst(&mut app, 3);
+        burst(&mut app, 3);
+        app.update();
+
+        assert_eq!(particle_count(&mut app), 4);
+        assert_eq!(app.world.resource::<ParticleSystem>().slots.len(), 4);
+    }
+
+    #[test]
+    fn later_bursts_reuse_the_pooled_particles() {
+        let mut app = app(4);
+
+        burst(&mut app, 4);
+        app.update();
+        let slots = app.world.resource::<ParticleSystem>().slots.clone();
+
+        burst(&mut app, 4);
+        app.update();
+
+        assert_eq!(particle_count(&mut app), 4);
+        assert_eq!(app.world.resource::<ParticleSystem>().slots, slots);
+    }
+
+    fn active_particles(app: &mut App) -> usize {
+        app.world
+            .query::<(&Particle, &Visibility)>()
+            .iter(&app.world)
+            .filter(|(particle, visibility)| particle.active && visibility.is_visible)
+            .count()
+    }
+
+    #[test]
+    fn particles_go_once_their_lifetime_is_up() {
+        let mut app = app(8);
+
+        burst(&mut app, 4);
+        app.update();
+        app.update();
+        assert_eq!(active_particles(&mut app), 4);
+
+        // Hit particles last half a second, thirty steps.
+        for _ in 0..30 {
+            app.update();
+        }
+
+        assert_eq!(active_particles(&mut app), 0);
+    }
+}
@@ -0,0 +1,563 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+const WAYPOINT_THRESHOLD: f32 = 0.05;
+
+pub struct Path {
+    pub name: String,
+    pub waypoints: Vec<Vec3>,
+}
+
+impl Path {
+    pub fn new(name: &str, waypoints: Vec<Vec3>) -> Self {
+        Self {
+            name: name.to_string(),
+            waypoints,
+        }
+    }
+
+    pub fn start(&self) -> Vec3 {
+        self.waypoints.first().copied().unwrap_or_default()
+    }
+
+    pub fn end(&self) -> Vec3 {
+        self.waypoints.last().copied().unwrap_or_default()
+    }
+
+    /// How far a follower at `position` still has to walk to reach the goal, round the rest of
+    /// the path's corners.
+    pub fn remaining_distance(&self, follower: &PathFollower, position: Vec3) -> f32 {
+        let mut remaining = 0.0;
+        let mut from = position;
+
+        for waypoint in self.waypoints.iter().skip(follower.index) {
+            remaining += Vec3::distance(from, *waypoint);
+            from = *waypoint;
+        }
+
+        remaining
+    }
+
+    /// How fast and which way a follower at `position` is currently moving, ignoring slows.
+    pub fn velocity(&self, follower: &PathFollower, position: Vec3) -> Vec3 {
+        match self.waypoints.get(follower.index) {
+            Some(waypoint) => (*waypoint - position).normalize_or_zero() * follower.speed,
+            None => Vec3::ZERO,
+        }
+    }
+
+    /// Where a follower at `position` will be after `time` seconds if it keeps its speed,
+    /// following the path round its corners.
+    pub fn predict(&self, follower: &PathFollower, position: Vec3, time: f32) -> Vec3 {
+        let mut position = position;
+        let mut remaining = follower.speed * time;
+
+        for waypoint in self.waypoints.iter().skip(follower.index) {
+            let distance = Vec3::distance(position, *waypoint);
+
+            if distance >= remaining {
+                return position + (*waypoint - position).normalize_or_zero() * remaining;
+            }
+
+            remaining -= distance;
+            position = *waypoint;
+        }
+
+        position
+    }
+}
+
+/// Every route enemies can take, one per lane. There is always at least one.
+pub struct Paths {
+    pub routes: Vec<Path>,
+}
+
+impl Paths {
+    pub fn new(routes: Vec<Path>) -> Self {
+        assert!(!routes.is_empty(), "a map needs at least one path");
+        Self { routes }
+    }
+
+    /// The route at `index`, falling back to the first for an index out of range.
+    pub fn get(&self, index: usize) -> &Path {
+        self.routes.get(index).unwrap_or(&self.routes[0])
+    }
+
+    pub fn for_follower(&self, follower: &PathFollower) -> &Path {
+        self.get(follower.path)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Path> {
+        self.routes.iter()
+    }
+}
+
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct PathFollower {
+    /// Which of the `Paths` this follower is on.
+    pub path: usize,
+    pub index: usize,
+    pub speed: f32,
+    /// Distance covered along the path so far.
+    pub progress: f32,
+}
+
+/// Turns a target to face the way it's moving, at up to `turn_speed`. Targets without it keep
+/// whatever rotation they spawned with.
+#[derive(Reflect, Component)]
+#[reflect(Component)]
+pub struct FaceMovement {
+    pub turn_speed: f32,
+}
+
+impl Default for FaceMovement {
+    fn default() -> Self {
+        Self { turn_speed: 8.0 }
+    }
+}
+
+impl FaceMovement {
+    /// The rotation that points a target's model along `direction`, ignoring any climb. Enemy
+    /// models face +Z.
+    pub fn facing(direction: Vec3) -> Option<Quat> {
+        let flat = Vec3::new(direction.x, 0.0, direction.z);
+
+        if flat.length_squared() <= f32::EPSILON {
+            return None;
+        }
+
+        Some(Quat::from_rotation_y(f32::atan2(flat.x, flat.z)))
+    }
+}
+
+/// Speeds a follower up by `rate` units per second, every second, until it reaches
+/// `max_speed`. Slows scale whatever speed it has built up.
+#[derive(Reflect, Component, Clone, Copy, Default, Debug)]
+#[reflect(Component)]
+pub struct Acceleration {
+    pub rate: f32,
+    pub max_speed: f32,
+}
+
+impl Acceleration {
+    /// `speed` after speeding up for `seconds`. Never slows down something already over the cap.
+    pub fn accelerate(&self, speed: f32, seconds: f32) -> f32 {
+        if speed >= self.max_speed {
+            return speed;
+        }
+
+        (speed + self.rate * seconds).min(self.max_speed)
+    }
+}
+
+pub struct TargetLeak {
+    pub entity: Entity,
+    /// The path whose goal the target reached.
+    pub path: usize,
+}
+
+pub struct PathPlugin;
+
+impl Plugin for PathPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PathFollower>()
+            .register_type::<FaceMovement>()
+            .register_type::<Acceleration>()
+            .add_event::<TargetLeak>()
+            .add_system_to_stage(GameStep, move_targets);
+    }
+}
+
+fn move_targets(
+    mut commands: Commands,
+    mut targets: Query<
+        (
+            Entity,
+            &mut PathFollower,
+            &mut Transform,
+            Option<&Slow>,
+            Option<&Boss>,
+            Option<&Flying>,
+            Option<&FaceMovement>,
+            Option<&Acceleration>,
+            Option<&Lured>,
+            Option<&mut Retreating>,
+            Option<&Stun>,
+        ),
+        With<Target>,
+    >,
+    mut leak_events: EventWriter<TargetLeak>,
+    mut lives: ResMut<Lives>,
+    paths: Res<Paths>,
+    time: Res<GameTime>,
+) {
+    for (
+        entity,
+        mut follower,
+        mut transform,
+        slow,
+        boss,
+        flying,
+        face,
+        acceleration,
+        lured,
+        retreating,
+        stun,
+    ) in &mut targets
+    {
+        let path = paths.for_follower(&follower);
+
+        // Flyers skip the waypoints and make straight for the goal.
+        let waypoint = match (flying, path.waypoints.get(follower.index)) {
+            (Some(flying), Some(_)) => {
+                follower.index = path.waypoints.len() - 1;
+                path.end() + Vec3::Y * flying.altitude
+            }
+            (None, Some(waypoint)) => *waypoint,
+            (_, None) => continue,
+        };
+
+        // Lured enemies make for the decoy instead, keeping their waypoint so they walk back to
+        // the path from wherever they end up once it's gone.
+        // Retreating enemies walk back toward the waypoint they last passed.
+        let waypoint = match (lured, &retreating) {
+            (Some(lured), _) => {
+                Vec3::new(lured.position.x, transform.translation.y, lured.position.z)
+            }
+            (None, Some(_)) => path.waypoints[follower.index.saturating_sub(1)],
+            (None, None) => waypoint,
+        };
+
+        if let Some(acceleration) = acceleration {
+            follower.speed = acceleration.accelerate(follower.speed, time.delta_seconds());
+        }
+
+        let speed = follower.speed
+            * slow.map(|slow| slow.factor).unwrap_or(1.0)
+            * boss.map(Boss::speed_factor).unwrap_or(1.0)
+            * stun.map_or(1.0, |_| 0.0);
+        let step = speed * time.delta_seconds();
+        let to_waypoint = waypoint - transform.translation;
+        let distance = to_waypoint.length();
+
+        // Turning is smoothed, so corners are rounded off over a few frames rather than snapped.
+        if let Some(face) = face {
+            if let Some(facing) = FaceMovement::facing(to_waypoint) {
+                let t = (face.turn_speed * time.delta_seconds()).min(1.0);
+                transform.rotation = transform.rotation.slerp(facing, t);
+            }
+        }
+
+        if lured.is_some() {
+            transform.translation += to_waypoint.clamp_length_max(step);
+            continue;
+        }
+
+        if let Some(mut retreating) = retreating {
+            let step = step.min(retreating.remaining);
+            retreating.remaining -= step;
+            follower.progress = (follower.progress - distance.min(step)).max(0.0);
+
+            if distance <= step.max(WAYPOINT_THRESHOLD) {
+                transform.translation = waypoint;
+
+                // Back at the spawn there's nowhere further to go.
+                if follower.index > 1 {
+                    follower.index -= 1;
+                } else {
+                    retreating.remaining = 0.0;
+                }
+            } else {
+                transform.translation += to_waypoint / distance * step;
+            }
+
+            if retreating.remaining <= 0.0 {
+                commands.entity(entity).remove::<Retreating>();
+            }
+            continue;
+        }
+
+        if distance <= step.max(WAYPOINT_THRESHOLD) {
+            follower.progress += distance;
+            transform.translation = waypoint;
+            follower.index += 1;
+
+            if follower.index >= path.waypoints.len() {
+                lives.count = lives.count.saturating_sub(1);
+                leak_events.send(TargetLeak {
+                    entity,
+                    path: follower.path,
+                });
+                commands.entity(entity).despawn_recursive();
+            }
+        } else {
+            follower.progress += step;
+            transform.translation += to_waypoint / distance * step;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const STEP: f32 = 0.1;
+
+    fn app(routes: Vec<Path>) -> App {
+        let mut app = App::new();
+
+        app.insert_resource(Paths::new(routes))
+            .insert_resource(GameTime::stepped(Duration::from_secs_f32(STEP)))
+            .init_resource::<Lives>()
+            .add_event::<TargetLeak>()
+            .add_system(move_targets);
+
+        app
+    }
+
+    fn spawn_follower(app: &mut App, path: usize, speed: f32) -> Entity {
+        let start = app.world.resource::<Paths>().get(path).start();
+
+        app.world
+            .spawn()
+            .insert(Target { speed, reward: 1 })
+            .insert(PathFollower {
+                path,
+                index: 1,
+                speed,
+                progress: 0.0,
+            })
+            .insert(Transform::from_translation(start))
+            .id()
+    }
+
+    fn run(app: &mut App, steps: u32) {
+        for _ in 0..steps {
+            app.update();
+        }
+    }
+
+    #[test]
+    fn targets_follow_the_waypoints_round_corners() {
+        let mut app = app(vec![Path::new(
+            "L",
+            vec![
+                Vec3::ZERO,
+                Vec3::new(2.0, 0.0, 0.0),
+                Vec3::new(2.0, 0.0, 2.0),
+                Vec3::new(4.0, 0.0, 2.0),
+            ],
+        )]);
+        let target = spawn_follower(&mut app, 0, 1.0);
+
+        // Four units at one a second takes it past the corner to the second waypoint.
+        run(&mut app, 45);
+
+        let follower = app.world.get::<PathFollower>(target).unwrap();
+        let translation = app.world.get::<Transform>(target).unwrap().translation;
+        assert_eq!(follower.index, 3);
+        assert!((translation.z - 2.0).abs() < 1e-4);
+        assert!(translation.x > 2.0 && translation.x < 4.0);
+    }
+
+    fn straight() -> Path {
+        Path::new("Straight", vec![Vec3::ZERO, Vec3::new(100.0, 0.0, 0.0)])
+    }
+
+    fn progress(app: &App, target: Entity) -> f32 {
+        app.world.get::<PathFollower>(target).unwrap().progress
+    }
+
+    #[test]
+    fn slowed_targets_cover_less_ground() {
+        let mut app = app(vec![straight()]);
+        let slowed = spawn_follower(&mut app, 0, 1.0);
+        let unslowed = spawn_follower(&mut app, 0, 1.0);
+        app.world.entity_mut(slowed).insert(Slow::new(0.5));
+
+        run(&mut app, 10);
+
+        assert!((progress(&app, unslowed) - 1.0).abs() < 1e-4);
+        assert!((progress(&app, slowed) - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn enraged_bosses_cover_more_ground() {
+        let mut app = app(vec![straight()]);
+        let calm = spawn_follower(&mut app, 0, 1.0);
+        let enraged = spawn_follower(&mut app, 0, 1.0);
+        app.world.entity_mut(calm).insert(Boss::default());
+        app.world.entity_mut(enraged).insert(Boss {
+            enraged: true,
+            ..default()
+        });
+
+        run(&mut app, 10);
+
+        assert!((progress(&app, calm) - 1.0).abs() < 1e-4);
+        assert!((progress(&app, enraged) - 1.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn facing_targets_turn_to_face_the_way_they_move() {
+        let mut app = app(vec![straight()]);
+        let turning = spawn_follower(&mut app, 0, 1.0);
+        let spinning = spawn_follower(&mut app, 0, 1.0);
+        app.world
+            .entity_mut(turning)
+            .insert(FaceMovement::default());
+
+        run(&mut app, 20);
+
+        // Models face +Z, and the path runs along +X.
+        let forward = |target| app.world.get::<Transform>(target).unwrap().rotation * Vec3::Z;
+        assert!(forward(turning).dot(Vec3::X) > 0.999);
+        assert_eq!(forward(spinning), Vec3::Z);
+    }
+
+    #[test]
+    fn followers_keep_to_their_own_lane() {
+        let mut app = app(vec![
+            straight(),
+            Path::new(
+                "North",
+                vec![
+                    Vec3::new(0.0, 0.0, -5.0),
+                    Vec3::new(0.0, 0.0, -10.0),
+                    Vec3::new(5.0, 0.0, -10.0),
+                ],
+            ),
+        ]);
+        let east = spawn_follower(&mut app, 0, 1.0);
+        let north = spawn_follower(&mut app, 1, 1.0);
+
+        run(&mut app, 70);
+
+        let translation = |target| app.world.get::<Transform>(target).unwrap().translation;
+        assert!((translation(east) - Vec3::new(7.0, 0.0, 0.0)).length() < 1e-3);
+        // Round the corner and a couple of units along the top.
+        assert!((translation(north).z + 10.0).abs() < 1e-4);
+        assert!(translation(north).x > 1.5 && translation(north).x < 2.5);
+        assert_eq!(app.world.get::<PathFollower>(north).unwrap().index, 2);
+    }
+
+    #[test]
+    fn accelerating_targets_speed_up_to_their_cap_and_can_still_be_slowed() {
+        let mut app = app(vec![straight()]);
+        let fast = spawn_follower(&mut app, 0, 1.0);
+        let slowed = spawn_follower(&mut app, 0, 1.0);
+        let acceleration = Acceleration {
+            rate: 1.0,
+            max_speed: 2.0,
+        };
+        app.world.entity_mut(fast).insert(acceleration);
+        app.world
+            .entity_mut(slowed)
+            .insert(acceleration)
+            .insert(Slow::new(0.5));
+        let speed = |app: &App, target| app.world.get::<PathFollower>(target).unwrap().speed;
+
+        run(&mut app, 5);
+        assert!((speed(&app, fast) - 1.5).abs() < 1e-4);
+
+        run(&mut app, 20);
+        assert_eq!(speed(&app, fast), 2.0);
+        // The slow halves whatever speed has built up, without holding the build-up back.
+        assert_eq!(speed(&app, slowed), 2.0);
+        assert!((progress(&app, slowed) - progress(&app, fast) / 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn lured_targets_walk_to_the_decoy_then_back_to_their_path() {
+        let mut app = app(vec![straight()]);
+        let target = spawn_follower(&mut app, 0, 1.0);
+        run(&mut app, 10);
+
+        let decoy = app.world.spawn().id();
+        app.world.entity_mut(target).insert(Lured {
+            decoy,
+            position: Vec3::new(1.0, 0.0, 3.0),
+        });
+        run(&mut app, 20);
+
+        let translation = |app: &App| app.world.get::<Transform>(target).unwrap().translation;
+        let lured_to = translation(&app);
+        assert!((lured_to - Vec3::new(1.0, 0.0, 2.0)).length() < 1e-3);
+        // Being drawn off the path doesn't count as progress along it.
+        assert!((progress(&app, target) - 1.0).abs() < 1e-4);
+        assert_eq!(app.world.get::<PathFollower>(target).unwrap().index, 1);
+
+        // Once let go it walks back toward its waypoint from where it stands.
+        app.world.entity_mut(target).remove::<Lured>();
+        run(&mut app, 10);
+        assert!((translation(&app).distance(lured_to) - 1.0).abs() < 1e-3);
+        assert!(translation(&app).z < lured_to.z);
+    }
+
+    #[test]
+    fn retreating_targets_lose_ground_then_push_on() {
+        let mut app = app(vec![Path::new(
+            "Bend",
+            vec![
+                Vec3::ZERO,
+                Vec3::new(2.0, 0.0, 0.0),
+                Vec3::new(2.0, 0.0, 10.0),
+            ],
+        )]);
+        let target = spawn_follower(&mut app, 0, 1.0);
+        // Round the corner and about two units up the second leg.
+        run(&mut app, 40);
+        let before = progress(&app, target);
+
+        app.world
+            .entity_mut(target)
+            .insert(Retreating { remaining: 1.0 });
+        let mut lowest = before;
+        for _ in 0..30 {
+            app.update();
+            lowest = lowest.min(progress(&app, target));
+        }
+
+        assert!((lowest - (before - 1.0)).abs() < 0.05);
+        assert!(app.world.get::<Retreating>(target).is_none());
+        assert!(progress(&app, target) > lowest + 1.5);
+    }
+
+    #[test]
+    fn retreats_stop_at_the_spawn() {
+        let mut app = app(vec![straight()]);
+        let target = spawn_follower(&mut app, 0, 1.0);
+        run(&mut app, 10);
+
+        app.world
+            .entity_mut(target)
+            .insert(Retreating { remaining: 5.0 });
+        run(&mut app, 20);
+
+        assert!(progress(&app, target) < 1e-4);
+        assert_eq!(
+            app.world.get::<Transform>(target).unwrap().translation,
+            Vec3::ZERO
+        );
+        assert!(app.world.get::<Retreating>(target).is_none());
+    }
+
+    #[test]
+    fn stunned_targets_hold_still_until_it_wears_off() {
+        let mut app = app(vec![straight()]);
+        let target = spawn_follower(&mut app, 0, 1.0);
+        run(&mut app, 10);
+        let before = progress(&app, target);
+
+        app.world.entity_mut(target).insert(Stun::new(1.0));
+        run(&mut app, 10);
+        assert_eq!(progress(&app, target), before);
+
+        app.world.entity_mut(target).remove::<Stun>();
+        run(&mut app, 10);
+        assert!((progress(&app, target) - (before + 1.0)).abs() < 1e-4);
+    }
+}
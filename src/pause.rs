@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+#[derive(Component)]
+pub struct PauseMenuUi;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PauseMenuButton {
+    Resume,
+    Settings,
+    Restart,
+    QuitToMenu,
+}
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_pause)
+            .add_system_set(SystemSet::on_enter(GameState::Paused).with_system(spawn_pause_menu))
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(pause_menu_buttons))
+            .add_system_set(SystemSet::on_exit(GameState::Paused).with_system(despawn_pause_menu))
+            .add_system_set(
+                SystemSet::on_enter(GameState::Restarting)
+                    .with_system(cleanup_gameplay_entities)
+                    .with_system(reset_game_resources),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Restarting).with_system(finish_restart))
+            .add_system_set(
+                SystemSet::on_enter(GameState::MainMenu)
+                    .with_system(cleanup_gameplay_entities)
+                    .with_system(reset_game_resources),
+            );
+    }
+}
+
+/// Pushes `Paused` on top of `Next` rather than replacing it, so resuming doesn't re-run the
+/// `on_enter(GameState::Next)` scene setup. Gameplay timers only tick inside `Next` systems, so
+/// nothing accumulates while paused. While building, Escape cancels the build selection instead.
+fn toggle_pause(
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut state: ResMut<State<GameState>>,
+    mut build: ResMut<BuildSelection>,
+) {
+    if !bindings.is_action_just_pressed(&keyboard, Action::Pause) {
+        return;
+    }
+
+    if build.is_active() && *state.current() == GameState::Next {
+        build.tower_type = None;
+        return;
+    }
+
+    let result = match state.current().clone() {
+        GameState::Next => state.push(GameState::Paused),
+        GameState::Paused => state.pop(),
+        _ => return,
+    };
+
+    if let Err(error) = result {
+        warn!(?error, "Failed to toggle pause");
+    }
+}
+
+fn spawn_pause_menu(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            // Dims the paused game behind the menu.
+            color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+            ..default()
+        })
+        .insert(PauseMenuUi)
+        .insert(Name::new("PauseMenuUi"))
+        .with_children(|commands| {
+            commands.spawn_bundle(TextBundle::from_section(
+                "Paused",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 80.0,
+                    color: Color::WHITE,
+                },
+            ));
+            spawn_button(commands, &assets.font, "Resume", PauseMenuButton::Resume);
+            spawn_button(
+                commands,
+                &assets.font,
+                "Settings",
+                PauseMenuButton::Settings,
+            );
+            spawn_button(commands, &assets.font, "Restart", PauseMenuButton::Restart);
+            spawn_button(
+                commands,
+                &assets.font,
+                "Quit to Menu",
+                PauseMenuButton::QuitToMenu,
+            );
+        });
+}
+
+/// Restart and Quit to Menu replace the whole state stack. Both clean up the game on the way
+/// into their state, and Restart passes through `Restarting` so `Next` is entered afresh and
+/// re-runs the scene setup.
+fn pause_menu_buttons(
+    buttons: Query<(&Interaction, &PauseMenuButton), Changed<Interaction>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        let result = match button {
+            PauseMenuButton::Resume => state.pop(),
+            PauseMenuButton::Settings => state.push(GameState::Settings),
+            PauseMenuButton::Restart => state.replace(GameState::Restarting),
+            PauseMenuButton::QuitToMenu => state.replace(GameState::MainMenu),
+        };
+
+        if let Err(error) = result {
+            warn!(?error, ?button, "Failed to leave pause menu");
+        }
+    }
+}
+
+fn despawn_pause_menu(mut commands: Commands, ui: Query<Entity, With<PauseMenuUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn finish_restart(mut state: ResMut<State<GameState>>) {
+    if let Err(error) = state.set(GameState::Next) {
+        warn!(?error, "Failed to restart game");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::input::{keyboard::KeyboardInput, ButtonState};
+
+    #[derive(Default)]
+    struct Shots(usize);
+
+    fn count_shots(mut fired_events: EventReader<TowerFired>, mut shots: ResMut<Shots>) {
+        shots.0 += fired_events.iter().count();
+    }
+
+    fn tap(app: &mut App, key_code: KeyCode) {
+        for state in [ButtonState::Pressed, ButtonState::Released] {
+            app.world
+                .resource_mut::<Events<KeyboardInput>>()
+                .send(KeyboardInput {
+                    scan_code: 0,
+                    key_code: Some(key_code),
+                    state,
+                });
+            app.update();
+        }
+    }
+
+    fn state(app: &App) -> GameState {
+        app.world.resource::<State<GameState>>().current().clone()
+    }
+
+    #[test]
+    fn towers_hold_fire_while_paused() {
+        let mut app = headless_app(Headless {
+            step: 0.1,
+            ..default()
+        });
+        app.init_resource::<Shots>()
+            .add_system_to_stage(CoreStage::Last, count_shots);
+        app.update();
+
+        tap(&mut app, KeyCode::Escape);
+        assert_eq!(state(&app), GameState::Paused);
+
+        // Right next to the layout's cannon, which would otherwise fire within a second.
+        let target = app
+            .world
+            .spawn()
+            .insert(Target {
+                speed: 0.0,
+                reward: 1,
+            })
+            .insert(Health {
+                value: 100,
+                max: 100,
+            })
+            .insert(PathFollower::default())
+            .insert_bundle(SpatialBundle {
+                transform: Transform::from_xyz(-2.0, 0.0, 1.0),
+                ..default()
+            })
+            .id();
+
+        for _ in 0..50 {
+            app.update();
+        }
+
+        assert_eq!(app.world.resource::<Shots>().0, 0);
+        assert_eq!(app.world.get::<Health>(target).unwrap().value, 100);
+
+        tap(&mut app, KeyCode::Escape);
+        assert_eq!(state(&app), GameState::Next);
+
+        for _ in 0..50 {
+            app.update();
+        }
+
+        assert!(app.world.resource::<Shots>().0 > 0);
+    }
+
+    #[test]
+    fn resume_returns_to_the_game_and_closes_the_menu() {
+        let mut app = headless_app(Headless {
+            step: 0.1,
+            ..default()
+        });
+        app.update();
+
+        tap(&mut app, KeyCode::Escape);
+        let mut menus = app.world.query_filtered::<(), With<PauseMenuUi>>();
+        assert_eq!(menus.iter(&app.world).count(), 1);
+
+        let mut buttons = app.world.query::<(&PauseMenuButton, &mut Interaction)>();
+        for (button, mut interaction) in buttons.iter_mut(&mut app.world) {
+            if *button == PauseMenuButton::Resume {
+                *interaction = Interaction::Clicked;
+            }
+        }
+        app.update();
+        app.update();
+
+        assert_eq!(state(&app), GameState::Next);
+        assert_eq!(menus.iter(&app.world).count(), 0);
+    }
+
+    #[test]
+    fn a_remapped_pause_key_toggles_pause() {
+        let mut bindings = KeyBindings::default();
+        bindings.rebind(Action::Pause, KeyCode::P);
+        let mut app = App::new();
+        app.add_state(GameState::Next)
+            .insert_resource(bindings)
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<BuildSelection>()
+            .add_system(toggle_pause);
+        let press = |app: &mut App| {
+            let mut keyboard = app.world.resource_mut::<Input<KeyCode>>();
+            keyboard.release(KeyCode::P);
+            keyboard.clear();
+            keyboard.press(KeyCode::P);
+            app.update();
+            state(app)
+        };
+
+        assert_eq!(press(&mut app), GameState::Paused);
+        assert_eq!(press(&mut app), GameState::Next);
+    }
+}
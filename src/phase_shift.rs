@@ -0,0 +1,268 @@
+use bevy::{prelude::*, utils::HashMap};
+use std::time::Duration;
+
+use crate::*;
+
+/// Alternates an enemy between a `vulnerable` window and an `invuln` window, starting
+/// vulnerable. While invulnerable it carries `Invulnerable` and takes no damage from hits.
+#[derive(Component, Clone)]
+pub struct PhaseShift {
+    pub invuln: Timer,
+    pub vulnerable: Timer,
+}
+
+impl PhaseShift {
+    pub fn new(vulnerable: f32, invuln: f32) -> Self {
+        Self {
+            invuln: Timer::from_seconds(invuln, false),
+            vulnerable: Timer::from_seconds(vulnerable, false),
+        }
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.vulnerable.finished() && !self.invuln.finished()
+    }
+
+    /// Runs whichever window is open, starting the cycle over once the invulnerable one ends.
+    pub fn tick(&mut self, delta: Duration) {
+        if !self.vulnerable.finished() {
+            self.vulnerable.tick(delta);
+            return;
+        }
+
+        self.invuln.tick(delta);
+
+        if self.invuln.finished() {
+            self.vulnerable.reset();
+            self.invuln.reset();
+        }
+    }
+}
+
+/// Present while a `PhaseShift` enemy can't be hurt. Damage systems skip anything carrying it.
+#[derive(Component)]
+pub struct Invulnerable;
+
+pub struct PhaseShiftSettings {
+    /// Bullets fly straight through invulnerable enemies instead of being used up on them.
+    pub bullets_pass_through: bool,
+    /// How see-through invulnerable enemies are drawn.
+    pub alpha: f32,
+}
+
+impl Default for PhaseShiftSettings {
+    fn default() -> Self {
+        Self {
+            bullets_pass_through: false,
+            alpha: 0.35,
+        }
+    }
+}
+
+/// Translucent copies of the enemy materials, made the first time each one is needed. Every
+/// enemy of a type shares its scene's materials, so invulnerable ones are switched over to
+/// copies rather than having the shared materials changed under everyone.
+#[derive(Default)]
+struct PhasedMaterials {
+    translucent: HashMap<Handle<StandardMaterial>, Handle<StandardMaterial>>,
+    opaque: HashMap<Handle<StandardMaterial>, Handle<StandardMaterial>>,
+}
+
+impl PhasedMaterials {
+    fn translucent_for(
+        &mut self,
+        handle: &Handle<StandardMaterial>,
+        materials: &mut Assets<StandardMaterial>,
+        alpha: f32,
+    ) -> Option<Handle<StandardMaterial>> {
+        if let Some(translucent) = self.translucent.get(handle) {
+            return Some(translucent.clone());
+        }
+
+        let mut material = materials.get(handle)?.clone();
+        material.alpha_mode = AlphaMode::Blend;
+        material.base_color.set_a(alpha);
+
+        let translucent = materials.add(material);
+        self.translucent.insert(handle.clone(), translucent.clone());
+        self.opaque.insert(translucent.clone(), handle.clone());
+        Some(translucent)
+    }
+}
+
+pub struct PhaseShiftPlugin;
+
+impl Plugin for PhaseShiftPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PhaseShiftSettings>()
+            .init_resource::<PhasedMaterials>()
+            .add_system_to_stage(GameStep, phase_shift.before(BulletSystem::Collision));
+    }
+}
+
+fn phase_shift(
+    mut commands: Commands,
+    mut shifters: Query<(Entity, &mut PhaseShift, Option<&Invulnerable>)>,
+    children: Query<&Children>,
+    mut handles: Query<&mut Handle<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut phased: ResMut<PhasedMaterials>,
+    settings: Res<PhaseShiftSettings>,
+    time: Res<GameTime>,
+) {
+    for (entity, mut phase, invulnerable) in &mut shifters {
+        phase.tick(time.delta());
+
+        let shifted = phase.is_invulnerable();
+        if shifted == invulnerable.is_some() {
+            continue;
+        }
+
+        if shifted {
+            commands.entity(entity).insert(Invulnerable);
+        } else {
+            commands.entity(entity).remove::<Invulnerable>();
+        }
+
+        let mut stack = vec![entity];
+        while let Some(node) = stack.pop() {
+            if let Ok(node_children) = children.get(node) {
+                stack.extend(node_children.iter());
+            }
+
+            if let Ok(mut handle) = handles.get_mut(node) {
+                let swapped = if shifted {
+                    phased.translucent_for(&handle, &mut materials, settings.alpha)
+                } else {
+                    phased.opaque.get(&*handle).cloned()
+                };
+
+                if let Some(swapped) = swapped {
+                    *handle = swapped;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::asset::AssetPlugin;
+
+    #[test]
+    fn phase_shift_alternates_between_its_windows() {
+        let mut phase = PhaseShift::new(1.0, 0.5);
+        let step = Duration::from_secs_f32(0.5);
+
+        phase.tick(step);
+        assert!(!phase.is_invulnerable());
+
+        phase.tick(step);
+        assert!(phase.is_invulnerable());
+
+        // The invulnerable window closes and the cycle starts over, vulnerable again.
+        phase.tick(step);
+        assert!(!phase.is_invulnerable());
+        phase.tick(step);
+        assert!(!phase.is_invulnerable());
+        phase.tick(step);
+        assert!(phase.is_invulnerable());
+    }
+
+    fn app(settings: PhaseShiftSettings) -> App {
+        let mut app = App::new();
+
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin)
+            .add_asset::<StandardMaterial>()
+            .insert_resource(settings)
+            .init_resource::<PhasedMaterials>()
+            .init_resource::<BulletPool>()
+            .init_resource::<PoisonSettings>()
+            .insert_resource(GameRng::new(0))
+            .insert_resource(GameTime::stepped(Duration::from_secs_f32(0.5)))
+            .add_event::<BulletHit>()
+            .add_event::<DamageDealt>()
+            .add_system(phase_shift.before(BulletSystem::Collision))
+            .add_system(bullet_collision.label(BulletSystem::Collision));
+
+        app
+    }
+
+    fn spawn_phantom(app: &mut App) -> Entity {
+        app.world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .insert(Health { value: 10, max: 10 })
+            .insert(PhaseShift::new(1.0, 1.0))
+            .insert(GlobalTransform::default())
+            .id()
+    }
+
+    fn fire(app: &mut App) -> Entity {
+        app.world
+            .spawn()
+            .insert(Bullet {
+                damage: 3,
+                collision_radius: 0.3,
+                ..default()
+            })
+            .insert(GlobalTransform::default())
+            .id()
+    }
+
+    fn health(app: &App, target: Entity) -> i32 {
+        app.world.get::<Health>(target).unwrap().value
+    }
+
+    #[test]
+    fn invulnerable_enemies_use_up_bullets_without_taking_damage() {
+        let mut app = app(PhaseShiftSettings::default());
+        let phantom = spawn_phantom(&mut app);
+
+        // A second in, the vulnerable window has run out.
+        app.update();
+        app.update();
+        assert!(app.world.get::<Invulnerable>(phantom).is_some());
+
+        let blocked = fire(&mut app);
+        app.update();
+        assert_eq!(health(&app, phantom), 10);
+        assert!(app.world.get::<Pooled>(blocked).is_some());
+
+        app.update();
+        assert!(app.world.get::<Invulnerable>(phantom).is_none());
+
+        let landed = fire(&mut app);
+        app.update();
+        assert_eq!(health(&app, phantom), 7);
+        assert!(app.world.get::<Pooled>(landed).is_some());
+    }
+
+    #[test]
+    fn bullets_can_be_set_to_pass_through_invulnerable_enemies() {
+        let mut app = app(PhaseShiftSettings {
+            bullets_pass_through: true,
+            ..default()
+        });
+        let phantom = spawn_phantom(&mut app);
+
+        app.update();
+        app.update();
+
+        let bullet = fire(&mut app);
+        app.update();
+        assert_eq!(health(&app, phantom), 10);
+        assert!(app.world.get::<Pooled>(bullet).is_none());
+
+        // Still in flight when the enemy turns vulnerable again, so it lands then.
+        app.update();
+        app.update();
+        assert_eq!(health(&app, phantom), 7);
+        assert!(app.world.get::<Pooled>(bullet).is_some());
+    }
+}
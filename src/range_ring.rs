@@ -0,0 +1,208 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+
+use crate::*;
+
+const RING_SEGMENTS: u32 = 64;
+const RING_THICKNESS: f32 = 0.04;
+
+/// Shows a ring on the ground around a tower or the placement ghost at its firing range.
+#[derive(Component)]
+pub struct ShowRange;
+
+#[derive(Component)]
+pub struct RangeRing;
+
+struct RangeRingAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+pub struct RangeRingPlugin;
+
+impl Plugin for RangeRingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(setup_range_ring_assets)
+            .add_system_set(
+                SystemSet::on_update(GameState::Next)
+                    .with_system(show_selected_range)
+                    .with_system(range_ring.after(show_selected_range)),
+            );
+    }
+}
+
+/// A flat ring of radius 1 lying on the XZ plane, facing up.
+fn ring_mesh() -> Mesh {
+    let inner = 1.0 - RING_THICKNESS;
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+
+    for segment in 0..=RING_SEGMENTS {
+        let angle = segment as f32 / RING_SEGMENTS as f32 * std::f32::consts::TAU;
+        let (sin, cos) = angle.sin_cos();
+        positions.push([cos * inner, 0.0, sin * inner]);
+        positions.push([cos, 0.0, sin]);
+    }
+
+    for segment in 0..RING_SEGMENTS {
+        let start = segment * 2;
+        indices.extend_from_slice(&[start, start + 2, start + 1, start + 1, start + 2, start + 3]);
+    }
+
+    let count = positions.len();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; count]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0.0, 0.0]; count]);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+fn setup_range_ring_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(RangeRingAssets {
+        mesh: meshes.add(ring_mesh()),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.3, 0.8, 1.0, 0.5),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            double_sided: true,
+            cull_mode: None,
+            ..default()
+        }),
+    });
+}
+
+fn show_selected_range(
+    mut commands: Commands,
+    towers: Query<(Entity, Option<&ShowRange>), With<Tower>>,
+    selection: Res<Selection>,
+) {
+    for (entity, shown) in &towers {
+        let selected = selection.tower == Some(entity);
+
+        if selected && shown.is_none() {
+            commands.entity(entity).insert(ShowRange);
+        } else if !selected && shown.is_some() {
+            commands.entity(entity).remove::<ShowRange>();
+        }
+    }
+}
+
+/// Gives everything marked `ShowRange` a ring child sized to its current range, so upgrades
+/// grow the ring with the tower, and clears rings whose parent no longer wants one.
+fn range_ring(
+    mut commands: Commands,
+    owners: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            Option<&Tower>,
+            Option<&PlacementGhost>,
+            Option<&Children>,
+        ),
+        With<ShowRange>,
+    >,
+    mut rings: Query<(Entity, &Parent, &mut Transform), With<RangeRing>>,
+    ring_assets: Res<RangeRingAssets>,
+) {
+    for (ring, parent, _) in &rings {
+        if !owners.contains(parent.get()) {
+            commands.entity(ring).despawn_recursive();
+        }
+    }
+
+    for (owner, transform, tower, ghost, children) in &owners {
+        let range = match (tower, ghost) {
+            (Some(tower), _) => tower.range,
+            (None, Some(ghost)) => ghost.range,
+            (None, None) => continue,
+        };
+
+        // Owners sit above the ground, so drop the ring back down to it.
+        let height = transform.translation().y - 0.02;
+        let scale = Vec3::new(range, 1.0, range);
+
+        let existing = children.and_then(|children| {
+            children
+                .iter()
+                .find(|child| rings.contains(**child))
+                .copied()
+        });
+
+        match existing {
+            Some(ring) => {
+                if let Ok((_, _, mut ring_transform)) = rings.get_mut(ring) {
+                    ring_transform.translation = Vec3::NEG_Y * height;
+                    ring_transform.scale = scale;
+                }
+            }
+            None => {
+                let ring = commands
+                    .spawn_bundle(PbrBundle {
+                        mesh: ring_assets.mesh.clone(),
+                        material: ring_assets.material.clone(),
+                        transform: Transform::from_translation(Vec3::NEG_Y * height)
+                            .with_scale(scale),
+                        ..default()
+                    })
+                    .insert(RangeRing)
+                    .insert(Name::new("RangeRing"))
+                    .id();
+                commands.entity(owner).add_child(ring);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring_scales(app: &mut App) -> Vec<Vec3> {
+        app.world
+            .query_filtered::<&Transform, With<RangeRing>>()
+            .iter(&app.world)
+            .map(|transform| transform.scale)
+            .collect()
+    }
+
+    #[test]
+    fn upgrades_grow_the_range_ring() {
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+        let cannon = config.tower(TowerType::Cannon).clone();
+        let tower = Tower::new(TowerType::Cannon, &config, &GameAssets::headless());
+        let mut app = App::new();
+        app.insert_resource(RangeRingAssets {
+            mesh: Handle::default(),
+            material: Handle::default(),
+        })
+        .add_system(range_ring);
+        let tower = app
+            .world
+            .spawn()
+            .insert(tower)
+            .insert(ShowRange)
+            .insert(GlobalTransform::from_translation(Vec3::Y * 0.75))
+            .id();
+
+        app.update();
+        assert_eq!(
+            ring_scales(&mut app),
+            vec![Vec3::new(cannon.range, 1.0, cannon.range)]
+        );
+
+        let second = cannon.level(2).unwrap();
+        app.world.get_mut::<Tower>(tower).unwrap().upgrade(second);
+        app.update();
+        assert_eq!(
+            ring_scales(&mut app),
+            vec![Vec3::new(second.range, 1.0, second.range)]
+        );
+    }
+}
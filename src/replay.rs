@@ -0,0 +1,378 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{fs, time::Duration};
+
+use crate::*;
+
+const REPLAY_FILE: &str = "replay.ron";
+
+/// Something the player did that changes how a run plays out. Towers are named by their grid
+/// cell, since entities differ between runs.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ReplayInput {
+    PlaceTower { tower_type: TowerType, cell: IVec2 },
+    Upgrade { cell: IVec2 },
+    Sell { cell: IVec2 },
+    CycleTargeting { cell: IVec2 },
+    Undo,
+    StartWaveEarly,
+    SetSpeed { multiplier: f32 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct ReplayEvent {
+    pub frame: u64,
+    pub input: ReplayInput,
+}
+
+/// A recorded run: where it started from and every input, in order, with the frame it landed
+/// on.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct ReplayData {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub events: Vec<ReplayEvent>,
+}
+
+impl ReplayData {
+    pub fn load() -> Option<Self> {
+        let contents = match fs::read_to_string(REPLAY_FILE) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!(?error, "Failed to read replay");
+                return None;
+            }
+        };
+
+        match ron::from_str(&contents) {
+            Ok(data) => Some(data),
+            Err(error) => {
+                warn!(?error, "Failed to parse replay");
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!(?error, "Failed to serialize replay");
+                return;
+            }
+        };
+
+        if let Err(error) = fs::write(REPLAY_FILE, contents) {
+            warn!(?error, "Failed to save replay");
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ReplayMode {
+    Idle,
+    Recording(ReplayData),
+    /// Plays `data` back, with `next` the index of the first event not yet injected.
+    Playing {
+        data: ReplayData,
+        next: usize,
+    },
+}
+
+/// Records or plays back a run. While either is going, `GameTime` takes the same number of
+/// `step`s every frame rather than following the clock, so the same inputs on the same frames
+/// give the same run.
+///
+/// Inputs are recorded where they take effect rather than where they're made, so a recording
+/// doesn't depend on which frame a click happened to be read on. During playback the player's
+/// own inputs are ignored.
+pub struct Replay {
+    pub mode: ReplayMode,
+    /// Frames of `Next` so far in this recording or playback, counting from 1.
+    pub frame: u64,
+    pub step: f32,
+}
+
+impl Default for Replay {
+    fn default() -> Self {
+        Self {
+            mode: ReplayMode::Idle,
+            frame: 0,
+            step: 1.0 / 60.0,
+        }
+    }
+}
+
+impl Replay {
+    pub fn is_recording(&self) -> bool {
+        matches!(self.mode, ReplayMode::Recording(_))
+    }
+
+    pub fn is_playing(&self) -> bool {
+        matches!(self.mode, ReplayMode::Playing { .. })
+    }
+
+    /// Whether the player's own inputs should go through, which they don't during playback.
+    pub fn accepts_input(&self) -> bool {
+        !self.is_playing()
+    }
+
+    /// The fixed step `GameTime` should use, if a recording or playback is running.
+    pub fn fixed_step(&self) -> Option<Duration> {
+        match self.mode {
+            ReplayMode::Idle => None,
+            _ => Some(Duration::from_secs_f32(self.step)),
+        }
+    }
+
+    /// Starts a recording of a run beginning from `seed`. The run itself needs restarting so
+    /// the recording covers it from the first frame.
+    pub fn start_recording(&mut self, seed: u64, difficulty: Difficulty) {
+        self.frame = 0;
+        self.mode = ReplayMode::Recording(ReplayData {
+            seed,
+            difficulty,
+            events: Vec::new(),
+        });
+    }
+
+    pub fn stop_recording(&mut self) -> Option<ReplayData> {
+        match std::mem::replace(&mut self.mode, ReplayMode::Idle) {
+            ReplayMode::Recording(data) => Some(data),
+            mode => {
+                self.mode = mode;
+                None
+            }
+        }
+    }
+
+    pub fn start_playback(&mut self, data: ReplayData) {
+        self.frame = 0;
+        self.mode = ReplayMode::Playing { data, next: 0 };
+    }
+
+    /// Notes `input` against the current frame. Does nothing unless recording.
+    pub fn record(&mut self, input: ReplayInput) {
+        let frame = self.frame;
+
+        if let ReplayMode::Recording(data) = &mut self.mode {
+            data.events.push(ReplayEvent { frame, input });
+        }
+    }
+
+    /// The recorded inputs for the current frame, finishing playback once none are left.
+    pub fn due_inputs(&mut self) -> Vec<ReplayInput> {
+        let frame = self.frame;
+
+        let (inputs, finished) = match &mut self.mode {
+            ReplayMode::Playing { data, next } => {
+                let mut inputs = Vec::new();
+
+                while let Some(event) = data.events.get(*next) {
+                    if event.frame > frame {
+                        break;
+                    }
+
+                    inputs.push(event.input);
+                    *next += 1;
+                }
+
+                (inputs, *next >= data.events.len())
+            }
+            _ => return Vec::new(),
+        };
+
+        if finished {
+            info!(frame, "Replay finished");
+            self.mode = ReplayMode::Idle;
+        }
+
+        inputs
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AdvanceReplay;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Replay>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                advance_replay.label(AdvanceReplay).after(UpdateGameTime),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Next).with_system(replay_controls));
+    }
+}
+
+/// Counts the frame and sends any recorded inputs due on it. This runs before `Update`, so
+/// every system that acts on them sees them this frame, just as they were recorded. Speed
+/// changes land after this frame's `GameTime` has been worked out, matching a live toggle.
+fn advance_replay(
+    mut replay: ResMut<Replay>,
+    mut place_requests: EventWriter<PlaceTowerRequest>,
+    mut upgrade_requests: EventWriter<UpgradeRequest>,
+    mut sell_requests: EventWriter<SellRequest>,
+    mut targeting_requests: EventWriter<CycleTargetingRequest>,
+    mut undo_requests: EventWriter<UndoRequest>,
+    mut wave_requests: EventWriter<StartWaveEarly>,
+    mut speed: ResMut<GameSpeed>,
+    grid: Res<GridMap>,
+    state: Res<State<GameState>>,
+) {
+    if replay.mode == ReplayMode::Idle || *state.current() != GameState::Next {
+        return;
+    }
+
+    replay.frame += 1;
+
+    for input in replay.due_inputs() {
+        match input {
+            ReplayInput::PlaceTower { tower_type, cell } => {
+                place_requests.send(PlaceTowerRequest { tower_type, cell })
+            }
+            ReplayInput::Upgrade { cell } => {
+                if let Some(tower) = grid.occupant(cell) {
+                    upgrade_requests.send(UpgradeRequest { tower });
+                }
+            }
+            ReplayInput::Sell { cell } => {
+                if let Some(tower) = grid.occupant(cell) {
+                    sell_requests.send(SellRequest { tower });
+                }
+            }
+            ReplayInput::CycleTargeting { cell } => {
+                if let Some(tower) = grid.occupant(cell) {
+                    targeting_requests.send(CycleTargetingRequest { tower });
+                }
+            }
+            ReplayInput::Undo => undo_requests.send(UndoRequest),
+            ReplayInput::StartWaveEarly => wave_requests.send(StartWaveEarly),
+            ReplayInput::SetSpeed { multiplier } => speed.multiplier = multiplier,
+        }
+    }
+}
+
+/// Starting either a recording or a playback restarts the run, so both begin from the same
+/// fresh game. Stopping a recording writes it to `replay.ron`.
+fn replay_controls(
+    mut replay: ResMut<Replay>,
+    mut state: ResMut<State<GameState>>,
+    mut rng: ResMut<GameRng>,
+    mut difficulty: ResMut<DifficultySettings>,
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+) {
+    if bindings.is_action_just_pressed(&keyboard, Action::ToggleRecording) {
+        if let Some(data) = replay.stop_recording() {
+            info!(events = data.events.len(), "Saved replay");
+            data.save();
+            return;
+        }
+
+        if replay.is_playing() {
+            return;
+        }
+
+        replay.start_recording(rng.seed(), difficulty.difficulty);
+        info!("Recording replay");
+    } else if bindings.is_action_just_pressed(&keyboard, Action::PlayReplay) {
+        if replay.is_recording() {
+            return;
+        }
+
+        let data = match ReplayData::load() {
+            Some(data) => data,
+            None => return,
+        };
+
+        *rng = GameRng::new(data.seed);
+        if difficulty.difficulty != data.difficulty {
+            *difficulty = DifficultySettings::new(data.difficulty);
+        }
+        replay.start_playback(data);
+        info!("Playing replay");
+    } else {
+        return;
+    }
+
+    if let Err(error) = state.replace(GameState::Restarting) {
+        warn!(?error, "Failed to restart for replay");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Free on either map.
+    const CELL: IVec2 = IVec2::new(-1, -2);
+    const FRAMES: u32 = 600;
+
+    /// Everything a run's outcome is judged by, with enemy progress compared bit for bit.
+    fn snapshot(app: &mut App) -> (u32, u32, u64, usize, Vec<(u32, i32)>) {
+        let mut enemies: Vec<(u32, i32)> = app
+            .world
+            .query_filtered::<(&PathFollower, &Health), With<Target>>()
+            .iter(&app.world)
+            .map(|(follower, health)| (follower.progress.to_bits(), health.value))
+            .collect();
+        enemies.sort_unstable();
+
+        (
+            app.world.resource::<Currency>().gold,
+            app.world.resource::<Lives>().count,
+            app.world.resource::<Score>().value,
+            app.world.resource::<Wave>().index,
+            enemies,
+        )
+    }
+
+    #[test]
+    fn a_recorded_run_plays_back_the_same() {
+        let mut app = headless_app(Headless::default());
+        let seed = app.world.resource::<GameRng>().seed();
+        app.world
+            .resource_mut::<Replay>()
+            .start_recording(seed, Difficulty::default());
+
+        for frame in 1..=FRAMES {
+            match frame {
+                10 => {
+                    app.world
+                        .resource_mut::<Events<PlaceTowerRequest>>()
+                        .send(PlaceTowerRequest {
+                            tower_type: TowerType::Sniper,
+                            cell: CELL,
+                        })
+                }
+                20 => app
+                    .world
+                    .resource_mut::<Events<StartWaveEarly>>()
+                    .send(StartWaveEarly),
+                _ => {}
+            }
+            app.update();
+        }
+        let recorded = snapshot(&mut app);
+        let data = app
+            .world
+            .resource_mut::<Replay>()
+            .stop_recording()
+            .expect("the run was being recorded");
+        assert_eq!(data.events.len(), 2);
+        assert!(app.world.resource::<GridMap>().occupant(CELL).is_some());
+
+        let mut app = headless_app(Headless::default());
+        app.insert_resource(GameRng::new(data.seed));
+        app.world.resource_mut::<Replay>().start_playback(data);
+        for _ in 0..FRAMES {
+            app.update();
+        }
+
+        assert!(!recorded.4.is_empty(), "no enemies were out to compare");
+        assert_eq!(snapshot(&mut app), recorded);
+    }
+}
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// Sends an enemy walking back along its path for `distance` the first time a hit leaves it at
+/// or below `threshold` of its max health. Flyers never retreat.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Retreat {
+    pub threshold: f32,
+    pub distance: f32,
+}
+
+impl Retreat {
+    pub fn triggered_by(&self, health: &Health) -> bool {
+        health.value > 0 && health.value as f32 <= health.max as f32 * self.threshold
+    }
+}
+
+/// Put on an enemy while it walks back toward the waypoint it last passed. It goes no further
+/// back than the spawn, and heads forward again once `remaining` is used up.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Retreating {
+    pub remaining: f32,
+}
+
+pub struct RetreatPlugin;
+
+impl Plugin for RetreatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(GameStep, start_retreats.after(BulletSystem::Collision));
+    }
+}
+
+/// Swaps `Retreat` for `Retreating`, so each enemy only falls back the once.
+fn start_retreats(
+    mut commands: Commands,
+    targets: Query<(Entity, &Retreat, &Health), (With<Target>, Without<Flying>)>,
+) {
+    for (entity, retreat, health) in &targets {
+        if !retreat.triggered_by(health) {
+            continue;
+        }
+
+        commands
+            .entity(entity)
+            .remove::<Retreat>()
+            .insert(Retreating {
+                remaining: retreat.distance,
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_target(app: &mut App, health: i32) -> Entity {
+        app.world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .insert(Health {
+                value: health,
+                max: 10,
+            })
+            .insert(Retreat {
+                threshold: 0.5,
+                distance: 1.0,
+            })
+            .id()
+    }
+
+    #[test]
+    fn enemies_fall_back_once_they_drop_to_the_threshold() {
+        let mut app = App::new();
+        app.add_system(start_retreats);
+        let hurt = spawn_target(&mut app, 5);
+        let healthy = spawn_target(&mut app, 6);
+        let flyer = spawn_target(&mut app, 2);
+        app.world.entity_mut(flyer).insert(Flying { altitude: 2.0 });
+
+        app.update();
+
+        let retreating = app
+            .world
+            .get::<Retreating>(hurt)
+            .expect("hurt enemy didn't retreat");
+        assert_eq!(retreating.remaining, 1.0);
+        assert!(app.world.get::<Retreat>(hurt).is_none());
+        assert!(app.world.get::<Retreating>(healthy).is_none());
+        assert!(app.world.get::<Retreating>(flyer).is_none());
+    }
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::*;
+
+/// The one source of randomness for gameplay. Everything random should draw from here so a run
+/// can be reproduced from its seed.
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Starts the sequence over from the seed, so a restarted run plays out the same way.
+    pub fn reseed(&mut self) {
+        self.rng = StdRng::seed_from_u64(self.seed);
+    }
+
+    /// A value in `min..max`, or `min` if the range is empty.
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        if min >= max {
+            return min;
+        }
+
+        self.rng.gen_range(min..max)
+    }
+
+    /// True with probability `p`, clamped to 0..=1.
+    pub fn chance(&mut self, p: f32) -> bool {
+        self.rng.gen_bool(p.clamp(0.0, 1.0) as f64)
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::new(rand::random())
+    }
+}
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRng>()
+            .add_startup_system_to_stage(StartupStage::PostStartup, seed_rng);
+    }
+}
+
+/// Uses the seed from the config when there is one. Otherwise the random seed is logged so an
+/// interesting run can still be replayed by putting it in the config.
+fn seed_rng(mut rng: ResMut<GameRng>, config: Res<GameConfig>) {
+    if let Some(seed) = config.seed {
+        *rng = GameRng::new(seed);
+    }
+
+    info!(seed = rng.seed(), "Seeded game RNG");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draws(rng: &mut GameRng) -> Vec<f32> {
+        (0..8).map(|_| rng.range(0.0, 100.0)).collect()
+    }
+
+    #[test]
+    fn the_same_seed_draws_the_same_sequence() {
+        let mut first = GameRng::new(42);
+        let mut second = GameRng::new(42);
+        let sequence = draws(&mut first);
+
+        assert_eq!(sequence, draws(&mut second));
+        assert_ne!(sequence, draws(&mut GameRng::new(43)));
+
+        first.reseed();
+        assert_eq!(sequence, draws(&mut first));
+    }
+}
@@ -0,0 +1,264 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::*;
+
+const SAVE_FILE: &str = "savegame.ron";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TowerSave {
+    #[serde(default)]
+    pub tower_type: TowerType,
+    pub position: [f32; 3],
+    pub level: u8,
+}
+
+/// Everything needed to restore a run, kept separate from the ECS components themselves.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SaveData {
+    pub gold: u32,
+    pub lives: u32,
+    pub score: u64,
+    pub wave_index: usize,
+    pub towers: Vec<TowerSave>,
+}
+
+impl SaveData {
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    pub fn from_ron(contents: &str) -> Result<Self, ron::Error> {
+        ron::from_str(contents)
+    }
+}
+
+/// Replaces the running game with `data`. Quickload sends one once the save file is read.
+pub struct LoadGameRequest {
+    pub data: SaveData,
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LoadGameRequest>().add_system_set(
+            SystemSet::on_update(GameState::Next)
+                .with_system(save_game)
+                .with_system(load_game)
+                .with_system(restore_game.after(load_game)),
+        );
+    }
+}
+
+fn save_game(
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    towers: Query<(&Tower, &GlobalTransform)>,
+    grid: Res<GridMap>,
+    currency: Res<Currency>,
+    lives: Res<Lives>,
+    score: Res<Score>,
+    wave: Res<Wave>,
+) {
+    if !bindings.is_action_just_pressed(&keyboard, Action::QuickSave) {
+        return;
+    }
+
+    let data = SaveData {
+        gold: currency.gold,
+        lives: lives.count,
+        score: score.value,
+        wave_index: wave.index,
+        towers: towers
+            .iter()
+            .map(|(tower, transform)| TowerSave {
+                tower_type: tower.tower_type,
+                position: grid
+                    .cell_to_world(grid.world_to_cell(transform.translation()))
+                    .to_array(),
+                level: tower.level,
+            })
+            .collect(),
+    };
+
+    let contents = match data.to_ron() {
+        Ok(contents) => contents,
+        Err(error) => {
+            warn!(?error, "Failed to serialize save data");
+            return;
+        }
+    };
+
+    match fs::write(SAVE_FILE, contents) {
+        Ok(()) => info!("Game saved to {}", SAVE_FILE),
+        Err(error) => warn!(?error, "Failed to write save file"),
+    }
+}
+
+fn load_game(
+    mut requests: EventWriter<LoadGameRequest>,
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+) {
+    if !bindings.is_action_just_pressed(&keyboard, Action::QuickLoad) {
+        return;
+    }
+
+    match fs::read_to_string(SAVE_FILE)
+        .map_err(|error| error.to_string())
+        .and_then(|contents| SaveData::from_ron(&contents).map_err(|error| error.to_string()))
+    {
+        Ok(data) => requests.send(LoadGameRequest { data }),
+        Err(error) => warn!(%error, "Failed to load save file, ignoring"),
+    }
+}
+
+fn restore_game(
+    mut commands: Commands,
+    mut requests: EventReader<LoadGameRequest>,
+    entities: Query<Entity, Or<(With<Tower>, With<Target>, With<Bullet>, With<Ballistic>)>>,
+    mut currency: ResMut<Currency>,
+    mut lives: ResMut<Lives>,
+    mut score: ResMut<Score>,
+    mut wave: ResMut<Wave>,
+    mut selection: ResMut<Selection>,
+    mut pool: ResMut<BulletPool>,
+    mut grid: ResMut<GridMap>,
+    config: Res<GameConfig>,
+    assets: Res<GameAssets>,
+) {
+    // Only the last request counts, since each replaces everything before it.
+    let data = match requests.iter().last() {
+        Some(request) => &request.data,
+        None => return,
+    };
+
+    for entity in &entities {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    currency.gold = data.gold;
+    lives.count = data.lives;
+    score.value = data.score;
+    *wave = Wave {
+        index: data.wave_index,
+        ..default()
+    };
+    *selection = Selection::default();
+    *pool = BulletPool::default();
+    grid.clear_occupied();
+
+    for saved in &data.towers {
+        let mut tower = Tower::new(saved.tower_type, &config, &assets);
+
+        for level in 2..=saved.level {
+            if let Some(stats) = config.tower(saved.tower_type).level(level) {
+                tower.upgrade(stats);
+            }
+        }
+
+        spawn_tower(
+            &mut commands,
+            &assets,
+            &config,
+            Vec3::from_array(saved.position),
+            tower,
+        );
+    }
+
+    info!("Game loaded");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crafted() -> SaveData {
+        SaveData {
+            gold: 42,
+            lives: 7,
+            score: 300,
+            wave_index: 2,
+            towers: vec![
+                TowerSave {
+                    tower_type: TowerType::Sniper,
+                    position: [3.0, 0.0, -2.0],
+                    level: 2,
+                },
+                TowerSave {
+                    tower_type: TowerType::Cannon,
+                    position: [0.0, 0.0, 1.0],
+                    level: 1,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn saves_round_trip_through_ron() {
+        let data = crafted();
+        let contents = data.to_ron().unwrap();
+
+        assert_eq!(SaveData::from_ron(&contents).unwrap(), data);
+    }
+
+    #[test]
+    fn loading_a_save_restores_it_into_a_fresh_world() {
+        let data = SaveData::from_ron(&crafted().to_ron().unwrap()).unwrap();
+        let mut app = App::new();
+        app.insert_resource(GameConfig::parse(include_str!("../assets/config.ron")).unwrap())
+            .insert_resource(GameAssets::headless())
+            .init_resource::<Currency>()
+            .init_resource::<Lives>()
+            .init_resource::<Score>()
+            .init_resource::<Wave>()
+            .init_resource::<Selection>()
+            .init_resource::<BulletPool>()
+            .init_resource::<GridMap>()
+            .add_event::<LoadGameRequest>()
+            .add_system(restore_game);
+        let leftover = app
+            .world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .id();
+
+        app.world
+            .resource_mut::<Events<LoadGameRequest>>()
+            .send(LoadGameRequest { data });
+        app.update();
+
+        assert_eq!(app.world.resource::<Currency>().gold, 42);
+        assert_eq!(app.world.resource::<Lives>().count, 7);
+        assert_eq!(app.world.resource::<Score>().value, 300);
+        assert_eq!(app.world.resource::<Wave>().index, 2);
+        assert!(app.world.get_entity(leftover).is_none());
+
+        let grid = GridMap::default();
+        let mut towers: Vec<(TowerType, u8, IVec2)> = app
+            .world
+            .query::<(&Tower, &Transform)>()
+            .iter(&app.world)
+            .map(|(tower, transform)| {
+                (
+                    tower.tower_type,
+                    tower.level,
+                    grid.world_to_cell(transform.translation),
+                )
+            })
+            .collect();
+        towers.sort_by_key(|(_, level, _)| *level);
+        assert_eq!(
+            towers,
+            vec![
+                (TowerType::Cannon, 1, IVec2::new(0, 1)),
+                (TowerType::Sniper, 2, IVec2::new(3, -2)),
+            ]
+        );
+    }
+}
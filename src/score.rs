@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use std::fs;
+
+use crate::*;
+
+const HIGH_SCORE_FILE: &str = "high_score.txt";
+
+#[derive(Default)]
+pub struct Score {
+    pub value: u64,
+}
+
+pub struct HighScore {
+    pub value: u64,
+}
+
+impl HighScore {
+    pub fn load() -> Self {
+        let value = fs::read_to_string(HIGH_SCORE_FILE)
+            .ok()
+            .and_then(|contents| contents.trim().parse().ok())
+            .unwrap_or(0);
+
+        Self { value }
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = fs::write(HIGH_SCORE_FILE, self.value.to_string()) {
+            warn!(?error, "Failed to save high score");
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ScoreText;
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+            .insert_resource(HighScore::load())
+            .add_system_set(
+                SystemSet::on_enter(GameState::Next)
+                    .with_system(reset_score)
+                    .with_system(spawn_score_text),
+            )
+            .add_system_to_stage(GameStep, award_kill_score)
+            .add_system_set(SystemSet::on_update(GameState::Next).with_system(update_score_text))
+            .add_system_set(SystemSet::on_exit(GameState::Next).with_system(despawn_score_text))
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(save_high_score))
+            .add_system_set(SystemSet::on_enter(GameState::Victory).with_system(save_high_score));
+    }
+}
+
+fn reset_score(mut score: ResMut<Score>) {
+    score.value = 0;
+}
+
+fn award_kill_score(
+    mut death_events: EventReader<TargetDeath>,
+    mut score: ResMut<Score>,
+    mut high_score: ResMut<HighScore>,
+) {
+    for death in death_events.iter() {
+        score.value += u64::from(death.reward);
+    }
+
+    if score.value > high_score.value {
+        high_score.value = score.value;
+    }
+}
+
+fn save_high_score(high_score: Res<HighScore>) {
+    high_score.save();
+}
+
+fn spawn_score_text(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(ScoreText)
+        .insert(Name::new("ScoreText"));
+}
+
+fn update_score_text(
+    mut texts: Query<&mut Text, With<ScoreText>>,
+    new_texts: Query<(), Added<ScoreText>>,
+    score: Res<Score>,
+    high_score: Res<HighScore>,
+) {
+    if !score.is_changed() && !high_score.is_changed() && new_texts.is_empty() {
+        return;
+    }
+
+    for mut text in &mut texts {
+        text.sections[0].value = format!("Score: {}  Best: {}", score.value, high_score.value);
+    }
+}
+
+fn despawn_score_text(mut commands: Commands, texts: Query<Entity, With<ScoreText>>) {
+    for entity in &texts {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kills_add_their_reward_to_the_score() {
+        let mut app = App::new();
+        app.init_resource::<Score>()
+            .insert_resource(HighScore { value: 0 })
+            .add_event::<TargetDeath>()
+            .add_system(award_kill_score);
+        let entity = app.world.spawn().id();
+
+        for _ in 0..2 {
+            app.world
+                .resource_mut::<Events<TargetDeath>>()
+                .send(TargetDeath {
+                    entity,
+                    enemy_type: EnemyType::Normal,
+                    position: Vec3::ZERO,
+                    reward: 5,
+                });
+        }
+        app.update();
+
+        assert_eq!(app.world.resource::<Score>().value, 10);
+        assert_eq!(app.world.resource::<HighScore>().value, 10);
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+#[derive(Component)]
+pub struct SelectionPanel;
+
+#[derive(Component)]
+pub struct SelectionPanelText;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SelectionButton {
+    Upgrade,
+    Sell,
+}
+
+pub struct SelectionPanelPlugin;
+
+impl Plugin for SelectionPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Next).with_system(spawn_selection_panel))
+            .add_system_set(
+                SystemSet::on_update(GameState::Next)
+                    .with_system(update_selection_panel)
+                    .with_system(selection_buttons),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Next).with_system(despawn_selection_panel),
+            );
+    }
+}
+
+fn spawn_selection_panel(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                padding: UiRect::all(Val::Px(10.0)),
+                display: Display::None,
+                ..default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+            ..default()
+        })
+        .insert(SelectionPanel)
+        .insert(Name::new("SelectionPanel"))
+        .with_children(|commands| {
+            commands
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: assets.font.clone(),
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(SelectionPanelText);
+
+            spawn_button(commands, &assets.font, "Upgrade", SelectionButton::Upgrade);
+            spawn_button(commands, &assets.font, "Sell", SelectionButton::Sell);
+        });
+}
+
+/// Hides the panel when nothing is selected and otherwise keeps it in step with the selected
+/// tower's stats, writing the text only when it actually changes. Hiding goes through
+/// `Display::None` so the buttons can't be clicked while they're out of sight.
+fn update_selection_panel(
+    mut panels: Query<&mut Style, With<SelectionPanel>>,
+    mut texts: Query<&mut Text, With<SelectionPanelText>>,
+    towers: Query<&Tower>,
+    selection: Res<Selection>,
+    config: Res<GameConfig>,
+    refund: Res<SellRefund>,
+) {
+    let tower = selection.tower.and_then(|entity| towers.get(entity).ok());
+
+    let display = if tower.is_some() {
+        Display::Flex
+    } else {
+        Display::None
+    };
+
+    for mut style in &mut panels {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+
+    let tower = match tower {
+        Some(tower) => tower,
+        None => return,
+    };
+
+    let upgrade = match config.tower(tower.tower_type).level(tower.level + 1) {
+        Some(stats) => format!("{} gold", stats.cost),
+        None => "max level".to_string(),
+    };
+
+    let contents = format!(
+        "{:?} level {}\nDamage: {}\nRange: {:.1}\nTargeting: {:?}\nUpgrade: {}\nSell: {} gold",
+        tower.tower_type,
+        tower.level,
+        tower.damage,
+        tower.range,
+        tower.targeting,
+        upgrade,
+        refund.value(tower),
+    );
+
+    for mut text in &mut texts {
+        if text.sections[0].value != contents {
+            text.sections[0].value = contents.clone();
+        }
+    }
+}
+
+fn selection_buttons(
+    buttons: Query<(&Interaction, &SelectionButton), Changed<Interaction>>,
+    mut upgrade_requests: EventWriter<UpgradeRequest>,
+    mut sell_requests: EventWriter<SellRequest>,
+    selection: Res<Selection>,
+    replay: Res<Replay>,
+) {
+    if !replay.accepts_input() {
+        return;
+    }
+
+    let tower = match selection.tower {
+        Some(tower) => tower,
+        None => return,
+    };
+
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        match button {
+            SelectionButton::Upgrade => upgrade_requests.send(UpgradeRequest { tower }),
+            SelectionButton::Sell => sell_requests.send(SellRequest { tower }),
+        }
+    }
+}
+
+fn despawn_selection_panel(mut commands: Commands, panels: Query<Entity, With<SelectionPanel>>) {
+    for entity in &panels {
+        commands.entity(entity).despawn_recursive();
+    }
+}
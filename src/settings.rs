@@ -0,0 +1,418 @@
+use bevy::{prelude::*, window::PresentMode};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+use crate::*;
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+/// Resolutions the settings menu cycles through.
+pub const RESOLUTIONS: [(f32, f32); 4] = [
+    (1280.0, 720.0),
+    (1600.0, 900.0),
+    (1920.0, 1080.0),
+    (2560.0, 1440.0),
+];
+
+/// Sample counts the renderer supports.
+pub const MSAA_SAMPLES: [u32; 2] = [1, 4];
+
+/// Window and rendering options, saved to `settings.ron` whenever they change.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct DisplaySettings {
+    pub resolution: (f32, f32),
+    pub vsync: bool,
+    pub msaa_samples: u32,
+    #[serde(default = "default_on")]
+    pub muzzle_flash: bool,
+    #[serde(default = "default_on")]
+    pub screen_shake: bool,
+    /// Whether a spawning boss takes over the camera.
+    #[serde(default = "default_on")]
+    pub boss_focus: bool,
+}
+
+fn default_on() -> bool {
+    true
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            resolution: (1920.0, 1080.0),
+            vsync: true,
+            msaa_samples: 4,
+            muzzle_flash: true,
+            screen_shake: true,
+            boss_focus: true,
+        }
+    }
+}
+
+impl DisplaySettings {
+    /// Reads the saved settings, falling back to the defaults for a missing or unreadable file
+    /// and for any saved value the game doesn't offer.
+    pub fn load() -> Self {
+        let settings: Self = fs::read_to_string(SETTINGS_FILE)
+            .ok()
+            .and_then(|contents| ron::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        settings.validated()
+    }
+
+    pub fn save(&self) {
+        let contents = match ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => contents,
+            Err(error) => {
+                warn!(?error, "Failed to serialize settings");
+                return;
+            }
+        };
+
+        if let Err(error) = fs::write(SETTINGS_FILE, contents) {
+            warn!(?error, "Failed to save settings");
+        }
+    }
+
+    pub fn validated(self) -> Self {
+        let defaults = Self::default();
+
+        Self {
+            resolution: if RESOLUTIONS.contains(&self.resolution) {
+                self.resolution
+            } else {
+                defaults.resolution
+            },
+            vsync: self.vsync,
+            muzzle_flash: self.muzzle_flash,
+            screen_shake: self.screen_shake,
+            boss_focus: self.boss_focus,
+            msaa_samples: if MSAA_SAMPLES.contains(&self.msaa_samples) {
+                self.msaa_samples
+            } else {
+                defaults.msaa_samples
+            },
+        }
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+
+    pub fn msaa(&self) -> Msaa {
+        Msaa {
+            samples: self.msaa_samples,
+        }
+    }
+
+    pub fn next_resolution(&mut self) {
+        self.resolution = next_in(&RESOLUTIONS, self.resolution);
+    }
+
+    pub fn next_msaa(&mut self) {
+        self.msaa_samples = next_in(&MSAA_SAMPLES, self.msaa_samples);
+    }
+}
+
+/// The entry after `current`, wrapping round, or the first one if `current` isn't listed.
+fn next_in<T: Copy + PartialEq>(options: &[T], current: T) -> T {
+    let index = options
+        .iter()
+        .position(|option| *option == current)
+        .map_or(0, |index| (index + 1) % options.len());
+
+    options[index]
+}
+
+#[derive(Component)]
+pub struct SettingsMenuUi;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SettingsButton {
+    Resolution,
+    Vsync,
+    Msaa,
+    MuzzleFlash,
+    ScreenShake,
+    BossFocus,
+    Back,
+}
+
+impl SettingsButton {
+    fn label(&self, settings: &DisplaySettings) -> String {
+        match self {
+            Self::Resolution => {
+                let (width, height) = settings.resolution;
+                format!("{}x{}", width, height)
+            }
+            Self::Vsync => format!("VSync: {}", if settings.vsync { "On" } else { "Off" }),
+            Self::Msaa => format!("MSAA: {}x", settings.msaa_samples),
+            Self::MuzzleFlash => format!(
+                "Flashes: {}",
+                if settings.muzzle_flash { "On" } else { "Off" }
+            ),
+            Self::ScreenShake => format!(
+                "Shake: {}",
+                if settings.screen_shake { "On" } else { "Off" }
+            ),
+            Self::BossFocus => format!(
+                "Boss Camera: {}",
+                if settings.boss_focus { "On" } else { "Off" }
+            ),
+            Self::Back => "Back".to_string(),
+        }
+    }
+}
+
+/// The settings menu is pushed on top of whichever menu opened it, so Back pops straight back
+/// to the main menu or the pause menu.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(apply_display_settings)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Settings).with_system(spawn_settings_menu),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Settings)
+                    .with_system(settings_buttons)
+                    .with_system(update_settings_labels.after(settings_buttons)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Settings).with_system(despawn_settings_menu),
+            );
+    }
+}
+
+/// Pushes changed settings to the window and renderer and writes them to disk. The window is
+/// created from the loaded settings, so the first run only has to sync `Msaa`.
+fn apply_display_settings(
+    settings: Res<DisplaySettings>,
+    mut windows: ResMut<Windows>,
+    mut msaa: ResMut<Msaa>,
+    mut shake: ResMut<ScreenShake>,
+    mut focus: ResMut<CameraFocusSettings>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    *msaa = settings.msaa();
+    shake.enabled = settings.screen_shake;
+    focus.enabled = settings.boss_focus;
+
+    if settings.is_added() {
+        return;
+    }
+
+    if let Some(window) = windows.get_primary_mut() {
+        let (width, height) = settings.resolution;
+        window.set_resolution(width, height);
+        window.set_present_mode(settings.present_mode());
+    }
+
+    settings.save();
+}
+
+fn spawn_settings_menu(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    settings: Res<DisplaySettings>,
+    bindings: Res<KeyBindings>,
+) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            // Covers the menu underneath, which also stops its buttons catching clicks.
+            color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            ..default()
+        })
+        .insert(SettingsMenuUi)
+        .insert(Name::new("SettingsMenuUi"))
+        .with_children(|commands| {
+            commands.spawn_bundle(TextBundle::from_section(
+                "Settings",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 80.0,
+                    color: Color::WHITE,
+                },
+            ));
+
+            commands
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::FlexStart,
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    ..default()
+                })
+                .with_children(|commands| {
+                    commands
+                        .spawn_bundle(settings_column())
+                        .with_children(|commands| {
+                            for button in [
+                                SettingsButton::Resolution,
+                                SettingsButton::Vsync,
+                                SettingsButton::Msaa,
+                                SettingsButton::MuzzleFlash,
+                                SettingsButton::ScreenShake,
+                                SettingsButton::BossFocus,
+                                SettingsButton::Back,
+                            ] {
+                                spawn_button(
+                                    commands,
+                                    &assets.font,
+                                    &button.label(&settings),
+                                    button,
+                                );
+                            }
+                        });
+
+                    commands
+                        .spawn_bundle(settings_column())
+                        .with_children(|commands| {
+                            spawn_rebind_buttons(commands, &assets.font, &bindings);
+                        });
+                });
+        });
+}
+
+fn settings_column() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Center,
+            margin: UiRect::all(Val::Px(20.0)),
+            ..default()
+        },
+        color: Color::NONE.into(),
+        ..default()
+    }
+}
+
+fn settings_buttons(
+    buttons: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+    mut settings: ResMut<DisplaySettings>,
+    mut state: ResMut<State<GameState>>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        match button {
+            SettingsButton::Resolution => settings.next_resolution(),
+            SettingsButton::Vsync => settings.vsync = !settings.vsync,
+            SettingsButton::Msaa => settings.next_msaa(),
+            SettingsButton::MuzzleFlash => settings.muzzle_flash = !settings.muzzle_flash,
+            SettingsButton::ScreenShake => settings.screen_shake = !settings.screen_shake,
+            SettingsButton::BossFocus => settings.boss_focus = !settings.boss_focus,
+            SettingsButton::Back => {
+                if let Err(error) = state.pop() {
+                    warn!(?error, "Failed to close settings");
+                }
+            }
+        }
+    }
+}
+
+fn update_settings_labels(
+    buttons: Query<(&SettingsButton, &Children)>,
+    mut texts: Query<&mut Text>,
+    settings: Res<DisplaySettings>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (button, children) in &buttons {
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = button.label(&settings);
+            }
+        }
+    }
+}
+
+fn despawn_settings_menu(mut commands: Commands, ui: Query<Entity, With<SettingsMenuUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_saved_before_a_toggle_existed_default_it_on() {
+        let settings: DisplaySettings =
+            ron::from_str("(resolution: (1280.0, 720.0), vsync: false, msaa_samples: 1)").unwrap();
+
+        assert!(settings.muzzle_flash);
+        assert!(settings.screen_shake);
+        assert!(settings.boss_focus);
+    }
+
+    #[test]
+    fn boss_focus_survives_a_save_and_load() {
+        let settings = DisplaySettings {
+            boss_focus: false,
+            ..default()
+        };
+        let contents = ron::to_string(&settings).unwrap();
+        let loaded: DisplaySettings = ron::from_str(&contents).unwrap();
+
+        assert_eq!(loaded.validated(), settings);
+    }
+
+    #[test]
+    fn the_msaa_setting_is_pushed_to_the_renderer() {
+        let mut app = App::new();
+        app.insert_resource(DisplaySettings {
+            msaa_samples: 1,
+            ..default()
+        })
+        .insert_resource(Msaa { samples: 4 })
+        .init_resource::<Windows>()
+        .init_resource::<ScreenShake>()
+        .init_resource::<CameraFocusSettings>()
+        .add_system(apply_display_settings);
+
+        app.update();
+
+        assert_eq!(app.world.resource::<Msaa>().samples, 1);
+    }
+
+    #[test]
+    fn msaa_cycles_through_the_supported_sample_counts() {
+        let mut settings = DisplaySettings {
+            msaa_samples: 4,
+            ..default()
+        };
+
+        settings.next_msaa();
+        assert_eq!(settings.msaa().samples, 1);
+        settings.next_msaa();
+        assert_eq!(settings.msaa().samples, 4);
+
+        settings.msaa_samples = 3;
+        assert_eq!(settings.validated().msaa_samples, 4);
+    }
+}
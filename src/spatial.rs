@@ -0,0 +1,147 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::*;
+
+/// Buckets target positions into square cells so towers only look at enemies near them.
+pub struct SpatialGrid {
+    pub cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<(Entity, Vec3)>>,
+    flying: HashSet<Entity>,
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        Self::new(2.0)
+    }
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+            flying: HashSet::default(),
+        }
+    }
+
+    fn cell(&self, position: Vec3) -> (i32, i32) {
+        (
+            (position.x / self.cell_size).floor() as i32,
+            (position.z / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Empties every cell but keeps their allocations for the next rebuild.
+    pub fn clear(&mut self) {
+        for entries in self.cells.values_mut() {
+            entries.clear();
+        }
+        self.flying.clear();
+    }
+
+    pub fn insert(&mut self, entity: Entity, position: Vec3) {
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push((entity, position));
+    }
+
+    pub fn mark_flying(&mut self, entity: Entity) {
+        self.flying.insert(entity);
+    }
+
+    pub fn is_flying(&self, entity: Entity) -> bool {
+        self.flying.contains(&entity)
+    }
+
+    /// Whether a tower can shoot at `entity`. Only anti-air towers can hit `Flying` enemies.
+    pub fn is_targetable(&self, entity: Entity, can_target_air: bool) -> bool {
+        can_target_air || !self.is_flying(entity)
+    }
+
+    /// Every entry within `radius` of `center`, along with its position.
+    pub fn query_radius_positions(
+        &self,
+        center: Vec3,
+        radius: f32,
+    ) -> impl Iterator<Item = (Entity, Vec3)> + '_ {
+        let (min_x, min_z) = self.cell(center - Vec3::new(radius, 0.0, radius));
+        let (max_x, max_z) = self.cell(center + Vec3::new(radius, 0.0, radius));
+
+        (min_x..=max_x)
+            .flat_map(move |x| (min_z..=max_z).map(move |z| (x, z)))
+            .filter_map(move |cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .filter(move |(_, position)| Vec3::distance(*position, center) <= radius)
+    }
+
+    pub fn query_radius(&self, center: Vec3, radius: f32) -> impl Iterator<Item = Entity> + '_ {
+        self.query_radius_positions(center, radius)
+            .map(|(entity, _)| entity)
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RebuildSpatialGrid;
+
+pub struct SpatialPlugin;
+
+impl Plugin for SpatialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpatialGrid>()
+            .add_system_to_stage(GameStep, rebuild_spatial_grid.label(RebuildSpatialGrid));
+    }
+}
+
+fn rebuild_spatial_grid(
+    mut grid: ResMut<SpatialGrid>,
+    targets: Query<(Entity, &GlobalTransform, Option<&Flying>), With<Target>>,
+) {
+    grid.clear();
+
+    for (entity, transform, flying) in &targets {
+        grid.insert(entity, transform.translation());
+
+        if flying.is_some() {
+            grid.mark_flying(entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn radius_queries_only_return_entries_in_range() {
+        let mut grid = SpatialGrid::new(2.0);
+        let near = Entity::from_raw(0);
+        let edge = Entity::from_raw(1);
+        let far = Entity::from_raw(2);
+        let across_cells = Entity::from_raw(3);
+        grid.insert(near, Vec3::new(0.5, 0.0, 0.5));
+        grid.insert(edge, Vec3::new(3.0, 0.0, 0.0));
+        grid.insert(far, Vec3::new(3.1, 0.0, 0.0));
+        grid.insert(across_cells, Vec3::new(-2.5, 0.0, -1.0));
+
+        let mut found: Vec<Entity> = grid.query_radius(Vec3::ZERO, 3.0).collect();
+        found.sort();
+
+        assert_eq!(found, vec![near, edge, across_cells]);
+    }
+
+    #[test]
+    fn clearing_empties_the_grid() {
+        let mut grid = SpatialGrid::default();
+        let flyer = Entity::from_raw(0);
+        grid.insert(flyer, Vec3::ZERO);
+        grid.mark_flying(flyer);
+
+        grid.clear();
+
+        assert_eq!(grid.query_radius(Vec3::ZERO, 10.0).count(), 0);
+        assert!(!grid.is_flying(flyer));
+    }
+}
@@ -0,0 +1,469 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct Target {
+    pub speed: f32,
+    pub reward: u32,
+}
+
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct Health {
+    pub value: i32,
+    pub max: i32,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum EnemyType {
+    Normal,
+    Fast,
+    Tank,
+    Boss,
+    Splitter,
+    Flyer,
+    Healer,
+    Phantom,
+}
+
+impl EnemyType {
+    pub const ALL: [EnemyType; 8] = [
+        Self::Normal,
+        Self::Fast,
+        Self::Tank,
+        Self::Boss,
+        Self::Splitter,
+        Self::Flyer,
+        Self::Healer,
+        Self::Phantom,
+    ];
+}
+
+/// Flies over everything at `altitude`, straight from wherever it spawned to the goal. Only
+/// towers with `CanTargetAir` can shoot it.
+#[derive(Reflect, Component, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct Flying {
+    pub altitude: f32,
+}
+
+/// Breaks the enemy into `count` enemies of type `into` when it dies. The pieces pick up where
+/// it left off on the path and never split again themselves.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct SplitOnDeath {
+    pub into: EnemyType,
+    pub count: u32,
+}
+
+pub struct EnemyTypeStats {
+    pub speed: f32,
+    pub health: i32,
+    pub reward: u32,
+    pub scale: f32,
+    pub armor: i32,
+    pub resistances: Resistances,
+    pub split: Option<SplitOnDeath>,
+    pub flying: Option<Flying>,
+    pub healer: Option<Healer>,
+    pub shield: Option<Shield>,
+    pub face_movement: Option<FaceMovement>,
+    pub acceleration: Option<Acceleration>,
+    pub phase_shift: Option<PhaseShift>,
+    pub retreat: Option<Retreat>,
+    pub scene: Handle<Scene>,
+}
+
+pub struct EnemyStats {
+    pub types: HashMap<EnemyType, EnemyTypeStats>,
+}
+
+impl EnemyStats {
+    pub fn get(&self, enemy_type: EnemyType) -> &EnemyTypeStats {
+        &self.types[&enemy_type]
+    }
+}
+
+pub struct TargetDeath {
+    pub entity: Entity,
+    pub enemy_type: EnemyType,
+    pub position: Vec3,
+    pub reward: u32,
+}
+
+pub struct TargetPlugin;
+
+impl Plugin for TargetPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Target>()
+            .register_type::<Health>()
+            .register_type::<Flying>()
+            .add_event::<TargetDeath>()
+            .add_system_set(
+                SystemSet::on_exit(GameState::AssetLoading).with_system(setup_enemy_stats),
+            )
+            .add_system_to_stage(GameStep, target_death.after(BulletSystem::Collision));
+    }
+}
+
+pub fn setup_enemy_stats(mut commands: Commands, assets: Res<GameAssets>, config: Res<GameConfig>) {
+    let types = config
+        .enemies
+        .iter()
+        .map(|(enemy_type, enemy)| {
+            let stats = EnemyTypeStats {
+                speed: enemy.speed,
+                health: enemy.health,
+                reward: enemy.reward,
+                scale: enemy.scale,
+                armor: enemy.armor,
+                resistances: enemy.resistances,
+                split: enemy
+                    .split
+                    .map(|(into, count)| SplitOnDeath { into, count }),
+                flying: enemy.flying.map(|altitude| Flying { altitude }),
+                healer: enemy
+                    .heal
+                    .map(|(interval, radius, amount)| Healer::new(interval, radius, amount)),
+                shield: enemy
+                    .shield
+                    .map(|(amount, regen)| Shield::new(amount, regen)),
+                face_movement: enemy
+                    .turn_speed
+                    .map(|turn_speed| FaceMovement { turn_speed }),
+                acceleration: enemy
+                    .acceleration
+                    .map(|(rate, max_speed)| Acceleration { rate, max_speed }),
+                phase_shift: enemy
+                    .phase_shift
+                    .map(|(vulnerable, invuln)| PhaseShift::new(vulnerable, invuln)),
+                retreat: enemy.retreat.map(|(threshold, distance)| Retreat {
+                    threshold,
+                    distance,
+                }),
+                // Every archetype shares one model; `scale` is what tells them apart.
+                scene: assets.target_scene.clone(),
+            };
+
+            (*enemy_type, stats)
+        })
+        .collect();
+
+    commands.insert_resource(EnemyStats { types });
+}
+
+/// Multipliers applied on top of an enemy type's base stats when it spawns.
+#[derive(Clone, Copy, Debug)]
+pub struct EnemyScaling {
+    pub health: f32,
+    pub speed: f32,
+    pub reward: f32,
+}
+
+impl Default for EnemyScaling {
+    fn default() -> Self {
+        Self {
+            health: 1.0,
+            speed: 1.0,
+            reward: 1.0,
+        }
+    }
+}
+
+/// Spawns an enemy of the given type heading for `waypoint` on the `path`th of the `Paths`.
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    stats: &EnemyStats,
+    enemy_type: EnemyType,
+    position: Vec3,
+    path: usize,
+    waypoint: usize,
+    scaling: EnemyScaling,
+) -> Entity {
+    let type_stats = stats.get(enemy_type);
+    let health = (type_stats.health as f32 * scaling.health).round() as i32;
+    let speed = type_stats.speed * scaling.speed;
+    let reward = (type_stats.reward as f32 * scaling.reward).round() as u32;
+    let altitude = type_stats.flying.map_or(0.0, |flying| flying.altitude);
+
+    let mut entity = commands.spawn_bundle(SceneBundle {
+        scene: type_stats.scene.clone(),
+        transform: Transform::from_translation(position + Vec3::Y * altitude)
+            .with_rotation(Quat::from_rotation_y(eul_to_rad(90.0)))
+            .with_scale(Vec3::splat(type_stats.scale)),
+        ..default()
+    });
+
+    entity
+        .insert(Target { speed, reward })
+        .insert(PathFollower {
+            path,
+            index: waypoint,
+            speed,
+            progress: 0.0,
+        })
+        .insert(Health {
+            value: health,
+            max: health,
+        })
+        .insert(Armor {
+            value: type_stats.armor,
+        })
+        .insert(type_stats.resistances)
+        .insert(IncomingDamage::default())
+        .insert(Interpolated::new(position + Vec3::Y * altitude))
+        .insert(enemy_type)
+        .insert(Gameplay)
+        .insert(Name::new("Target"));
+
+    if let Some(split) = type_stats.split {
+        entity.insert(split);
+    }
+
+    if let Some(flying) = type_stats.flying {
+        entity.insert(flying);
+    }
+
+    if let Some(face_movement) = &type_stats.face_movement {
+        entity.insert(FaceMovement {
+            turn_speed: face_movement.turn_speed,
+        });
+    }
+
+    // Scaled like the starting speed, so harder difficulties also raise the cap.
+    if let Some(acceleration) = type_stats.acceleration {
+        entity.insert(Acceleration {
+            rate: acceleration.rate * scaling.speed,
+            max_speed: acceleration.max_speed * scaling.speed,
+        });
+    }
+
+    if let Some(shield) = &type_stats.shield {
+        entity.insert(shield.clone());
+    }
+
+    if let Some(phase_shift) = &type_stats.phase_shift {
+        entity.insert(phase_shift.clone());
+    }
+
+    if let Some(healer) = &type_stats.healer {
+        entity.insert(healer.clone());
+    }
+
+    if let Some(retreat) = type_stats.retreat {
+        entity.insert(retreat);
+    }
+
+    if enemy_type == EnemyType::Boss {
+        entity
+            .insert(Boss::default())
+            .insert(HealthBarOffset {
+                offset: Vec3::new(0.0, 1.2, 0.0),
+            })
+            .insert(HealthBarScale { scale: 2.0 })
+            .insert(Name::new("Boss"));
+    }
+
+    entity.id()
+}
+
+fn target_death(
+    mut commands: Commands,
+    targets: Query<(
+        Entity,
+        &Target,
+        &Health,
+        &Transform,
+        &PathFollower,
+        &EnemyType,
+        Option<&SplitOnDeath>,
+    )>,
+    mut death_events: EventWriter<TargetDeath>,
+    enemy_stats: Res<EnemyStats>,
+    wave_config: Res<WaveConfig>,
+    difficulty: Res<DifficultySettings>,
+    wave: Res<Wave>,
+) {
+    for (entity, target, health, transform, follower, enemy_type, split) in &targets {
+        if health.value <= 0 {
+            death_events.send(TargetDeath {
+                entity,
+                enemy_type: *enemy_type,
+                position: transform.translation,
+                reward: target.reward,
+            });
+            commands.entity(entity).despawn_recursive();
+
+            if let Some(split) = split {
+                let scaling = difficulty.scale(wave_config.scaling(wave.index));
+                let speed = enemy_stats.get(split.into).speed * scaling.speed;
+
+                for _ in 0..split.count {
+                    let piece = spawn_enemy(
+                        &mut commands,
+                        &enemy_stats,
+                        split.into,
+                        transform.translation,
+                        follower.path,
+                        follower.index,
+                        scaling,
+                    );
+
+                    commands
+                        .entity(piece)
+                        .remove::<SplitOnDeath>()
+                        .insert(PathFollower {
+                            path: follower.path,
+                            index: follower.index,
+                            speed,
+                            progress: follower.progress,
+                        });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+
+        app.insert_resource(GameConfig::parse(include_str!("../assets/config.ron")).unwrap())
+            .insert_resource(GameAssets::headless())
+            .insert_resource(WaveConfig {
+                waves: vec![],
+                health_scaling: 0.2,
+                reward_scaling: 0.1,
+            })
+            .init_resource::<DifficultySettings>()
+            .init_resource::<Wave>()
+            .add_event::<TargetDeath>()
+            .add_startup_system(setup_enemy_stats)
+            .add_system(target_death);
+
+        app
+    }
+
+    fn spawn_target(app: &mut App, enemy_type: EnemyType, health: i32) -> Entity {
+        app.world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 5,
+            })
+            .insert(Health {
+                value: health,
+                max: 3,
+            })
+            .insert(Transform::default())
+            .insert(PathFollower::default())
+            .insert(enemy_type)
+            .id()
+    }
+
+    #[test]
+    fn targets_at_zero_health_are_despawned() {
+        let mut app = app();
+        let dead = spawn_target(&mut app, EnemyType::Normal, 0);
+        let alive = spawn_target(&mut app, EnemyType::Normal, 1);
+
+        app.update();
+
+        assert!(app.world.get_entity(dead).is_none());
+        assert!(app.world.get_entity(alive).is_some());
+        assert_eq!(app.world.resource::<Events<TargetDeath>>().len(), 1);
+    }
+
+    fn spawn_one_of_each(mut commands: Commands, stats: Res<EnemyStats>, mut done: Local<bool>) {
+        if std::mem::replace(&mut *done, true) {
+            return;
+        }
+
+        for enemy_type in EnemyType::ALL {
+            spawn_enemy(
+                &mut commands,
+                &stats,
+                enemy_type,
+                Vec3::ZERO,
+                0,
+                1,
+                EnemyScaling::default(),
+            );
+        }
+    }
+
+    fn max_health(app: &mut App, wanted: EnemyType) -> i32 {
+        app.world
+            .query::<(&EnemyType, &Health)>()
+            .iter(&app.world)
+            .find(|(enemy_type, _)| **enemy_type == wanted)
+            .map(|(_, health)| health.max)
+            .unwrap()
+    }
+
+    #[test]
+    fn tanks_spawn_tougher_than_fast_enemies() {
+        let mut app = app();
+        app.add_system(spawn_one_of_each);
+
+        app.update();
+
+        assert!(max_health(&mut app, EnemyType::Tank) > max_health(&mut app, EnemyType::Fast));
+    }
+
+    #[test]
+    fn bosses_spawn_tougher_than_normal_enemies() {
+        let mut app = app();
+        app.add_system(spawn_one_of_each);
+
+        app.update();
+
+        assert!(max_health(&mut app, EnemyType::Boss) > max_health(&mut app, EnemyType::Normal));
+        let mut bosses = app.world.query_filtered::<&EnemyType, With<Boss>>();
+        assert_eq!(
+            bosses.iter(&app.world).collect::<Vec<_>>(),
+            vec![&EnemyType::Boss]
+        );
+    }
+
+    #[test]
+    fn splitters_break_into_pieces_where_they_died() {
+        let mut app = app();
+        let position = Vec3::new(2.0, 0.0, 3.0);
+        let splitter = spawn_target(&mut app, EnemyType::Splitter, 0);
+        app.world
+            .entity_mut(splitter)
+            .insert(Transform::from_translation(position))
+            .insert(PathFollower {
+                index: 2,
+                progress: 4.5,
+                ..default()
+            })
+            .insert(SplitOnDeath {
+                into: EnemyType::Fast,
+                count: 3,
+            });
+
+        app.update();
+
+        let pieces: Vec<_> = app
+            .world
+            .query::<(&EnemyType, &Transform, &PathFollower)>()
+            .iter(&app.world)
+            .map(|(enemy_type, transform, follower)| {
+                (
+                    *enemy_type,
+                    transform.translation,
+                    follower.index,
+                    follower.progress,
+                )
+            })
+            .collect();
+        assert_eq!(pieces, vec![(EnemyType::Fast, position, 2, 4.5); 3]);
+    }
+}
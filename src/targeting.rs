@@ -0,0 +1,433 @@
+use bevy::{prelude::*, utils::FloatOrd};
+
+use crate::*;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TargetingMode {
+    Closest,
+    /// Furthest along the path.
+    First,
+    /// Least far along the path.
+    Last,
+    Strongest,
+    Weakest,
+    /// Nearest the goal by what's left of its route, so flyers heading straight for it and
+    /// enemies that split partway along are ranked by how soon they'd actually leak.
+    DefendBase,
+}
+
+impl Default for TargetingMode {
+    fn default() -> Self {
+        Self::Closest
+    }
+}
+
+impl TargetingMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Closest => Self::First,
+            Self::First => Self::Last,
+            Self::Last => Self::Strongest,
+            Self::Strongest => Self::Weakest,
+            Self::Weakest => Self::DefendBase,
+            Self::DefendBase => Self::Closest,
+        }
+    }
+}
+
+/// Damage heading for an enemy in bullets that haven't landed yet, rebuilt every step from the
+/// bullets carrying an `AimedAt`.
+#[derive(Component, Default, Debug)]
+pub struct IncomingDamage {
+    pub amount: i32,
+}
+
+impl IncomingDamage {
+    /// Whether what's already on its way should finish off an enemy on `health`. Only raw
+    /// damage is counted, so armor or a shield can still leave it standing.
+    pub fn is_lethal(&self, health: &Health) -> bool {
+        self.amount > 0 && self.amount >= health.value
+    }
+}
+
+/// The enemy a bullet was fired at and the damage it counts toward that enemy's
+/// `IncomingDamage`. Taken off once the bullet hits something or goes back in the pool.
+#[derive(Component)]
+pub struct AimedAt {
+    pub target: Entity,
+    pub damage: i32,
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TrackIncomingDamage;
+
+/// Makes a tower aim its bullets where the target is heading rather than where it is now.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct PredictiveAim;
+
+/// The point where a bullet fired from `shooter` at `bullet_speed` meets a target at
+/// `target_position` moving at a constant `target_velocity`. Returns `None` when the bullet can
+/// never catch up.
+pub fn lead_target(
+    shooter: Vec3,
+    target_position: Vec3,
+    target_velocity: Vec3,
+    bullet_speed: f32,
+) -> Option<Vec3> {
+    let offset = target_position - shooter;
+
+    // Solve |offset + velocity * t| = bullet_speed * t for the earliest positive t.
+    let a = target_velocity.length_squared() - bullet_speed * bullet_speed;
+    let b = 2.0 * offset.dot(target_velocity);
+    let c = offset.length_squared();
+
+    let time = if a.abs() < f32::EPSILON {
+        if b.abs() < f32::EPSILON {
+            return None;
+        }
+        -c / b
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let root = discriminant.sqrt();
+        let (t1, t2) = ((-b - root) / (2.0 * a), (-b + root) / (2.0 * a));
+
+        match (t1 > 0.0, t2 > 0.0) {
+            (true, true) => t1.min(t2),
+            (true, false) => t1,
+            (false, true) => t2,
+            (false, false) => return None,
+        }
+    };
+
+    if time <= 0.0 {
+        return None;
+    }
+
+    Some(target_position + target_velocity * time)
+}
+
+/// Chooses a target within `range` of `position` according to `mode`, returning its entity and
+/// world position. Flying enemies are skipped unless `can_target_air` is set, as is anything
+/// `allow` turns down.
+///
+/// A `locked` target the tower already has is kept for as long as it's alive and could still be
+/// picked, and `mode` only chooses a new one once it's lost.
+pub fn select_target(
+    mode: TargetingMode,
+    locked: Option<Entity>,
+    grid: &SpatialGrid,
+    position: Vec3,
+    range: f32,
+    can_target_air: bool,
+    paths: &Paths,
+    targets: &Query<(&Health, &PathFollower), With<Target>>,
+    allow: impl Fn(Entity) -> bool,
+) -> Option<(Entity, Vec3)> {
+    let progress = |entity: Entity| {
+        targets
+            .get(entity)
+            .map(|(_, follower)| follower.progress)
+            .unwrap_or(0.0)
+    };
+    let health = |entity: Entity| {
+        targets
+            .get(entity)
+            .map(|(health, _)| health.value)
+            .unwrap_or(0)
+    };
+
+    let to_goal = |entity: Entity, position: Vec3| {
+        let (_, follower) = match targets.get(entity) {
+            Ok(target) => target,
+            Err(_) => return f32::MAX,
+        };
+        let path = paths.for_follower(follower);
+
+        if grid.is_flying(entity) {
+            return Vec3::distance(position * Vec3::new(1.0, 0.0, 1.0), path.end());
+        }
+
+        path.remaining_distance(follower, position)
+    };
+
+    if let Some(locked) = locked {
+        if let Some(kept) = grid
+            .query_radius_positions(position, range)
+            .find(|(entity, _)| *entity == locked)
+            .filter(|(entity, _)| grid.is_targetable(*entity, can_target_air) && allow(*entity))
+            .filter(|(entity, _)| health(*entity) > 0)
+        {
+            return Some(kept);
+        }
+    }
+
+    let mut candidates = grid
+        .query_radius_positions(position, range)
+        .filter(|(entity, _)| grid.is_targetable(*entity, can_target_air))
+        .filter(|(entity, _)| allow(*entity));
+
+    match mode {
+        TargetingMode::Closest => candidates
+            .min_by_key(|(_, translation)| FloatOrd(Vec3::distance(*translation, position))),
+        TargetingMode::First => candidates.max_by_key(|(entity, _)| FloatOrd(progress(*entity))),
+        TargetingMode::Last => candidates.min_by_key(|(entity, _)| FloatOrd(progress(*entity))),
+        TargetingMode::Strongest => candidates.max_by_key(|(entity, _)| health(*entity)),
+        TargetingMode::Weakest => candidates.min_by_key(|(entity, _)| health(*entity)),
+        TargetingMode::DefendBase => {
+            candidates.min_by_key(|(entity, position)| FloatOrd(to_goal(*entity, *position)))
+        }
+    }
+}
+
+pub struct TargetingPlugin;
+
+impl Plugin for TargetingPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PredictiveAim>()
+            .add_event::<CycleTargetingRequest>()
+            .add_system_to_stage(
+                GameStep,
+                track_incoming_damage
+                    .label(TrackIncomingDamage)
+                    .after(BulletSystem::Collision)
+                    .after(BulletSystem::Despawn),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Next)
+                    .with_system(targeting_hotkey)
+                    .with_system(cycle_targeting_mode.after(targeting_hotkey)),
+            );
+    }
+}
+
+/// Bullets released this step are already back in the pool's list even though they don't carry
+/// `Pooled` yet, so that's checked too.
+fn track_incoming_damage(
+    mut targets: Query<&mut IncomingDamage>,
+    bullets: Query<(Entity, &AimedAt), Without<Pooled>>,
+    pool: Res<BulletPool>,
+) {
+    for mut incoming in &mut targets {
+        incoming.amount = 0;
+    }
+
+    for (bullet, aimed) in &bullets {
+        if pool.inactive.contains(&bullet) {
+            continue;
+        }
+
+        if let Ok(mut incoming) = targets.get_mut(aimed.target) {
+            incoming.amount += aimed.damage;
+        }
+    }
+}
+
+fn targeting_hotkey(
+    mut requests: EventWriter<CycleTargetingRequest>,
+    selection: Res<Selection>,
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    replay: Res<Replay>,
+) {
+    if !replay.accepts_input()
+        || !bindings.is_action_just_pressed(&keyboard, Action::CycleTargeting)
+    {
+        return;
+    }
+
+    if let Some(tower) = selection.tower {
+        requests.send(CycleTargetingRequest { tower });
+    }
+}
+
+fn cycle_targeting_mode(
+    mut requests: EventReader<CycleTargetingRequest>,
+    mut towers: Query<(&mut Tower, &Transform)>,
+    mut replay: ResMut<Replay>,
+    grid: Res<GridMap>,
+) {
+    for request in requests.iter() {
+        if let Ok((mut tower, transform)) = towers.get_mut(request.tower) {
+            tower.targeting = tower.targeting.next();
+            tower.current_target = None;
+            replay.record(ReplayInput::CycleTargeting {
+                cell: grid.world_to_cell(transform.translation),
+            });
+            debug!(?tower.targeting, "Targeting mode changed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What `pick` passes to `select_target`, and the entity it got back.
+    #[derive(Default)]
+    struct Pick {
+        mode: TargetingMode,
+        locked: Option<Entity>,
+        can_target_air: bool,
+        picked: Option<Entity>,
+    }
+
+    const RANGE: f32 = 5.0;
+
+    fn pick(
+        mut pick: ResMut<Pick>,
+        targets: Query<(&Health, &PathFollower), With<Target>>,
+        grid: Res<SpatialGrid>,
+        paths: Res<Paths>,
+    ) {
+        pick.picked = select_target(
+            pick.mode,
+            pick.locked,
+            &grid,
+            Vec3::ZERO,
+            RANGE,
+            pick.can_target_air,
+            &paths,
+            &targets,
+            |_| true,
+        )
+        .map(|(entity, _)| entity);
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+
+        app.insert_resource(Paths::new(vec![Path::new(
+            "Straight",
+            vec![Vec3::new(-10.0, 0.0, 0.0), Vec3::new(10.0, 0.0, 0.0)],
+        )]))
+        .init_resource::<SpatialGrid>()
+        .init_resource::<Pick>()
+        .add_system(pick);
+
+        app
+    }
+
+    /// An enemy at `position` on the path, `progress` along it.
+    fn spawn_enemy_at(app: &mut App, position: Vec3, progress: f32, health: i32) -> Entity {
+        let enemy = app
+            .world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .insert(Health {
+                value: health,
+                max: health,
+            })
+            .insert(PathFollower {
+                path: 0,
+                index: 1,
+                speed: 1.0,
+                progress,
+            })
+            .id();
+        app.world
+            .resource_mut::<SpatialGrid>()
+            .insert(enemy, position);
+
+        enemy
+    }
+
+    fn picked(app: &mut App, mode: TargetingMode) -> Option<Entity> {
+        app.world.resource_mut::<Pick>().mode = mode;
+        app.update();
+        app.world.resource::<Pick>().picked
+    }
+
+    #[test]
+    fn each_mode_picks_its_own_target() {
+        let mut app = app();
+        let closest = spawn_enemy_at(&mut app, Vec3::new(1.0, 0.0, 0.0), 11.0, 2);
+        let first = spawn_enemy_at(&mut app, Vec3::new(3.0, 0.0, 0.0), 13.0, 9);
+        let last = spawn_enemy_at(&mut app, Vec3::new(-4.0, 0.0, 0.0), 6.0, 5);
+        let weakest = spawn_enemy_at(&mut app, Vec3::new(-2.0, 0.0, 1.0), 8.0, 1);
+        // Further along and tougher than all of them, but out of range.
+        spawn_enemy_at(&mut app, Vec3::new(8.0, 0.0, 0.0), 18.0, 20);
+
+        assert_eq!(picked(&mut app, TargetingMode::Closest), Some(closest));
+        assert_eq!(picked(&mut app, TargetingMode::First), Some(first));
+        assert_eq!(picked(&mut app, TargetingMode::Last), Some(last));
+        assert_eq!(picked(&mut app, TargetingMode::Strongest), Some(first));
+        assert_eq!(picked(&mut app, TargetingMode::Weakest), Some(weakest));
+        assert_eq!(picked(&mut app, TargetingMode::DefendBase), Some(first));
+    }
+
+    #[test]
+    fn defend_base_picks_the_enemy_nearest_the_goal() {
+        let mut app = app();
+        let nearby = spawn_enemy_at(&mut app, Vec3::new(-1.0, 0.0, 0.0), 9.0, 3);
+        let leaking = spawn_enemy_at(&mut app, Vec3::new(4.0, 0.0, 0.0), 14.0, 3);
+
+        assert_eq!(picked(&mut app, TargetingMode::Closest), Some(nearby));
+        assert_eq!(picked(&mut app, TargetingMode::DefendBase), Some(leaking));
+    }
+
+    #[test]
+    fn nothing_in_range_picks_nothing() {
+        let mut app = app();
+        spawn_enemy_at(&mut app, Vec3::new(8.0, 0.0, 0.0), 18.0, 20);
+
+        assert_eq!(picked(&mut app, TargetingMode::Closest), None);
+    }
+
+    #[test]
+    fn leading_aims_ahead_of_a_moving_target() {
+        let target = Vec3::new(0.0, 0.0, 10.0);
+        let velocity = Vec3::X;
+
+        let aim = lead_target(Vec3::ZERO, target, velocity, 5.0).unwrap();
+
+        assert!(aim.x > 0.0, "aimed at {aim}, not ahead of the target");
+        // The bullet and the target reach the aim point at the same moment.
+        let bullet_time = aim.length() / 5.0;
+        let target_time = aim.distance(target) / velocity.length();
+        assert!((bullet_time - target_time).abs() < 1e-4);
+
+        // A bullet slower than a target running straight away never catches it.
+        assert!(lead_target(Vec3::ZERO, target, Vec3::Z, 0.5).is_none());
+    }
+
+    #[test]
+    fn only_anti_air_towers_pick_flyers() {
+        let mut app = app();
+        let flyer = spawn_enemy_at(&mut app, Vec3::new(1.0, 2.0, 0.0), 11.0, 3);
+        app.world.resource_mut::<SpatialGrid>().mark_flying(flyer);
+        let walker = spawn_enemy_at(&mut app, Vec3::new(4.0, 0.0, 0.0), 6.0, 3);
+
+        assert_eq!(picked(&mut app, TargetingMode::Closest), Some(walker));
+
+        app.world.resource_mut::<Pick>().can_target_air = true;
+        assert_eq!(picked(&mut app, TargetingMode::Closest), Some(flyer));
+    }
+
+    #[test]
+    fn locked_targets_are_kept_while_in_range() {
+        let mut app = app();
+        let locked = spawn_enemy_at(&mut app, Vec3::new(2.0, 0.0, 0.0), 12.0, 3);
+        app.world.resource_mut::<Pick>().locked = Some(locked);
+        // A touch closer, which would win a fresh pick.
+        let closer = spawn_enemy_at(&mut app, Vec3::new(-1.9, 0.0, 0.0), 8.1, 3);
+
+        assert_eq!(picked(&mut app, TargetingMode::Closest), Some(locked));
+        assert_eq!(picked(&mut app, TargetingMode::Closest), Some(locked));
+
+        // Once it walks out of range the tower picks afresh.
+        let mut grid = app.world.resource_mut::<SpatialGrid>();
+        grid.clear();
+        grid.insert(locked, Vec3::new(6.0, 0.0, 0.0));
+        grid.insert(closer, Vec3::new(-1.9, 0.0, 0.0));
+        assert_eq!(picked(&mut app, TargetingMode::Closest), Some(closer));
+    }
+}
@@ -0,0 +1,308 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+const BUTTON_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const HOVERED_COLOR: Color = Color::rgb(0.25, 0.25, 0.25);
+const SELECTED_COLOR: Color = Color::rgb(0.2, 0.35, 0.2);
+const UNAFFORDABLE_COLOR: Color = Color::rgb(0.08, 0.08, 0.08);
+const UNAFFORDABLE_TEXT_COLOR: Color = Color::rgb(0.45, 0.45, 0.45);
+
+#[derive(Component)]
+pub struct BuildToolbar;
+
+/// One toolbar button per tower type. Clicking an affordable one selects it in `BuildSelection`,
+/// exactly like its number key.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BuildButton {
+    pub tower_type: TowerType,
+}
+
+#[derive(Component)]
+pub struct BuildButtonLabel {
+    pub tower_type: TowerType,
+}
+
+#[derive(Component)]
+pub struct BuildTooltip;
+
+pub struct BuildToolbarPlugin;
+
+impl Plugin for BuildToolbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Next).with_system(spawn_build_toolbar))
+            .add_system_set(
+                SystemSet::on_update(GameState::Next)
+                    .with_system(build_button_clicks)
+                    .with_system(update_build_buttons)
+                    .with_system(update_build_tooltip),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Next).with_system(despawn_build_toolbar));
+    }
+}
+
+/// Stand-in icon colour for each tower type until there are proper icon images.
+fn icon_color(tower_type: TowerType) -> Color {
+    match tower_type {
+        TowerType::Cannon => Color::rgb(0.6, 0.6, 0.6),
+        TowerType::Sniper => Color::rgb(0.3, 0.5, 0.9),
+        TowerType::Mortar => Color::rgb(0.9, 0.5, 0.2),
+        TowerType::Shotgun => Color::rgb(0.8, 0.8, 0.3),
+        TowerType::Tesla => Color::rgb(0.6, 0.3, 0.9),
+        TowerType::Railgun => Color::rgb(0.3, 0.9, 0.8),
+        TowerType::Buff => Color::rgb(0.9, 0.8, 0.9),
+        TowerType::GoldMine => Color::GOLD,
+        TowerType::Artillery => Color::rgb(0.7, 0.3, 0.1),
+        TowerType::Decoy => Color::rgb(1.0, 0.4, 0.8),
+        TowerType::Stunner => Color::rgb(1.0, 1.0, 0.5),
+        TowerType::Frost => Color::rgb(0.6, 0.9, 1.0),
+        TowerType::Poison => Color::rgb(0.4, 0.8, 0.2),
+    }
+}
+
+fn spawn_build_toolbar(mut commands: Commands, assets: Res<GameAssets>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(10.0),
+                    ..default()
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(BuildToolbar)
+        .insert(Name::new("BuildToolbar"))
+        .with_children(|commands| {
+            commands
+                .spawn_bundle(
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: assets.font.clone(),
+                            font_size: 20.0,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::all(Val::Px(5.0)),
+                        display: Display::None,
+                        ..default()
+                    }),
+                )
+                .insert(BuildTooltip);
+
+            commands
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    ..default()
+                })
+                .with_children(|commands| {
+                    for tower_type in TowerType::ALL {
+                        spawn_build_button(commands, &assets, tower_type);
+                    }
+                });
+        });
+}
+
+/// The button text, naming the key that selects the tower as well as its cost.
+fn build_label(tower_type: TowerType, config: &GameConfig, bindings: &KeyBindings) -> String {
+    format!(
+        "{:?}. {:?}\n{} gold",
+        bindings.key(Action::SelectTower(tower_type)),
+        tower_type,
+        config.tower(tower_type).cost
+    )
+}
+
+/// The label is filled in by `update_build_buttons`, which also keeps it in step with rebinding.
+fn spawn_build_button(commands: &mut ChildBuilder, assets: &GameAssets, tower_type: TowerType) {
+    commands
+        .spawn_bundle(ButtonBundle {
+            style: Style {
+                size: Size::new(Val::Px(110.0), Val::Px(80.0)),
+                margin: UiRect::all(Val::Px(5.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: BUTTON_COLOR.into(),
+            ..default()
+        })
+        .insert(BuildButton { tower_type })
+        .with_children(|commands| {
+            commands.spawn_bundle(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(24.0), Val::Px(24.0)),
+                    margin: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                color: icon_color(tower_type).into(),
+                ..default()
+            });
+
+            commands
+                .spawn_bundle(
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: assets.font.clone(),
+                            font_size: 18.0,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_text_alignment(TextAlignment::CENTER),
+                )
+                .insert(BuildButtonLabel { tower_type });
+        });
+}
+
+/// Clicking an unaffordable button does nothing, so the player can't enter build mode for a
+/// tower they'd only be refused.
+fn build_button_clicks(
+    buttons: Query<(&Interaction, &BuildButton), Changed<Interaction>>,
+    mut build: ResMut<BuildSelection>,
+    currency: Res<Currency>,
+    config: Res<GameConfig>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        if currency.can_afford(config.tower(button.tower_type).cost) {
+            build.tower_type = Some(button.tower_type);
+        }
+    }
+}
+
+/// Recolours every button each frame so the toolbar follows `Currency` and the build selection
+/// as they change.
+fn update_build_buttons(
+    mut buttons: Query<(&BuildButton, &Interaction, &mut UiColor)>,
+    mut labels: Query<(&BuildButtonLabel, &mut Text)>,
+    currency: Res<Currency>,
+    config: Res<GameConfig>,
+    build: Res<BuildSelection>,
+    bindings: Res<KeyBindings>,
+) {
+    let affordable = |tower_type| currency.can_afford(config.tower(tower_type).cost);
+
+    for (button, interaction, mut color) in &mut buttons {
+        let target = if build.tower_type == Some(button.tower_type) {
+            SELECTED_COLOR
+        } else if !affordable(button.tower_type) {
+            UNAFFORDABLE_COLOR
+        } else if *interaction != Interaction::None {
+            HOVERED_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+
+        if color.0 != target {
+            color.0 = target;
+        }
+    }
+
+    for (label, mut text) in &mut labels {
+        let target = if affordable(label.tower_type) {
+            Color::WHITE
+        } else {
+            UNAFFORDABLE_TEXT_COLOR
+        };
+
+        if text.sections[0].style.color != target {
+            text.sections[0].style.color = target;
+        }
+
+        let contents = build_label(label.tower_type, &config, &bindings);
+        if text.sections[0].value != contents {
+            text.sections[0].value = contents;
+        }
+    }
+}
+
+fn update_build_tooltip(
+    buttons: Query<(&BuildButton, &Interaction)>,
+    mut tooltips: Query<(&mut Text, &mut Style), With<BuildTooltip>>,
+    config: Res<GameConfig>,
+) {
+    let hovered = buttons
+        .iter()
+        .find(|(_, interaction)| **interaction != Interaction::None)
+        .map(|(button, _)| button.tower_type);
+
+    for (mut text, mut style) in &mut tooltips {
+        let tower_type = match hovered {
+            Some(tower_type) => tower_type,
+            None => {
+                if style.display != Display::None {
+                    style.display = Display::None;
+                }
+                continue;
+            }
+        };
+
+        let tower = config.tower(tower_type);
+        let contents = format!(
+            "{:?}: {} damage, {:.1} range, {:.1}s cooldown, {:?}",
+            tower_type, tower.damage, tower.range, tower.cooldown, tower.damage_type
+        );
+
+        if style.display != Display::Flex {
+            style.display = Display::Flex;
+        }
+
+        if text.sections[0].value != contents {
+            text.sections[0].value = contents;
+        }
+    }
+}
+
+fn despawn_build_toolbar(mut commands: Commands, toolbars: Query<Entity, With<BuildToolbar>>) {
+    for entity in &toolbars {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn click_with_gold(gold: u32, tower_type: TowerType) -> Option<TowerType> {
+        let mut app = App::new();
+        app.insert_resource(GameConfig::parse(include_str!("../assets/config.ron")).unwrap())
+            .insert_resource(Currency { gold })
+            .init_resource::<BuildSelection>()
+            .add_system(build_button_clicks);
+        app.world
+            .spawn()
+            .insert(BuildButton { tower_type })
+            .insert(Interaction::Clicked);
+
+        app.update();
+
+        app.world.resource::<BuildSelection>().tower_type
+    }
+
+    #[test]
+    fn only_affordable_buttons_enter_build_mode() {
+        // Railguns cost 120.
+        assert_eq!(
+            click_with_gold(120, TowerType::Railgun),
+            Some(TowerType::Railgun)
+        );
+        assert_eq!(click_with_gold(119, TowerType::Railgun), None);
+    }
+}
@@ -0,0 +1,1381 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+const TOWER_BASE_HEIGHT: f32 = 0.75;
+
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct TowerBase {}
+
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct TowerBarrel {}
+
+/// Makes a tower's bullets `Pierce` through `count` enemies after the first.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct PierceShots {
+    pub count: u32,
+}
+
+/// Makes a tower's bullets `Stun` whatever they hit for `duration` seconds.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct StunShots {
+    pub duration: f32,
+}
+
+/// Lets a tower shoot at `Flying` enemies, which every other tower ignores.
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct CanTargetAir;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum TowerType {
+    Cannon,
+    Sniper,
+    Mortar,
+    Shotgun,
+    Tesla,
+    Railgun,
+    Buff,
+    GoldMine,
+    Artillery,
+    Decoy,
+    Stunner,
+    Frost,
+    Poison,
+}
+
+impl TowerType {
+    pub const ALL: [TowerType; 13] = [
+        Self::Cannon,
+        Self::Sniper,
+        Self::Mortar,
+        Self::Shotgun,
+        Self::Tesla,
+        Self::Railgun,
+        Self::Buff,
+        Self::GoldMine,
+        Self::Artillery,
+        Self::Decoy,
+        Self::Stunner,
+        Self::Frost,
+        Self::Poison,
+    ];
+}
+
+impl Default for TowerType {
+    fn default() -> Self {
+        Self::Cannon
+    }
+}
+
+#[derive(Reflect, Component, Default)]
+#[reflect(Component)]
+pub struct Tower {
+    #[reflect(ignore)]
+    pub tower_type: TowerType,
+    pub shooting_timer: Timer,
+    /// Where shots leave the tower, in the barrel's frame: -Z points at the target. Successive
+    /// shots cycle through them, so a twin barrel alternates sides.
+    #[reflect(ignore)]
+    pub muzzles: Vec<Vec3>,
+    pub next_muzzle: usize,
+    pub cost: u32,
+    pub range: f32,
+    pub turn_speed: f32,
+    pub level: u8,
+    pub damage: i32,
+    /// Purchase price plus every upgrade paid for, used to work out the sell refund.
+    pub total_spent: u32,
+    #[reflect(ignore)]
+    pub targeting: TargetingMode,
+    pub bullet_speed: f32,
+    pub bullet_scene: Handle<Scene>,
+    /// Hits from this tower ignore armor.
+    pub true_damage: bool,
+    #[reflect(ignore)]
+    pub damage_type: DamageType,
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+    /// Bullets damage every enemy this close to where they hit.
+    pub splash_radius: f32,
+    /// Speed factor bullets slow the enemies they hit to.
+    pub slow: Option<f32>,
+    /// Damage per second and duration of the poison bullets leave on the enemies they hit.
+    #[reflect(ignore)]
+    pub poison: Option<(f32, f32)>,
+    /// Skips enemies that bullets already in flight are expected to kill, going by
+    /// `IncomingDamage`.
+    pub avoid_overkill: bool,
+    /// The enemy the tower last picked, kept until it dies or leaves range so two enemies at
+    /// much the same distance don't have it flicking between them.
+    #[reflect(ignore)]
+    pub current_target: Option<Entity>,
+}
+
+/// Fires `count` bullets per shot, fanned evenly across `spread_degrees` around the aim.
+#[derive(Reflect, Component)]
+#[reflect(Component)]
+pub struct Multishot {
+    pub count: u32,
+    pub spread_degrees: f32,
+}
+
+impl Default for Multishot {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            spread_degrees: 0.0,
+        }
+    }
+}
+
+impl Multishot {
+    pub fn directions(&self, aim: Vec3) -> Vec<Vec3> {
+        if self.count <= 1 {
+            return vec![aim];
+        }
+
+        let spread = self.spread_degrees.to_radians();
+        let step = spread / (self.count - 1) as f32;
+
+        (0..self.count)
+            .map(|index| Quat::from_rotation_y(-spread / 2.0 + step * index as f32) * aim)
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct Selection {
+    pub tower: Option<Entity>,
+}
+
+pub struct SellRefund {
+    pub ratio: f32,
+}
+
+impl Default for SellRefund {
+    fn default() -> Self {
+        Self { ratio: 0.7 }
+    }
+}
+
+impl SellRefund {
+    /// Gold paid back for selling `tower`.
+    pub fn value(&self, tower: &Tower) -> u32 {
+        (tower.total_spent as f32 * self.ratio).round() as u32
+    }
+}
+
+/// Sent whenever a tower fires, for sounds and effects that don't affect the game.
+pub struct TowerFired {
+    pub tower: Entity,
+    /// Where the shot left the tower.
+    pub position: Vec3,
+}
+
+pub struct SellRequest {
+    pub tower: Entity,
+}
+
+/// Asks for a `tower_type` tower to be built on `cell`, paid for if there's enough gold.
+pub struct PlaceTowerRequest {
+    pub tower_type: TowerType,
+    pub cell: IVec2,
+}
+
+impl Tower {
+    /// Offset from the tower to the muzzle the next shot leaves from, turned to face along
+    /// `aim`. An `aim` with no horizontal part leaves the offset unturned.
+    pub fn muzzle(&self, aim: Vec3) -> Vec3 {
+        let offset = self
+            .muzzles
+            .get(self.next_muzzle)
+            .copied()
+            .unwrap_or_default();
+
+        if aim.x == 0.0 && aim.z == 0.0 {
+            return offset;
+        }
+
+        Quat::from_rotation_y(f32::atan2(-aim.x, -aim.z)) * offset
+    }
+
+    /// Moves on to the next muzzle, wrapping back to the first.
+    pub fn cycle_muzzle(&mut self) {
+        if !self.muzzles.is_empty() {
+            self.next_muzzle = (self.next_muzzle + 1) % self.muzzles.len();
+        }
+    }
+
+    /// Seconds a bullet takes to fly out to the edge of this tower's range, plus `margin` to
+    /// spare, so it lasts just long enough to hit anything the tower can aim at.
+    pub fn bullet_lifetime(&self, margin: f32) -> f32 {
+        if self.bullet_speed <= 0.0 {
+            return margin;
+        }
+
+        self.range / self.bullet_speed + margin
+    }
+
+    /// A freshly built level 1 tower of `tower_type`.
+    pub fn new(tower_type: TowerType, config: &GameConfig, assets: &GameAssets) -> Self {
+        let config = config.tower(tower_type);
+        let muzzles = if config.muzzles.is_empty() {
+            vec![config.bullet_offset]
+        } else {
+            config.muzzles.clone()
+        };
+
+        Self {
+            tower_type,
+            shooting_timer: Timer::from_seconds(config.cooldown, false),
+            muzzles: muzzles
+                .into_iter()
+                .map(|(x, y, z)| Vec3::new(x, y, z))
+                .collect(),
+            next_muzzle: 0,
+            cost: config.cost,
+            range: config.range,
+            turn_speed: config.turn_speed,
+            level: 1,
+            damage: config.damage,
+            total_spent: config.cost,
+            targeting: TargetingMode::default(),
+            bullet_speed: config.bullet_speed,
+            bullet_scene: assets.projectile_scene(tower_type),
+            true_damage: config.true_damage,
+            damage_type: config.damage_type,
+            crit_chance: config.crit_chance,
+            crit_multiplier: config.crit_multiplier,
+            splash_radius: config.splash_radius,
+            slow: config.slow,
+            poison: config.poison,
+            avoid_overkill: config.avoid_overkill,
+            current_target: None,
+        }
+    }
+}
+
+pub struct TowerPlugin;
+
+impl Plugin for TowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Tower>()
+            .register_type::<TowerBase>()
+            .register_type::<TowerBarrel>()
+            .register_type::<CanTargetAir>()
+            .register_type::<PierceShots>()
+            .register_type::<StunShots>()
+            .register_type::<Multishot>()
+            .init_resource::<Selection>()
+            .init_resource::<SellRefund>()
+            .add_event::<SellRequest>()
+            .add_event::<PlaceTowerRequest>()
+            .add_event::<TowerFired>()
+            .add_system_set_to_stage(
+                GameStep,
+                SystemSet::new()
+                    // Bullets go back into the pool before towers take them out again, so
+                    // pooled commands always apply in that order.
+                    .with_system(
+                        tower_shooting
+                            .after(RebuildSpatialGrid)
+                            .after(BulletSystem::Collision)
+                            .after(BulletSystem::Despawn)
+                            .after(TrackIncomingDamage),
+                    )
+                    .with_system(
+                        tower_aiming
+                            .after(RebuildSpatialGrid)
+                            .after(TrackIncomingDamage),
+                    ),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Next)
+                    .with_system(tower_placement)
+                    .with_system(place_tower.after(tower_placement))
+                    .with_system(sell_tower),
+            );
+    }
+}
+
+/// Spawns `tower` along with whichever firing behaviours its type is configured with.
+pub fn spawn_tower(
+    commands: &mut Commands,
+    assets: &GameAssets,
+    config: &GameConfig,
+    position: Vec3,
+    tower: Tower,
+) -> Entity {
+    let tower_config = config.tower(tower.tower_type);
+    let mut entity = commands.spawn_bundle(SceneBundle {
+        scene: assets.tower_base_scene.clone(),
+        transform: Transform::from_translation(position + Vec3::Y * TOWER_BASE_HEIGHT),
+        ..default()
+    });
+
+    entity
+        .insert(TowerBase {})
+        .insert(tower)
+        .insert(Gameplay)
+        .insert(Name::new("Tower"))
+        .with_children(|commands| {
+            commands
+                .spawn_bundle(SceneBundle {
+                    scene: assets.tower_barrel_scene.clone(),
+                    ..default()
+                })
+                .insert(TowerBarrel {})
+                .insert(Name::new("TowerBarrel"));
+        });
+
+    if tower_config.mortar {
+        entity.insert(Mortar::default());
+    }
+
+    if tower_config.hitscan {
+        entity.insert(Hitscan::default());
+    }
+
+    if tower_config.predictive_aim {
+        entity.insert(PredictiveAim);
+    }
+
+    if tower_config.can_target_air {
+        entity.insert(CanTargetAir);
+    }
+
+    if let Some(count) = tower_config.pierce {
+        entity.insert(PierceShots { count });
+    }
+
+    if let Some(duration) = tower_config.stun {
+        entity.insert(StunShots { duration });
+    }
+
+    if let Some((jumps, jump_range, falloff)) = tower_config.chain_lightning {
+        entity.insert(ChainLightning {
+            jumps,
+            jump_range,
+            falloff,
+        });
+    }
+
+    if let Some(turn_rate) = tower_config.homing_turn_rate {
+        entity.insert(HomingShots {
+            turn_rate,
+            retarget: tower_config.homing_retarget,
+        });
+    }
+
+    if let Some((fire_rate, damage)) = tower_config.aura {
+        entity.insert(Aura { fire_rate, damage });
+    }
+
+    if let Some((max, per_shot, cooldown_rate)) = tower_config.heat {
+        entity.insert(Heat::new(max, per_shot, cooldown_rate));
+    }
+
+    if let Some((amount, interval)) = tower_config.gold_mine {
+        entity.insert(GoldMine::new(amount, interval));
+    }
+
+    if let Some((interval, duration, radius)) = tower_config.decoy {
+        entity.insert(DecoyEmitter::new(interval, duration, radius));
+    }
+
+    if let Some((submunitions, child_damage, spread)) = tower_config.cluster_bomb {
+        entity.insert(ClusterBomb {
+            submunitions,
+            child_damage,
+            spread,
+        });
+    }
+
+    if let Some((count, spread_degrees)) = tower_config.multishot {
+        entity.insert(Multishot {
+            count,
+            spread_degrees,
+        });
+    }
+
+    entity.id()
+}
+
+/// Projects the cursor through the camera and returns where it meets the ground plane (y = 0).
+pub fn cursor_to_ground(
+    windows: &Windows,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec3> {
+    let window = windows.get_primary()?;
+    let cursor = window.cursor_position()?;
+    let window_size = Vec2::new(window.width(), window.height());
+
+    cursor_ray_to_ground(
+        cursor,
+        window_size,
+        camera.projection_matrix(),
+        camera_transform,
+    )
+}
+
+/// Where the ray through `cursor`, in a window of `window_size`, meets the ground plane.
+fn cursor_ray_to_ground(
+    cursor: Vec2,
+    window_size: Vec2,
+    projection: Mat4,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec3> {
+    let ndc = (cursor / window_size) * 2.0 - Vec2::ONE;
+
+    let ndc_to_world = camera_transform.compute_matrix() * projection.inverse();
+    let near = ndc_to_world.project_point3(ndc.extend(1.0));
+    let far = ndc_to_world.project_point3(ndc.extend(f32::EPSILON));
+    let direction = far - near;
+
+    if direction.y.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let distance = -near.y / direction.y;
+
+    if distance < 0.0 {
+        return None;
+    }
+
+    Some(near + direction * distance)
+}
+
+fn tower_placement(
+    cameras: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    interactions: Query<&Interaction>,
+    mut upgrade_requests: EventWriter<UpgradeRequest>,
+    mut place_requests: EventWriter<PlaceTowerRequest>,
+    mut selection: ResMut<Selection>,
+    mut build: ResMut<BuildSelection>,
+    grid: Res<GridMap>,
+    mouse: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    replay: Res<Replay>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) || !replay.accepts_input() {
+        return;
+    }
+
+    // Clicks on the UI are handled by the buttons underneath the cursor, not the world.
+    if interactions
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+
+    let (camera, camera_transform) = match cameras.get_single() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+
+    let hit = match cursor_to_ground(&windows, camera, camera_transform) {
+        Some(hit) => hit,
+        None => return,
+    };
+
+    click_cell(
+        grid.world_to_cell(hit),
+        &grid,
+        &mut selection,
+        &mut build,
+        &mut upgrade_requests,
+        &mut place_requests,
+    );
+}
+
+/// Selects, upgrades or builds on `cell`, whichever a click there means right now.
+fn click_cell(
+    cell: IVec2,
+    grid: &GridMap,
+    selection: &mut Selection,
+    build: &mut BuildSelection,
+    upgrade_requests: &mut EventWriter<UpgradeRequest>,
+    place_requests: &mut EventWriter<PlaceTowerRequest>,
+) {
+    // Clicking a tower selects it, clicking it again while selected upgrades it.
+    if let Some(tower) = grid.occupant(cell) {
+        if selection.tower == Some(tower) {
+            upgrade_requests.send(UpgradeRequest { tower });
+        } else {
+            selection.tower = Some(tower);
+        }
+        return;
+    }
+
+    // Clicking empty ground clears the selection.
+    let tower_type = match build.tower_type {
+        Some(tower_type) => tower_type,
+        None => {
+            selection.tower = None;
+            return;
+        }
+    };
+
+    place_requests.send(PlaceTowerRequest { tower_type, cell });
+    build.tower_type = None;
+}
+
+fn place_tower(
+    mut commands: Commands,
+    mut requests: EventReader<PlaceTowerRequest>,
+    mut currency: ResMut<Currency>,
+    mut grid: ResMut<GridMap>,
+    mut history: ResMut<PlacementHistory>,
+    mut replay: ResMut<Replay>,
+    config: Res<GameConfig>,
+    assets: Res<GameAssets>,
+) {
+    for &PlaceTowerRequest { tower_type, cell } in requests.iter() {
+        if !grid.is_free(cell) {
+            debug!(?cell, "Tower placement rejected, cell is blocked");
+            continue;
+        }
+
+        let cost = config.tower(tower_type).cost;
+        if !currency.spend(cost) {
+            debug!("Tower placement rejected, not enough gold");
+            continue;
+        }
+
+        let position = grid.cell_to_world(cell);
+        let tower = Tower::new(tower_type, &config, &assets);
+        let tower = spawn_tower(&mut commands, &assets, &config, position, tower);
+        grid.occupy(cell, tower);
+        history.push(tower, cost, cell);
+        replay.record(ReplayInput::PlaceTower { tower_type, cell });
+    }
+}
+
+/// Removes towers along with their barrels and frees up their grid cells. Delete sells the
+/// selected tower; anything else can send a `SellRequest`.
+fn sell_tower(
+    mut commands: Commands,
+    towers: Query<(&Tower, &Transform)>,
+    mut requests: EventReader<SellRequest>,
+    mut selection: ResMut<Selection>,
+    mut currency: ResMut<Currency>,
+    mut grid: ResMut<GridMap>,
+    mut replay: ResMut<Replay>,
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    refund: Res<SellRefund>,
+) {
+    let mut sold: Vec<Entity> = requests.iter().map(|request| request.tower).collect();
+
+    if replay.accepts_input() && bindings.is_action_just_pressed(&keyboard, Action::Sell) {
+        sold.extend(selection.tower);
+    }
+
+    // The same tower can be asked for twice in a frame, but only pays out once.
+    sold.sort_unstable();
+    sold.dedup();
+
+    for entity in sold {
+        if let Ok((tower, transform)) = towers.get(entity) {
+            currency.gold += refund.value(tower);
+            let cell = grid.world_to_cell(transform.translation);
+            grid.clear(cell);
+            commands.entity(entity).despawn_recursive();
+            replay.record(ReplayInput::Sell { cell });
+        }
+
+        if selection.tower == Some(entity) {
+            selection.tower = None;
+        }
+    }
+}
+
+fn tower_aiming(
+    towers: Query<
+        (&Tower, &GlobalTransform, &Children, Option<&CanTargetAir>),
+        (Without<Aura>, Without<GoldMine>, Without<DecoyEmitter>),
+    >,
+    mut barrels: Query<&mut Transform, With<TowerBarrel>>,
+    targets: Query<(&Health, &PathFollower), With<Target>>,
+    incoming: Query<&IncomingDamage>,
+    grid: Res<SpatialGrid>,
+    paths: Res<Paths>,
+    time: Res<GameTime>,
+) {
+    for (tower, transform, children, anti_air) in &towers {
+        let origin = transform.translation() + tower.muzzle(Vec3::ZERO);
+
+        let target = match select_target(
+            tower.targeting,
+            tower.current_target,
+            &grid,
+            origin,
+            tower.range,
+            anti_air.is_some(),
+            &paths,
+            &targets,
+            |entity| {
+                !tower.avoid_overkill
+                    || !matches!(
+                        (incoming.get(entity), targets.get(entity)),
+                        (Ok(incoming), Ok((health, _))) if incoming.is_lethal(health)
+                    )
+            },
+        ) {
+            Some((_, target)) => target,
+            None => continue,
+        };
+
+        let direction = target - transform.translation();
+        let aim = Quat::from_rotation_y(f32::atan2(-direction.x, -direction.z));
+
+        for child in children {
+            if let Ok(mut barrel_transform) = barrels.get_mut(*child) {
+                barrel_transform.rotation = barrel_transform
+                    .rotation
+                    .slerp(aim, (tower.turn_speed * time.delta_seconds()).min(1.0));
+            }
+        }
+    }
+}
+
+fn tower_shooting(
+    mut commands: Commands,
+    mut towers: Query<
+        (
+            Entity,
+            &mut Tower,
+            &GlobalTransform,
+            Option<&Mortar>,
+            Option<&ClusterBomb>,
+            Option<&HomingShots>,
+            Option<&Multishot>,
+            Option<&PredictiveAim>,
+            Option<&CanTargetAir>,
+            Option<&PierceShots>,
+            Option<&StunShots>,
+            Option<&Buffed>,
+            Option<&mut Heat>,
+        ),
+        (
+            Without<Hitscan>,
+            Without<ChainLightning>,
+            Without<Aura>,
+            Without<GoldMine>,
+            Without<DecoyEmitter>,
+        ),
+    >,
+    targets: Query<(&Health, &PathFollower), With<Target>>,
+    mut incoming: Query<&mut IncomingDamage>,
+    mut pool: ResMut<BulletPool>,
+    grid: Res<SpatialGrid>,
+    paths: Res<Paths>,
+    mut fired_events: EventWriter<TowerFired>,
+    config: Res<GameConfig>,
+    time: Res<GameTime>,
+) {
+    for (
+        entity,
+        mut tower,
+        transform,
+        mortar,
+        cluster,
+        homing,
+        multishot,
+        predictive,
+        anti_air,
+        pierce,
+        stun,
+        buffed,
+        mut heat,
+    ) in &mut towers
+    {
+        let lifetime = tower.bullet_lifetime(config.bullet.lifetime_margin);
+        let delta = buffed.map_or(time.delta(), |buffed| buffed.cooldown_delta(time.delta()));
+        tower.shooting_timer.tick(delta);
+        let damage = buffed.map_or(tower.damage, |buffed| buffed.damage(tower.damage));
+
+        // The timer doesn't repeat, so a tower with nothing in range stays loaded and fires as
+        // soon as a target walks in instead of waiting out another cooldown. Overheated towers
+        // stay loaded too, and fire as soon as they've cooled.
+        let cool = heat.as_ref().map_or(true, |heat| heat.can_fire());
+        if tower.shooting_timer.finished() && cool {
+            let origin = transform.translation() + tower.muzzle(Vec3::ZERO);
+
+            let avoid_overkill = tower.avoid_overkill;
+            let target = select_target(
+                tower.targeting,
+                tower.current_target,
+                &grid,
+                origin,
+                tower.range,
+                anti_air.is_some(),
+                &paths,
+                &targets,
+                |entity| {
+                    !avoid_overkill
+                        || !matches!(
+                            (incoming.get(entity), targets.get(entity)),
+                            (Ok(incoming), Ok((health, _))) if incoming.is_lethal(health)
+                        )
+                },
+            );
+            tower.current_target = target.map(|(target, _)| target);
+
+            if let Some((target, target_position)) = target {
+                tower.shooting_timer.reset();
+                if let Some(heat) = heat.as_mut() {
+                    heat.add_shot();
+                }
+
+                let bullet_spawn = transform.translation() + tower.muzzle(target_position - origin);
+                tower.cycle_muzzle();
+
+                let aim_point = match (predictive, targets.get(target)) {
+                    (Some(_), Ok((_, follower))) => lead_target(
+                        bullet_spawn,
+                        target_position,
+                        paths
+                            .for_follower(follower)
+                            .velocity(follower, target_position),
+                        tower.bullet_speed,
+                    )
+                    .unwrap_or(target_position),
+                    _ => target_position,
+                };
+                let direction = aim_point - bullet_spawn;
+
+                if let Some(mortar) = mortar {
+                    let landing = match targets.get(target) {
+                        Ok((_, follower)) => paths.for_follower(follower).predict(
+                            follower,
+                            target_position,
+                            mortar.flight_time,
+                        ),
+                        Err(_) => target_position,
+                    };
+
+                    let shell = spawn_shell(
+                        &mut commands,
+                        &tower.bullet_scene,
+                        bullet_spawn,
+                        Ballistic {
+                            velocity: launch_velocity(
+                                bullet_spawn,
+                                landing * Vec3::new(1.0, 0.0, 1.0),
+                                mortar.flight_time,
+                                mortar.gravity,
+                            ),
+                            gravity: mortar.gravity,
+                            damage,
+                            splash_radius: mortar.splash_radius,
+                            damage_type: tower.damage_type,
+                            anti_air: anti_air.is_some(),
+                        },
+                    );
+
+                    if let Some(cluster) = cluster {
+                        commands.entity(shell).insert(*cluster);
+                    }
+                } else {
+                    let directions = match multishot {
+                        Some(multishot) => multishot.directions(direction),
+                        None => vec![direction],
+                    };
+
+                    // A spread of bullets may well miss, so only a single bullet counts as
+                    // damage on its way to the target.
+                    let aimed = directions.len() == 1;
+
+                    for direction in directions {
+                        let bullet = spawn_bullet(
+                            &mut commands,
+                            &mut pool,
+                            &tower.bullet_scene,
+                            bullet_spawn,
+                            Bullet {
+                                direction,
+                                speed: tower.bullet_speed,
+                                damage,
+                                collision_radius: config.bullet.collision_radius,
+                                splash_radius: tower.splash_radius,
+                                slow: tower.slow,
+                                poison_dps: tower.poison.map_or(0.0, |(dps, _)| dps),
+                                poison_duration: tower.poison.map_or(0.0, |(_, duration)| duration),
+                                stun: stun.map(|stun| stun.duration),
+                                crit: false,
+                                crit_chance: tower.crit_chance,
+                                crit_multiplier: tower.crit_multiplier,
+                                true_damage: tower.true_damage,
+                                damage_type: tower.damage_type,
+                                anti_air: anti_air.is_some(),
+                            },
+                            lifetime,
+                        );
+
+                        // Recycled bullets get their scale back from the fresh transform
+                        // `spawn_bullet` inserts.
+                        if config.bullet.fade_duration > 0.0 {
+                            commands
+                                .entity(bullet)
+                                .insert(FadeOut::new(config.bullet.fade_duration));
+                        }
+
+                        if let Some(homing) = homing {
+                            commands.entity(bullet).insert(Homing {
+                                target,
+                                turn_rate: homing.turn_rate,
+                                retarget: homing.retarget,
+                                can_target_air: anti_air.is_some(),
+                            });
+                        }
+
+                        if let Some(pierce) = pierce {
+                            commands.entity(bullet).insert(Pierce::new(pierce.count));
+                        }
+
+                        // Counted straight away so other towers firing this step see it too.
+                        if aimed {
+                            commands.entity(bullet).insert(AimedAt { target, damage });
+                            if let Ok(mut incoming) = incoming.get_mut(target) {
+                                incoming.amount += damage;
+                            }
+                        }
+                    }
+                }
+
+                fired_events.send(TowerFired {
+                    tower: entity,
+                    position: bullet_spawn,
+                });
+                debug!(?direction.x, ?direction.y, ?direction.z);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::camera::CameraProjection;
+    use std::time::Duration;
+
+    fn app() -> App {
+        let mut app = App::new();
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+
+        app.insert_resource(config)
+            .insert_resource(GameAssets::headless())
+            .init_resource::<Currency>()
+            .init_resource::<GridMap>()
+            .init_resource::<PlacementHistory>()
+            .init_resource::<Replay>()
+            .add_event::<PlaceTowerRequest>()
+            .add_system(place_tower);
+
+        app
+    }
+
+    fn request(app: &mut App, tower_type: TowerType, cell: IVec2) {
+        app.world
+            .resource_mut::<Events<PlaceTowerRequest>>()
+            .send(PlaceTowerRequest { tower_type, cell });
+        app.update();
+    }
+
+    fn towers(app: &mut App) -> Vec<Vec3> {
+        app.world
+            .query_filtered::<&Transform, With<Tower>>()
+            .iter(&app.world)
+            .map(|transform| transform.translation)
+            .collect()
+    }
+
+    #[test]
+    fn clicked_ground_is_snapped_to_a_cell_for_the_tower() {
+        let mut app = app();
+        let window_size = Vec2::new(800.0, 600.0);
+        let projection = PerspectiveProjection {
+            aspect_ratio: window_size.x / window_size.y,
+            ..default()
+        }
+        .get_projection_matrix();
+        let camera = GlobalTransform::from(
+            Transform::from_xyz(3.0, 10.0, 5.0).looking_at(Vec3::new(3.0, 0.0, 5.0), Vec3::Z),
+        );
+
+        // A little off the middle of the window, so the click lands off the cell's centre.
+        let hit = cursor_ray_to_ground(Vec2::new(410.0, 305.0), window_size, projection, &camera)
+            .expect("cursor ray missed the ground");
+        assert!(hit.distance(Vec3::new(3.0, 0.0, 5.0)) > 0.05);
+
+        let cell = app.world.resource::<GridMap>().world_to_cell(hit);
+        request(&mut app, TowerType::Cannon, cell);
+
+        assert_eq!(cell, IVec2::new(3, 5));
+        assert_eq!(
+            towers(&mut app),
+            vec![Vec3::new(3.0, TOWER_BASE_HEIGHT, 5.0)]
+        );
+        assert!(!app.world.resource::<GridMap>().is_free(cell));
+    }
+
+    #[test]
+    fn buying_a_tower_spends_its_cost() {
+        let mut app = app();
+        let cost = app
+            .world
+            .resource::<GameConfig>()
+            .tower(TowerType::Cannon)
+            .cost;
+
+        request(&mut app, TowerType::Cannon, IVec2::new(1, 1));
+
+        assert_eq!(app.world.resource::<Currency>().gold, 100 - cost);
+        assert_eq!(towers(&mut app).len(), 1);
+    }
+
+    #[test]
+    fn towers_that_cant_be_afforded_are_not_built() {
+        let mut app = app();
+        app.world.resource_mut::<Currency>().gold = 10;
+
+        request(&mut app, TowerType::Railgun, IVec2::new(1, 1));
+
+        assert_eq!(app.world.resource::<Currency>().gold, 10);
+        assert!(towers(&mut app).is_empty());
+        assert!(app.world.resource::<GridMap>().is_free(IVec2::new(1, 1)));
+    }
+
+    #[test]
+    fn occupied_cells_cant_be_built_on_again() {
+        let mut app = app();
+        let cost = app
+            .world
+            .resource::<GameConfig>()
+            .tower(TowerType::Cannon)
+            .cost;
+
+        request(&mut app, TowerType::Cannon, IVec2::new(2, -1));
+        request(&mut app, TowerType::Cannon, IVec2::new(2, -1));
+
+        assert_eq!(towers(&mut app).len(), 1);
+        assert_eq!(app.world.resource::<Currency>().gold, 100 - cost);
+    }
+
+    #[test]
+    fn towers_cant_be_built_on_the_path() {
+        let mut app = app();
+        let path = Path::new("Straight", vec![Vec3::ZERO, Vec3::new(5.0, 0.0, 0.0)]);
+        app.world.resource_mut::<GridMap>().block_path(&path);
+
+        request(&mut app, TowerType::Cannon, IVec2::new(2, 0));
+        assert!(towers(&mut app).is_empty());
+        assert_eq!(app.world.resource::<Currency>().gold, 100);
+
+        // The next row over is clear of the path.
+        request(&mut app, TowerType::Cannon, IVec2::new(2, 1));
+        assert_eq!(towers(&mut app).len(), 1);
+    }
+
+    fn combat_app() -> App {
+        let mut app = App::new();
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+
+        app.insert_resource(config)
+            .insert_resource(GameAssets::headless())
+            .insert_resource(Paths::new(vec![Path::new(
+                "Straight",
+                vec![Vec3::ZERO, Vec3::new(10.0, 0.0, 0.0)],
+            )]))
+            .insert_resource(GameTime::stepped(Duration::from_secs_f32(0.1)))
+            .init_resource::<SpatialGrid>()
+            .init_resource::<BulletPool>()
+            .add_event::<TowerFired>()
+            .add_system(tower_shooting)
+            .add_system(tower_aiming);
+
+        app
+    }
+
+    /// A tower of `tower_type` at the origin with its first shot ready.
+    fn spawn_loaded_tower(app: &mut App, tower_type: TowerType) -> Entity {
+        let mut tower = Tower::new(
+            tower_type,
+            app.world.resource::<GameConfig>(),
+            app.world.resource::<GameAssets>(),
+        );
+        let cooldown = tower.shooting_timer.duration();
+        tower.shooting_timer.tick(cooldown);
+
+        app.world
+            .spawn()
+            .insert(tower)
+            .insert(GlobalTransform::default())
+            .id()
+    }
+
+    /// A target `distance` along +X from where `tower`'s shots leave it.
+    fn spawn_target_from(app: &mut App, tower: Entity, distance: f32) -> Entity {
+        let origin = app.world.get::<Tower>(tower).unwrap().muzzle(Vec3::ZERO);
+        let position = origin + Vec3::X * distance;
+        let target = app
+            .world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .insert(Health { value: 10, max: 10 })
+            .insert(PathFollower::default())
+            .insert(GlobalTransform::from_translation(position))
+            .id();
+        app.world
+            .resource_mut::<SpatialGrid>()
+            .insert(target, position);
+
+        target
+    }
+
+    fn bullets(app: &mut App) -> usize {
+        app.world
+            .query_filtered::<(), (With<Bullet>, Without<Pooled>)>()
+            .iter(&app.world)
+            .count()
+    }
+
+    #[test]
+    fn targets_just_out_of_range_are_not_shot_at() {
+        let mut app = combat_app();
+        let tower = spawn_loaded_tower(&mut app, TowerType::Cannon);
+        let range = app.world.get::<Tower>(tower).unwrap().range;
+        spawn_target_from(&mut app, tower, range + 0.1);
+
+        app.update();
+        assert_eq!(bullets(&mut app), 0);
+
+        spawn_target_from(&mut app, tower, range - 0.1);
+        app.update();
+        assert_eq!(bullets(&mut app), 1);
+    }
+
+    #[test]
+    fn barrels_turn_to_face_their_target() {
+        let mut app = combat_app();
+        let barrel = app
+            .world
+            .spawn()
+            .insert(TowerBarrel {})
+            .insert(Transform::default())
+            .id();
+        let tower = spawn_loaded_tower(&mut app, TowerType::Cannon);
+        app.world.entity_mut(tower).push_children(&[barrel]);
+        let target = spawn_target_from(&mut app, tower, 3.0);
+        // Barrels only turn about the vertical.
+        let direction = (app
+            .world
+            .get::<GlobalTransform>(target)
+            .unwrap()
+            .translation()
+            * Vec3::new(1.0, 0.0, 1.0))
+        .normalize();
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        let forward = app.world.get::<Transform>(barrel).unwrap().forward();
+        assert!(
+            forward.dot(direction) > 0.99,
+            "barrel faces {forward} rather than {direction}"
+        );
+    }
+
+    #[test]
+    fn selling_an_upgraded_tower_refunds_most_of_what_was_spent() {
+        let mut app = app();
+        app.init_resource::<Selection>()
+            .init_resource::<SellRefund>()
+            .init_resource::<KeyBindings>()
+            .init_resource::<Input<KeyCode>>()
+            .add_event::<SellRequest>()
+            .add_system(sell_tower);
+        let cell = IVec2::new(2, 2);
+        request(&mut app, TowerType::Cannon, cell);
+        let tower = app.world.resource::<GridMap>().occupant(cell).unwrap();
+
+        let config = app
+            .world
+            .resource::<GameConfig>()
+            .tower(TowerType::Cannon)
+            .clone();
+        let mut spent = config.cost;
+        for level in 2..=3 {
+            let stats = config.level(level).unwrap();
+            app.world.get_mut::<Tower>(tower).unwrap().upgrade(stats);
+            spent += stats.cost;
+        }
+        app.world.resource_mut::<Currency>().gold = 0;
+
+        app.world
+            .resource_mut::<Events<SellRequest>>()
+            .send(SellRequest { tower });
+        app.update();
+
+        assert_eq!(
+            app.world.resource::<Currency>().gold,
+            (spent as f32 * 0.7).round() as u32
+        );
+        assert!(app.world.get_entity(tower).is_none());
+        assert!(app.world.resource::<GridMap>().is_free(cell));
+    }
+
+    #[test]
+    fn multishot_towers_fire_every_barrel_at_once() {
+        let mut app = combat_app();
+        let (count, spread_degrees) = app
+            .world
+            .resource::<GameConfig>()
+            .tower(TowerType::Shotgun)
+            .multishot
+            .unwrap();
+        let tower = spawn_loaded_tower(&mut app, TowerType::Shotgun);
+        app.world.entity_mut(tower).insert(Multishot {
+            count,
+            spread_degrees,
+        });
+        spawn_target_from(&mut app, tower, 2.0);
+
+        app.update();
+
+        assert_eq!(count, 3);
+        assert_eq!(bullets(&mut app), 3);
+        assert_eq!(app.world.resource::<Events<TowerFired>>().len(), 1);
+    }
+
+    fn fired_bullet_speed(tower_type: TowerType) -> f32 {
+        let mut app = combat_app();
+        let tower = spawn_loaded_tower(&mut app, tower_type);
+        spawn_target_from(&mut app, tower, 2.0);
+
+        app.update();
+
+        let mut bullets = app.world.query::<&Bullet>();
+        bullets.single(&app.world).speed
+    }
+
+    #[test]
+    fn each_tower_type_fires_bullets_at_its_own_speed() {
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+
+        let cannon = fired_bullet_speed(TowerType::Cannon);
+        let frost = fired_bullet_speed(TowerType::Frost);
+
+        assert_eq!(cannon, config.tower(TowerType::Cannon).bullet_speed);
+        assert_eq!(frost, config.tower(TowerType::Frost).bullet_speed);
+        assert!(frost > cannon);
+    }
+
+    #[test]
+    fn twin_barrels_take_turns_firing() {
+        let mut app = combat_app();
+        let tower = spawn_loaded_tower(&mut app, TowerType::Cannon);
+        app.world.get_mut::<Tower>(tower).unwrap().muzzles =
+            vec![Vec3::new(-0.2, 0.5, 0.0), Vec3::new(0.2, 0.5, 0.0)];
+        spawn_target_from(&mut app, tower, 2.0);
+
+        let mut reader = app.world.resource::<Events<TowerFired>>().get_reader();
+        let mut shots = Vec::new();
+        // Three shots a second apart.
+        for _ in 0..25 {
+            app.update();
+            let events = app.world.resource::<Events<TowerFired>>();
+            shots.extend(reader.iter(events).map(|fired| fired.position));
+        }
+
+        assert_eq!(shots.len(), 3);
+        assert!((shots[0].distance(shots[1]) - 0.4).abs() < 1e-4);
+        assert!(shots[0].distance(shots[2]) < 1e-4);
+    }
+
+    #[test]
+    fn overheated_towers_hold_fire_until_they_cool() {
+        let mut app = combat_app();
+        let tower = spawn_loaded_tower(&mut app, TowerType::Cannon);
+        // Two shots to overheat, and nothing here cools it down on its own.
+        app.world.entity_mut(tower).insert(Heat::new(4.0, 2.0, 1.0));
+        spawn_target_from(&mut app, tower, 2.0);
+
+        let mut reader = app.world.resource::<Events<TowerFired>>().get_reader();
+        let mut shots = 0;
+        let mut run = |app: &mut App, frames: u32| {
+            for _ in 0..frames {
+                app.update();
+                shots += reader
+                    .iter(app.world.resource::<Events<TowerFired>>())
+                    .count();
+            }
+            shots
+        };
+
+        assert_eq!(run(&mut app, 25), 2);
+        assert!(!app.world.get::<Heat>(tower).unwrap().can_fire());
+
+        // Down to half heat, it fires again straight away.
+        app.world.get_mut::<Heat>(tower).unwrap().dissipate(2.0);
+        assert_eq!(run(&mut app, 1), 3);
+    }
+
+    /// What two cannons side by side shoot at, with a one-hit enemy nearest.
+    fn paired_shots(avoid_overkill: bool) -> (Vec<Entity>, Entity, Entity) {
+        let mut app = combat_app();
+        let towers = [
+            spawn_loaded_tower(&mut app, TowerType::Cannon),
+            spawn_loaded_tower(&mut app, TowerType::Cannon),
+        ];
+        for tower in towers {
+            app.world.get_mut::<Tower>(tower).unwrap().avoid_overkill = avoid_overkill;
+        }
+        let weak = spawn_target_from(&mut app, towers[0], 2.0);
+        let healthy = spawn_target_from(&mut app, towers[0], 3.0);
+        app.world
+            .entity_mut(weak)
+            .insert(Health { value: 1, max: 1 })
+            .insert(IncomingDamage::default());
+        app.world
+            .entity_mut(healthy)
+            .insert(IncomingDamage::default());
+
+        app.update();
+
+        let mut aimed = app.world.query::<&AimedAt>();
+        let mut shots: Vec<Entity> = aimed.iter(&app.world).map(|aimed| aimed.target).collect();
+        shots.sort_unstable();
+        (shots, weak, healthy)
+    }
+
+    #[test]
+    fn towers_avoiding_overkill_leave_doomed_enemies_to_the_shot_already_fired() {
+        let (shots, weak, _) = paired_shots(false);
+        assert_eq!(shots, [weak, weak]);
+
+        let (shots, weak, healthy) = paired_shots(true);
+        let mut expected = [weak, healthy];
+        expected.sort_unstable();
+        assert_eq!(shots, expected);
+    }
+
+    /// The lifetime a cannon's bullet gets once its range and bullet speed are set to these.
+    fn fired_bullet_lifetime(range: f32, bullet_speed: f32) -> f32 {
+        let mut app = combat_app();
+        let tower = spawn_loaded_tower(&mut app, TowerType::Cannon);
+        let mut stats = app.world.get_mut::<Tower>(tower).unwrap();
+        stats.range = range;
+        stats.bullet_speed = bullet_speed;
+        spawn_target_from(&mut app, tower, 2.0);
+
+        app.update();
+
+        let mut lifetimes = app.world.query_filtered::<&Lifetime, With<Bullet>>();
+        lifetimes.single(&app.world).timer.duration().as_secs_f32()
+    }
+
+    #[test]
+    fn bullets_last_as_long_as_it_takes_to_cross_the_range() {
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+        let margin = config.bullet.lifetime_margin;
+
+        let slow_and_far = fired_bullet_lifetime(8.0, 2.0);
+        let fast_and_near = fired_bullet_lifetime(3.0, 6.0);
+
+        assert!((slow_and_far - (4.0 + margin)).abs() < 1e-4);
+        assert!((fast_and_near - (0.5 + margin)).abs() < 1e-4);
+        assert!(slow_and_far > fast_and_near);
+    }
+
+    /// A click waiting to land on a cell.
+    struct Click(Option<IVec2>);
+
+    fn click(
+        mut click: ResMut<Click>,
+        mut upgrade_requests: EventWriter<UpgradeRequest>,
+        mut place_requests: EventWriter<PlaceTowerRequest>,
+        mut selection: ResMut<Selection>,
+        mut build: ResMut<BuildSelection>,
+        grid: Res<GridMap>,
+    ) {
+        if let Some(cell) = click.0.take() {
+            click_cell(
+                cell,
+                &grid,
+                &mut selection,
+                &mut build,
+                &mut upgrade_requests,
+                &mut place_requests,
+            );
+        }
+    }
+
+    #[test]
+    fn clicking_a_tower_selects_it_and_clicking_ground_clears_it() {
+        let mut app = app();
+        app.init_resource::<Selection>()
+            .init_resource::<BuildSelection>()
+            .insert_resource(Click(None))
+            .add_event::<UpgradeRequest>()
+            .add_system(click.before(place_tower));
+        let cell = IVec2::new(1, 1);
+        request(&mut app, TowerType::Cannon, cell);
+        let tower = app.world.resource::<GridMap>().occupant(cell).unwrap();
+
+        app.world.resource_mut::<Click>().0 = Some(cell);
+        app.update();
+        assert_eq!(app.world.resource::<Selection>().tower, Some(tower));
+
+        app.world.resource_mut::<Click>().0 = Some(IVec2::new(3, 3));
+        app.update();
+        assert_eq!(app.world.resource::<Selection>().tower, None);
+        assert_eq!(towers(&mut app).len(), 1);
+    }
+
+    /// Shots fired by each tower so far.
+    #[derive(Default)]
+    struct Shots(bevy::utils::HashMap<Entity, u32>);
+
+    fn count_shots(mut fired_events: EventReader<TowerFired>, mut shots: ResMut<Shots>) {
+        for fired in fired_events.iter() {
+            *shots.0.entry(fired.tower).or_default() += 1;
+        }
+    }
+
+    #[test]
+    fn buffed_towers_fire_faster() {
+        let mut app = combat_app();
+        app.init_resource::<Shots>()
+            .add_system(count_shots.after(tower_shooting));
+        let plain = spawn_loaded_tower(&mut app, TowerType::Cannon);
+        let buffed = spawn_loaded_tower(&mut app, TowerType::Cannon);
+        app.world.entity_mut(buffed).insert(Buffed {
+            fire_rate: 1.0,
+            damage: 0.0,
+        });
+        spawn_target_from(&mut app, plain, 2.0);
+
+        // Ten seconds of a one second cooldown, the buffed tower's running at double time.
+        for _ in 0..100 {
+            app.update();
+        }
+
+        let shots = &app.world.resource::<Shots>().0;
+        assert_eq!(shots[&plain], 10);
+        assert_eq!(shots[&buffed], 20);
+    }
+}
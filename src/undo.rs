@@ -0,0 +1,246 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// A tower that was just built, kept so a misplaced one can be taken back.
+pub struct PlacementRecord {
+    pub tower: Entity,
+    pub cost: u32,
+    pub cell: IVec2,
+    /// How long the tower can still be undone after it has started firing.
+    pub grace: Timer,
+    pub fired: bool,
+}
+
+impl PlacementRecord {
+    /// Towers that haven't fired yet can always be undone, ones that have only until their
+    /// grace runs out.
+    pub fn can_undo(&self) -> bool {
+        !self.fired || !self.grace.finished()
+    }
+}
+
+/// The last few towers placed, newest last. Only the newest can be undone, and it's dropped
+/// from the history either way once tried.
+pub struct PlacementHistory {
+    pub records: Vec<PlacementRecord>,
+    pub limit: usize,
+    /// Seconds a tower stays undoable after it starts firing.
+    pub grace: f32,
+}
+
+impl Default for PlacementHistory {
+    fn default() -> Self {
+        Self {
+            records: Vec::new(),
+            limit: 5,
+            grace: 3.0,
+        }
+    }
+}
+
+impl PlacementHistory {
+    pub fn push(&mut self, tower: Entity, cost: u32, cell: IVec2) {
+        self.records.push(PlacementRecord {
+            tower,
+            cost,
+            cell,
+            grace: Timer::from_seconds(self.grace, false),
+            fired: false,
+        });
+
+        if self.records.len() > self.limit {
+            self.records.remove(0);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+}
+
+/// Asks for the newest tower placement to be taken back.
+pub struct UndoRequest;
+
+pub struct UndoPlugin;
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlacementHistory>()
+            .add_event::<UndoRequest>()
+            .add_system_to_stage(GameStep, track_placements)
+            .add_system_set(
+                SystemSet::on_update(GameState::Next)
+                    .with_system(undo_hotkey)
+                    .with_system(undo_placement.after(undo_hotkey)),
+            );
+    }
+}
+
+/// Starts a tower's grace clock when it first fires, and forgets towers that have been sold.
+fn track_placements(
+    mut history: ResMut<PlacementHistory>,
+    mut fired_events: EventReader<TowerFired>,
+    towers: Query<(), With<Tower>>,
+    time: Res<GameTime>,
+) {
+    let fired: Vec<Entity> = fired_events.iter().map(|fired| fired.tower).collect();
+
+    history
+        .records
+        .retain(|record| towers.contains(record.tower));
+
+    for record in &mut history.records {
+        if record.fired {
+            record.grace.tick(time.delta());
+        } else if fired.contains(&record.tower) {
+            record.fired = true;
+        }
+    }
+}
+
+fn undo_hotkey(
+    mut requests: EventWriter<UndoRequest>,
+    keyboard: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    replay: Res<Replay>,
+) {
+    let ctrl = keyboard.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if replay.accepts_input() && ctrl && bindings.is_action_just_pressed(&keyboard, Action::Undo) {
+        requests.send(UndoRequest);
+    }
+}
+
+/// Pressing undo with Ctrl held takes back the newest tower and refunds what it cost to build.
+/// Towers that have been upgraded, or fired for too long, stay put.
+fn undo_placement(
+    mut commands: Commands,
+    mut requests: EventReader<UndoRequest>,
+    mut history: ResMut<PlacementHistory>,
+    mut selection: ResMut<Selection>,
+    mut currency: ResMut<Currency>,
+    mut grid: ResMut<GridMap>,
+    mut replay: ResMut<Replay>,
+    towers: Query<&Tower>,
+) {
+    if requests.iter().count() == 0 {
+        return;
+    }
+
+    // Recorded whether or not it goes through, since a rejected undo still uses up the record.
+    replay.record(ReplayInput::Undo);
+
+    let record = match history.records.pop() {
+        Some(record) => record,
+        None => return,
+    };
+
+    let tower = match towers.get(record.tower) {
+        Ok(tower) => tower,
+        Err(_) => return,
+    };
+
+    if tower.level > 1 || !record.can_undo() {
+        debug!("Undo rejected, tower has been upgraded or fired for too long");
+        return;
+    }
+
+    currency.gold += record.cost;
+    grid.clear(record.cell);
+    commands.entity(record.tower).despawn_recursive();
+
+    if selection.tower == Some(record.tower) {
+        selection.tower = None;
+    }
+
+    info!(cell = ?record.cell, refund = record.cost, "Undid tower placement");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const CELL: IVec2 = IVec2::new(2, 3);
+    const COST: u32 = 50;
+
+    fn app() -> App {
+        let mut app = App::new();
+
+        app.insert_resource(GameTime::stepped(Duration::from_secs(1)))
+            .init_resource::<PlacementHistory>()
+            .init_resource::<Selection>()
+            .init_resource::<Currency>()
+            .init_resource::<GridMap>()
+            .init_resource::<Replay>()
+            .add_event::<TowerFired>()
+            .add_event::<UndoRequest>()
+            .add_system(track_placements)
+            .add_system(undo_placement.after(track_placements));
+
+        app
+    }
+
+    /// Builds a cannon on `CELL` the way placing one does.
+    fn place(app: &mut App) -> Entity {
+        let config = GameConfig::parse(include_str!("../assets/config.ron")).unwrap();
+        let tower = app
+            .world
+            .spawn()
+            .insert(Tower::new(
+                TowerType::Cannon,
+                &config,
+                &GameAssets::headless(),
+            ))
+            .id();
+
+        app.world.resource_mut::<GridMap>().occupy(CELL, tower);
+        app.world.resource_mut::<Currency>().gold -= COST;
+        app.world
+            .resource_mut::<PlacementHistory>()
+            .push(tower, COST, CELL);
+
+        tower
+    }
+
+    fn undo(app: &mut App) {
+        app.world
+            .resource_mut::<Events<UndoRequest>>()
+            .send(UndoRequest);
+        app.update();
+    }
+
+    #[test]
+    fn undoing_a_placement_refunds_it_and_frees_the_cell() {
+        let mut app = app();
+        let gold = app.world.resource::<Currency>().gold;
+        let tower = place(&mut app);
+
+        undo(&mut app);
+
+        assert_eq!(app.world.resource::<Currency>().gold, gold);
+        assert!(app.world.get_entity(tower).is_none());
+        assert!(app.world.resource::<GridMap>().is_free(CELL));
+    }
+
+    #[test]
+    fn towers_that_have_fired_past_their_grace_stay_put() {
+        let mut app = app();
+        let tower = place(&mut app);
+
+        app.world
+            .resource_mut::<Events<TowerFired>>()
+            .send(TowerFired {
+                tower,
+                position: Vec3::ZERO,
+            });
+        // Three seconds of grace, ticked a second at a time once it has fired.
+        for _ in 0..4 {
+            app.update();
+        }
+        undo(&mut app);
+
+        assert!(app.world.get_entity(tower).is_some());
+        assert_eq!(app.world.resource::<GridMap>().occupant(CELL), Some(tower));
+    }
+}
@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::*;
+
+/// What a tower has at one level. Each `TowerConfig` lists these for every level past the
+/// first, so every tower type upgrades along its own stats.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct TowerStats {
+    pub damage: i32,
+    pub range: f32,
+    pub cooldown: f32,
+    /// Gold needed to reach this level. For level 1 this is the purchase price.
+    pub cost: u32,
+}
+
+impl Tower {
+    /// Moves the tower up one level with `stats`, adding the cost to what's been spent on it.
+    pub fn upgrade(&mut self, stats: TowerStats) {
+        self.level += 1;
+        self.total_spent += stats.cost;
+        self.damage = stats.damage;
+        self.range = stats.range;
+
+        // Keep the same fraction of the cooldown elapsed so upgrading never skips or
+        // restarts a shot that was about to fire.
+        let progress = self.shooting_timer.percent();
+        let cooldown = Duration::from_secs_f32(stats.cooldown);
+        self.shooting_timer.set_duration(cooldown);
+        self.shooting_timer.set_elapsed(cooldown.mul_f32(progress));
+    }
+}
+
+pub struct UpgradeRequest {
+    pub tower: Entity,
+}
+
+pub struct UpgradePlugin;
+
+impl Plugin for UpgradePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<UpgradeRequest>()
+            .add_system_set(SystemSet::on_update(GameState::Next).with_system(upgrade_tower));
+    }
+}
+
+fn upgrade_tower(
+    mut requests: EventReader<UpgradeRequest>,
+    mut towers: Query<(&mut Tower, &Transform)>,
+    mut currency: ResMut<Currency>,
+    mut replay: ResMut<Replay>,
+    grid: Res<GridMap>,
+    config: Res<GameConfig>,
+) {
+    for request in requests.iter() {
+        let (mut tower, transform) = match towers.get_mut(request.tower) {
+            Ok(tower) => tower,
+            Err(_) => continue,
+        };
+
+        let stats = match config.tower(tower.tower_type).level(tower.level + 1) {
+            Some(stats) => stats,
+            None => {
+                debug!("Tower upgrade rejected, already at max level");
+                continue;
+            }
+        };
+
+        if !currency.spend(stats.cost) {
+            debug!("Tower upgrade rejected, not enough gold");
+            continue;
+        }
+
+        tower.upgrade(stats);
+        replay.record(ReplayInput::Upgrade {
+            cell: grid.world_to_cell(transform.translation),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> GameConfig {
+        GameConfig::parse(include_str!("../assets/config.ron")).unwrap()
+    }
+
+    #[test]
+    fn each_tower_type_upgrades_along_its_own_stats() {
+        let config = config();
+
+        for tower_type in TowerType::ALL {
+            let tower_config = config.tower(tower_type);
+            let base = tower_config.level(1).unwrap();
+
+            for level in 2..=tower_config.max_level() {
+                let stats = tower_config.level(level).unwrap();
+                assert!(stats.damage >= base.damage, "{:?} lost damage", tower_type);
+                assert!(stats.range >= base.range, "{:?} lost range", tower_type);
+            }
+
+            assert!(tower_config.level(tower_config.max_level() + 1).is_none());
+        }
+    }
+
+    #[test]
+    fn upgrading_a_sniper_keeps_it_a_sniper() {
+        let config = config();
+        let sniper = config.tower(TowerType::Sniper);
+        let mut tower = Tower::new(TowerType::Sniper, &config, &GameAssets::headless());
+
+        tower.upgrade(sniper.level(2).unwrap());
+
+        assert!(tower.damage > sniper.damage);
+        assert!(tower.range > sniper.range);
+    }
+
+    #[test]
+    fn upgrading_twice_reaches_the_third_level_stats() {
+        let config = config();
+        let cannon = config.tower(TowerType::Cannon);
+        let (second, third) = (cannon.level(2).unwrap(), cannon.level(3).unwrap());
+        let tower = Tower::new(TowerType::Cannon, &config, &GameAssets::headless());
+        let mut app = App::new();
+        app.insert_resource(config)
+            .insert_resource(Currency { gold: 1000 })
+            .init_resource::<Replay>()
+            .init_resource::<GridMap>()
+            .add_event::<UpgradeRequest>()
+            .add_system(upgrade_tower);
+        let tower = app
+            .world
+            .spawn()
+            .insert(tower)
+            .insert(Transform::default())
+            .id();
+
+        for _ in 0..2 {
+            app.world
+                .resource_mut::<Events<UpgradeRequest>>()
+                .send(UpgradeRequest { tower });
+            app.update();
+        }
+
+        let tower = app.world.get::<Tower>(tower).unwrap();
+        assert_eq!(tower.level, 3);
+        assert_eq!(tower.damage, third.damage);
+        assert_eq!(tower.range, third.range);
+        assert_eq!(
+            app.world.resource::<Currency>().gold,
+            1000 - second.cost - third.cost
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+/// In-game seconds the current run has lasted, pauses left out.
+#[derive(Default)]
+pub struct RunTime {
+    pub seconds: f32,
+}
+
+impl RunTime {
+    /// The time as minutes and seconds, like `12:05`.
+    pub fn label(&self) -> String {
+        let total = self.seconds as u32;
+        format!("{}:{:02}", total / 60, total % 60)
+    }
+}
+
+#[derive(Component)]
+pub struct VictoryUi;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VictoryButton {
+    Restart,
+    MainMenu,
+}
+
+pub struct VictoryPlugin;
+
+impl Plugin for VictoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunTime>()
+            .add_system_set(SystemSet::on_enter(GameState::Next).with_system(reset_run_time))
+            .add_system_to_stage(GameStep, tick_run_time)
+            .add_system_set(SystemSet::on_update(GameState::Next).with_system(check_victory))
+            .add_system_set(
+                SystemSet::on_enter(GameState::Victory)
+                    .with_system(spawn_victory_ui.after(RecordRun)),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Victory).with_system(victory_buttons))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Victory)
+                    .with_system(despawn_victory_ui)
+                    .with_system(cleanup_gameplay_entities)
+                    .with_system(reset_game_resources),
+            );
+    }
+}
+
+fn reset_run_time(mut run_time: ResMut<RunTime>) {
+    run_time.seconds = 0.0;
+}
+
+fn tick_run_time(mut run_time: ResMut<RunTime>, time: Res<GameTime>) {
+    run_time.seconds += time.delta_seconds();
+}
+
+/// The run is won once every wave has been completed. A wave only completes once all of its
+/// enemies are gone, so there's nothing left on the field by then.
+fn check_victory(
+    mut state: ResMut<State<GameState>>,
+    wave: Res<Wave>,
+    config: Res<WaveConfig>,
+    lives: Res<Lives>,
+    targets: Query<(), With<Target>>,
+) {
+    if wave.index < config.waves.len() || !targets.is_empty() || lives.count == 0 {
+        return;
+    }
+
+    if let Err(error) = state.set(GameState::Victory) {
+        warn!(?error, "Failed to enter victory state");
+    }
+}
+
+fn spawn_victory_ui(
+    mut commands: Commands,
+    assets: Res<GameAssets>,
+    leaderboard: Res<Leaderboard>,
+    score: Res<Score>,
+    wave: Res<Wave>,
+    run_time: Res<RunTime>,
+) {
+    let stats = format!(
+        "Score {}   Waves {}   Time {}",
+        score.value,
+        wave.index,
+        run_time.label()
+    );
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(VictoryUi)
+        .insert(Name::new("VictoryUi"))
+        .with_children(|commands| {
+            commands.spawn_bundle(TextBundle::from_section(
+                "Victory!",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 80.0,
+                    color: Color::GOLD,
+                },
+            ));
+            commands.spawn_bundle(TextBundle::from_section(
+                stats,
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+            ));
+            spawn_button(commands, &assets.font, "Restart", VictoryButton::Restart);
+            spawn_button(commands, &assets.font, "Main Menu", VictoryButton::MainMenu);
+            spawn_leaderboard(commands, &assets.font, &leaderboard);
+        });
+}
+
+fn victory_buttons(
+    buttons: Query<(&Interaction, &VictoryButton), Changed<Interaction>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+
+        let next = match button {
+            VictoryButton::Restart => GameState::Next,
+            VictoryButton::MainMenu => GameState::MainMenu,
+        };
+
+        if let Err(error) = state.set(next) {
+            warn!(?error, "Failed to leave victory screen");
+        }
+    }
+}
+
+fn despawn_victory_ui(mut commands: Commands, ui: Query<Entity, With<VictoryUi>>) {
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+
+        app.insert_resource(WaveConfig {
+            waves: (0..2)
+                .map(|_| WaveDefinition {
+                    count: 1,
+                    spawn_interval: 1.0,
+                    enemies: vec![EnemyType::Normal],
+                    lanes: vec![],
+                    boss: false,
+                })
+                .collect(),
+            health_scaling: 0.0,
+            reward_scaling: 0.0,
+        })
+        .init_resource::<Wave>()
+        .init_resource::<Lives>()
+        .add_state(GameState::Next)
+        .add_system_set(SystemSet::on_update(GameState::Next).with_system(check_victory));
+
+        app
+    }
+
+    fn state(app: &App) -> GameState {
+        app.world.resource::<State<GameState>>().current().clone()
+    }
+
+    #[test]
+    fn clearing_the_final_wave_wins_the_run() {
+        let mut app = app();
+        app.world.resource_mut::<Wave>().index = 1;
+        app.update();
+        assert_eq!(state(&app), GameState::Next);
+
+        // The last wave is done spawning, but one of its enemies is still out there.
+        app.world.resource_mut::<Wave>().index = 2;
+        let straggler = app
+            .world
+            .spawn()
+            .insert(Target {
+                speed: 1.0,
+                reward: 1,
+            })
+            .id();
+        app.update();
+        assert_eq!(state(&app), GameState::Next);
+
+        app.world.despawn(straggler);
+        app.update();
+        assert_eq!(state(&app), GameState::Victory);
+    }
+
+    #[test]
+    fn running_out_of_lives_on_the_final_wave_is_no_victory() {
+        let mut app = app();
+        app.world.resource_mut::<Wave>().index = 2;
+        app.world.resource_mut::<Lives>().count = 0;
+
+        app.update();
+
+        assert_eq!(state(&app), GameState::Next);
+    }
+
+    #[test]
+    fn run_times_read_as_minutes_and_seconds() {
+        assert_eq!(RunTime { seconds: 725.9 }.label(), "12:05");
+    }
+}
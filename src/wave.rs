@@ -0,0 +1,454 @@
+use bevy::prelude::*;
+
+use crate::*;
+
+pub struct WaveDefinition {
+    pub count: u32,
+    pub spawn_interval: f32,
+    /// Enemy types spawned in order, repeating once the list runs out.
+    pub enemies: Vec<EnemyType>,
+    /// Which of the `Paths` each enemy is sent down, in turn and repeating like `enemies`. Empty
+    /// sends every enemy down the first path.
+    pub lanes: Vec<usize>,
+    /// Replaces the last enemy of the wave with a boss.
+    pub boss: bool,
+}
+
+pub struct WaveConfig {
+    pub waves: Vec<WaveDefinition>,
+    /// Extra enemy health per wave, as a fraction of base health. Wave 0 is always base stats.
+    pub health_scaling: f32,
+    /// Extra kill reward per wave, as a fraction of the base reward.
+    pub reward_scaling: f32,
+}
+
+impl WaveConfig {
+    pub fn scaling(&self, wave_index: usize) -> EnemyScaling {
+        let wave = wave_index as f32;
+
+        EnemyScaling {
+            health: 1.0 + self.health_scaling * wave,
+            reward: 1.0 + self.reward_scaling * wave,
+            ..default()
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Wave {
+    pub index: usize,
+    pub spawned: u32,
+    pub spawn_timer: Timer,
+    pub active: bool,
+}
+
+/// The pause between waves. Skipping it with a `StartWaveEarly` pays `bonus_per_second` gold
+/// for every second left on the clock.
+pub struct WaveCountdown {
+    pub timer: Timer,
+    pub bonus_per_second: f32,
+}
+
+impl Default for WaveCountdown {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(10.0, false),
+            bonus_per_second: 2.0,
+        }
+    }
+}
+
+impl WaveCountdown {
+    pub fn remaining_secs(&self) -> f32 {
+        (self.timer.duration() - self.timer.elapsed()).as_secs_f32()
+    }
+}
+
+pub struct StartWaveEarly;
+
+/// Gold paid for clearing a wave: `flat`, plus `per_life` for every life the player has left.
+pub struct WaveBonus {
+    pub flat: u32,
+    pub per_life: u32,
+}
+
+impl Default for WaveBonus {
+    fn default() -> Self {
+        Self {
+            flat: 20,
+            per_life: 2,
+        }
+    }
+}
+
+impl WaveBonus {
+    pub fn amount(&self, lives: u32) -> u32 {
+        self.flat + self.per_life * lives
+    }
+}
+
+/// What happened during the current wave, reset whenever a wave starts. Still holds the last
+/// wave's numbers between waves, for its summary.
+#[derive(Default, Clone, Copy, Debug)]
+pub struct WaveStats {
+    pub killed: u32,
+    pub leaked: u32,
+    /// Kill rewards plus the clear bonus.
+    pub gold_earned: u32,
+    pub bonus: u32,
+}
+
+pub struct WaveStarted {
+    pub index: usize,
+}
+
+pub struct WaveCompleted {
+    pub index: usize,
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AwardWaveBonus;
+
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Wave>()
+            .init_resource::<WaveCountdown>()
+            .init_resource::<WaveBonus>()
+            .init_resource::<WaveStats>()
+            .add_event::<StartWaveEarly>()
+            .add_event::<WaveStarted>()
+            .add_event::<WaveCompleted>()
+            .add_system_set(SystemSet::on_update(GameState::Next).with_system(start_wave_early))
+            .add_system_set_to_stage(
+                GameStep,
+                SystemSet::new()
+                    .with_system(wave_spawner)
+                    .with_system(track_wave_stats.before(award_wave_bonus))
+                    .with_system(award_wave_bonus.label(AwardWaveBonus).after(wave_spawner)),
+            );
+    }
+}
+
+/// Ends the countdown right away so `wave_spawner` starts the next wave this frame. Requests
+/// during a wave, or once every wave is done, are ignored.
+fn start_wave_early(
+    mut requests: EventReader<StartWaveEarly>,
+    mut countdown: ResMut<WaveCountdown>,
+    mut currency: ResMut<Currency>,
+    mut replay: ResMut<Replay>,
+    wave: Res<Wave>,
+    config: Res<WaveConfig>,
+) {
+    if requests.iter().count() == 0 {
+        return;
+    }
+
+    if wave.active || wave.index >= config.waves.len() || countdown.timer.finished() {
+        return;
+    }
+
+    let bonus = (countdown.remaining_secs() * countdown.bonus_per_second).round() as u32;
+    currency.gold += bonus;
+
+    let duration = countdown.timer.duration();
+    countdown.timer.set_elapsed(duration);
+    replay.record(ReplayInput::StartWaveEarly);
+    info!(bonus, "Started wave early");
+}
+
+fn track_wave_stats(
+    mut stats: ResMut<WaveStats>,
+    mut started_events: EventReader<WaveStarted>,
+    mut death_events: EventReader<TargetDeath>,
+    mut leak_events: EventReader<TargetLeak>,
+) {
+    if started_events.iter().count() > 0 {
+        *stats = WaveStats::default();
+    }
+
+    for death in death_events.iter() {
+        stats.killed += 1;
+        stats.gold_earned += death.reward;
+    }
+
+    stats.leaked += leak_events.iter().count() as u32;
+}
+
+fn award_wave_bonus(
+    mut completed_events: EventReader<WaveCompleted>,
+    mut stats: ResMut<WaveStats>,
+    mut currency: ResMut<Currency>,
+    bonus: Res<WaveBonus>,
+    lives: Res<Lives>,
+) {
+    for completed in completed_events.iter() {
+        let amount = bonus.amount(lives.count);
+        currency.gold += amount;
+        stats.bonus = amount;
+        stats.gold_earned += amount;
+        info!(wave = completed.index, amount, "Wave cleared");
+    }
+}
+
+fn wave_spawner(
+    mut commands: Commands,
+    mut wave: ResMut<Wave>,
+    mut countdown: ResMut<WaveCountdown>,
+    mut started_events: EventWriter<WaveStarted>,
+    mut completed_events: EventWriter<WaveCompleted>,
+    targets: Query<(), With<Target>>,
+    config: Res<WaveConfig>,
+    difficulty: Res<DifficultySettings>,
+    enemy_stats: Res<EnemyStats>,
+    paths: Res<Paths>,
+    time: Res<GameTime>,
+) {
+    let definition = match config.waves.get(wave.index) {
+        Some(definition) => definition,
+        None => return,
+    };
+
+    if !wave.active {
+        countdown.timer.tick(time.delta());
+
+        if !countdown.timer.finished() {
+            return;
+        }
+
+        wave.active = true;
+        wave.spawned = 0;
+        wave.spawn_timer = Timer::from_seconds(definition.spawn_interval, true);
+        started_events.send(WaveStarted { index: wave.index });
+
+        // Nothing to cycle through, so only the boss, if there is one, is left to spawn.
+        if definition.enemies.is_empty() {
+            warn!(
+                wave = wave.index,
+                "Wave has no enemy types, skipping its spawns"
+            );
+            wave.spawned = definition.count.saturating_sub(definition.boss as u32);
+        }
+    }
+
+    if wave.spawned < definition.count {
+        wave.spawn_timer.tick(time.delta());
+
+        if wave.spawn_timer.just_finished() {
+            let enemies = &definition.enemies;
+            let enemy_type = if definition.boss && wave.spawned + 1 == definition.count {
+                EnemyType::Boss
+            } else {
+                enemies[wave.spawned as usize % enemies.len()]
+            };
+
+            let lanes = &definition.lanes;
+            let lane = if lanes.is_empty() {
+                0
+            } else {
+                lanes[wave.spawned as usize % lanes.len()]
+            };
+
+            spawn_enemy(
+                &mut commands,
+                &enemy_stats,
+                enemy_type,
+                paths.get(lane).start(),
+                lane,
+                1,
+                difficulty.scale(config.scaling(wave.index)),
+            );
+
+            wave.spawned += 1;
+        }
+    } else if targets.is_empty() {
+        completed_events.send(WaveCompleted { index: wave.index });
+        wave.index += 1;
+        wave.active = false;
+        countdown.timer.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn app(waves: Vec<WaveDefinition>) -> App {
+        let mut app = App::new();
+
+        app.insert_resource(GameConfig::parse(include_str!("../assets/config.ron")).unwrap())
+            .insert_resource(GameAssets::headless())
+            .insert_resource(WaveConfig {
+                waves,
+                health_scaling: 0.2,
+                reward_scaling: 0.1,
+            })
+            .insert_resource(Paths::new(vec![Path::new(
+                "Straight",
+                vec![Vec3::ZERO, Vec3::new(10.0, 0.0, 0.0)],
+            )]))
+            .insert_resource(GameTime::stepped(Duration::from_secs_f32(0.5)))
+            .insert_resource(WaveCountdown {
+                timer: Timer::from_seconds(0.0, false),
+                bonus_per_second: 2.0,
+            })
+            .init_resource::<Wave>()
+            .init_resource::<DifficultySettings>()
+            .add_event::<WaveStarted>()
+            .add_event::<WaveCompleted>()
+            .add_startup_system(setup_enemy_stats)
+            .add_system(wave_spawner);
+
+        app
+    }
+
+    fn wave(count: u32) -> WaveDefinition {
+        WaveDefinition {
+            count,
+            spawn_interval: 1.0,
+            enemies: vec![EnemyType::Normal],
+            lanes: vec![],
+            boss: false,
+        }
+    }
+
+    fn targets(app: &mut App) -> usize {
+        app.world
+            .query_filtered::<(), With<Target>>()
+            .iter(&app.world)
+            .count()
+    }
+
+    #[test]
+    fn spawner_sends_enemies_one_interval_apart() {
+        let mut app = app(vec![wave(5)]);
+
+        // Three seconds into a wave spawning one enemy a second.
+        for _ in 0..6 {
+            app.update();
+        }
+
+        assert_eq!(targets(&mut app), 3);
+        let wave = app.world.resource::<Wave>();
+        assert!(wave.active);
+        assert_eq!(wave.spawned, 3);
+    }
+
+    fn first_enemy_health(wave_index: usize) -> i32 {
+        let mut app = app((0..=wave_index).map(|_| wave(1)).collect());
+        app.world.resource_mut::<Wave>().index = wave_index;
+
+        for _ in 0..2 {
+            app.update();
+        }
+
+        app.world
+            .query_filtered::<&Health, With<Target>>()
+            .single(&app.world)
+            .max
+    }
+
+    #[test]
+    fn enemy_health_scales_with_the_wave() {
+        let wave_0 = first_enemy_health(0);
+        let wave_5 = first_enemy_health(5);
+
+        // 20% more per wave, so wave 5 enemies have double health.
+        assert_eq!(wave_5, wave_0 * 2);
+    }
+
+    #[test]
+    fn starting_early_begins_the_wave_and_pays_for_the_time_left() {
+        let mut app = app(vec![wave(3)]);
+        app.insert_resource(WaveCountdown::default())
+            .init_resource::<Currency>()
+            .init_resource::<Replay>()
+            .add_event::<StartWaveEarly>()
+            .add_system(start_wave_early.before(wave_spawner));
+
+        // Half a second into the ten second countdown.
+        app.update();
+        assert!(!app.world.resource::<Wave>().active);
+
+        app.world
+            .resource_mut::<Events<StartWaveEarly>>()
+            .send(StartWaveEarly);
+        app.update();
+
+        assert!(app.world.resource::<Wave>().active);
+        assert_eq!(app.world.resource::<Currency>().gold, 100 + 19);
+        assert_eq!(app.world.resource::<Events<WaveStarted>>().len(), 1);
+    }
+
+    #[test]
+    fn clearing_a_wave_pays_its_bonus_and_counts_the_kills() {
+        let mut app = app(vec![wave(2)]);
+        app.init_resource::<Currency>()
+            .init_resource::<Lives>()
+            .init_resource::<WaveBonus>()
+            .init_resource::<WaveStats>()
+            .add_event::<TargetDeath>()
+            .add_event::<TargetLeak>()
+            .add_system(track_wave_stats.before(award_wave_bonus))
+            .add_system(award_wave_bonus.after(wave_spawner));
+
+        for _ in 0..4 {
+            app.update();
+        }
+        assert_eq!(targets(&mut app), 2);
+
+        let spawned: Vec<_> = app
+            .world
+            .query_filtered::<Entity, With<Target>>()
+            .iter(&app.world)
+            .collect();
+        for entity in spawned {
+            app.world.despawn(entity);
+            app.world
+                .resource_mut::<Events<TargetDeath>>()
+                .send(TargetDeath {
+                    entity,
+                    enemy_type: EnemyType::Normal,
+                    position: Vec3::ZERO,
+                    reward: 5,
+                });
+        }
+        app.update();
+
+        // 20 for the clear and 2 for each of the 10 lives left.
+        let stats = *app.world.resource::<WaveStats>();
+        assert_eq!(app.world.resource::<Currency>().gold, 100 + 40);
+        assert_eq!(stats.killed, 2);
+        assert_eq!(stats.bonus, 40);
+        assert_eq!(stats.gold_earned, 2 * 5 + 40);
+    }
+
+    #[test]
+    fn waves_without_enemy_types_are_skipped_apart_from_their_boss() {
+        let empty = |boss| WaveDefinition {
+            enemies: vec![],
+            boss,
+            ..wave(3)
+        };
+        let mut app = app(vec![empty(false), empty(true)]);
+
+        app.update();
+        assert_eq!(targets(&mut app), 0);
+        assert_eq!(app.world.resource::<Wave>().index, 1);
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        // Wave 1's boss, with its 60 health scaled up 20%.
+        let health = app
+            .world
+            .query_filtered::<&Health, With<Target>>()
+            .single(&app.world)
+            .max;
+        assert_eq!(health, 72);
+        assert!(app.world.resource::<Wave>().active);
+    }
+}